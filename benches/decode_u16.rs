@@ -0,0 +1,38 @@
+use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group, criterion_main};
+use ome_bioformats_rs::format_in::ByteOrder;
+use ome_bioformats_rs::format_in::pixel::decode_u16;
+
+// 32 MB of samples, a mid-sized plane
+const BYTES: usize = 32 << 20;
+
+// The sample by sample decoding decode_u16 replaced
+fn decode_by_sample(bytes: &[u8], order: ByteOrder) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|s| match order {
+            ByteOrder::LE => u16::from_le_bytes([s[0], s[1]]),
+            ByteOrder::BE => u16::from_be_bytes([s[0], s[1]]),
+        })
+        .collect()
+}
+
+// Swapped order takes the SSSE3 path when built with --features simd
+fn decode(c: &mut Criterion) {
+    let bytes = (0..BYTES).map(|i| i as u8).collect::<Vec<_>>();
+    let mut group = c.benchmark_group("decode_u16");
+    group.throughput(Throughput::Bytes(BYTES as u64));
+
+    for (name, order) in [("le", ByteOrder::LE), ("be", ByteOrder::BE)] {
+        group.bench_with_input(BenchmarkId::new("by_sample", name), &bytes, |b, bytes| {
+            b.iter(|| decode_by_sample(black_box(bytes), order))
+        });
+        group.bench_with_input(BenchmarkId::new("decode_u16", name), &bytes, |b, bytes| {
+            b.iter(|| decode_u16(black_box(bytes), order))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
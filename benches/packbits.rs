@@ -0,0 +1,48 @@
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+use ome_bioformats_rs::format_in::tiff::compression::{Compression, PackBitsRows};
+
+const ROW_LEN: usize = 4096;
+const ROWS: usize = 256;
+
+// A strip of rows alternating between 100 byte runs and 28 literal bytes,
+// as PackBits encoders produce for images with flat areas and noise
+fn encoded_strip() -> Vec<u8> {
+    let mut out = Vec::new();
+    for row in 0..ROWS {
+        for block in 0..ROW_LEN / 128 {
+            // 100 repeats as a run of 97 and one of 3
+            out.extend([(257 - 97) as u8, row as u8, (257 - 3) as u8, row as u8]);
+            out.push(27);
+            out.extend((0..28).map(|i| (i * block) as u8));
+        }
+    }
+    out
+}
+
+fn decode(c: &mut Criterion) {
+    let input = encoded_strip();
+    let mut out = vec![0; ROW_LEN * ROWS];
+    let mut group = c.benchmark_group("packbits");
+    group.throughput(Throughput::Bytes(out.len() as u64));
+
+    group.bench_function("unpackbits", |b| {
+        b.iter(|| {
+            let len = input.len() as u64;
+            Compression::unpackbits(black_box(&input), len, &mut out, (ROW_LEN * ROWS) as u64)
+        })
+    });
+
+    group.bench_function("rows", |b| {
+        b.iter(|| {
+            let mut rows = PackBitsRows::new(black_box(&input), ROW_LEN);
+            for _ in 0..ROWS {
+                black_box(rows.next_row().unwrap());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
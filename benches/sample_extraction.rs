@@ -0,0 +1,77 @@
+use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group, criterion_main};
+use ome_bioformats_rs::format_in::layout::copy_sample;
+use ome_bioformats_rs::format_in::tiff_reader::TiffReader;
+use ome_bioformats_rs::format_in::{FormatReader, Loc, Region};
+
+// One sample of every pixel of a 2048 x 2048 chunky plane of 4 samples
+const PIXELS: usize = 2048 * 2048;
+const SAMPLES: usize = 4;
+
+// The byte by byte extraction copy_sample replaced
+fn copy_sample_by_byte(
+    chunky: &[u8],
+    samples: usize,
+    s: usize,
+    bytes_per_sample: usize,
+) -> Vec<u8> {
+    chunky
+        .chunks_exact(bytes_per_sample)
+        .skip(s)
+        .step_by(samples)
+        .flatten()
+        .map(u8::to_owned)
+        .collect()
+}
+
+fn extraction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sample_extraction");
+
+    for bytes_per_sample in [1, 2, 4] {
+        let chunky = (0..PIXELS * SAMPLES * bytes_per_sample)
+            .map(|b| b as u8)
+            .collect::<Vec<_>>();
+        let bytes_per_pixel = SAMPLES * bytes_per_sample;
+        group.throughput(Throughput::Bytes((PIXELS * bytes_per_sample) as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("by_byte", bytes_per_sample),
+            &chunky,
+            |b, chunky| {
+                b.iter(|| copy_sample_by_byte(black_box(chunky), SAMPLES, 1, bytes_per_sample))
+            },
+        );
+
+        let mut out = vec![0; PIXELS * bytes_per_sample];
+        group.bench_with_input(
+            BenchmarkId::new("copy_sample", bytes_per_sample),
+            &chunky,
+            |b, chunky| {
+                b.iter(|| {
+                    copy_sample(
+                        black_box(chunky),
+                        bytes_per_pixel,
+                        bytes_per_sample,
+                        bytes_per_sample,
+                        &mut out,
+                    )
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+// A whole channel of the 4 channel, 16 bit example image
+fn open_bytes(c: &mut Criterion) {
+    let mut reader = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+    let region = Region::full(1979, 1979);
+    let mut buf = vec![0; 1979 * 1979 * 2];
+
+    c.bench_function("open_bytes_into_chunky_channel", |b| {
+        b.iter(|| reader.open_bytes_into(Loc::new(0, 1, 0), region, &mut buf))
+    });
+}
+
+criterion_group!(benches, extraction, open_bytes);
+criterion_main!(benches);
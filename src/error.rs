@@ -0,0 +1,150 @@
+use std::io;
+
+use crate::format_in::Region;
+use crate::format_in::tiff::ifd::Tag;
+
+// Failures with enough context to diagnose a bad file. Readers keep
+// returning io::Result; these travel inside the io::Error and can be
+// recovered with OmeError::from_io
+#[derive(Debug, thiserror::Error)]
+pub enum OmeError {
+    #[error("Not a TIFF file: {0}")]
+    NotTiff(&'static str),
+
+    #[error("Unknown field type {kind} for tag {tag} in IFD at offset {ifd_offset}")]
+    UnknownType {
+        ifd_offset: u64,
+        tag: u16,
+        kind: u16,
+    },
+
+    #[error("IFD {index} out of range, the file has {count}")]
+    IfdOutOfRange { index: u64, count: u64 },
+
+    #[error("Missing or malformed {tag:?} in IFD at offset {ifd_offset}")]
+    BadTag { ifd_offset: u64, tag: Tag },
+
+    #[error("Read {read} of {expected} bytes at offset {offset}")]
+    Truncated {
+        offset: u64,
+        read: usize,
+        expected: usize,
+    },
+
+    #[error("Strip {strip} out of range in IFD at offset {ifd_offset}")]
+    StripOutOfRange { ifd_offset: u64, strip: u64 },
+
+    #[error("Unsupported compression {codec} in IFD at offset {ifd_offset}")]
+    UnsupportedCodec { ifd_offset: u64, codec: u16 },
+
+    #[error("Unsupported {bits} bit samples of format {format} in IFD at offset {ifd_offset}")]
+    UnsupportedSampleType {
+        ifd_offset: u64,
+        bits: u16,
+        format: u16,
+    },
+
+    #[error("Corrupt {codec} data in strip {strip} at offset {offset}: {reason}")]
+    Codec {
+        codec: &'static str,
+        strip: u64,
+        offset: u64,
+        reason: String,
+    },
+
+    #[error("{axis} index {index} out of bounds, the series has {size}")]
+    OutOfBounds {
+        axis: &'static str,
+        index: u64,
+        size: u64,
+    },
+
+    #[error("Reading needs {needed} bytes, more than the limit of {limit}")]
+    TooLarge { needed: u64, limit: u64 },
+
+    #[error("Plane ({z}, {c}, {t}) of series {series} is missing")]
+    PlaneMissing { series: u64, z: u64, c: u64, t: u64 },
+
+    #[error("Operation cancelled")]
+    Cancelled,
+
+    #[error("Region {region:?} doesn't fit in the {size_x}x{size_y} plane")]
+    InvalidRegion {
+        region: Region,
+        size_x: u64,
+        size_y: u64,
+    },
+}
+
+impl OmeError {
+    // The OmeError behind an io::Error, where there is one
+    pub fn from_io(e: &io::Error) -> Option<&OmeError> {
+        e.get_ref()?.downcast_ref()
+    }
+}
+
+impl From<OmeError> for io::Error {
+    fn from(e: OmeError) -> Self {
+        io::Error::other(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff::TiffParser;
+    use crate::format_in::tiff_reader::TiffReader;
+    use crate::format_in::{FormatReader, Loc};
+
+    #[test]
+    fn errors_carry_context() {
+        let mut parser = TiffParser::new("assets/example_valid.tiff".into()).unwrap();
+        let err = parser.nth_ifd(3).unwrap_err();
+
+        assert!(matches!(
+            OmeError::from_io(&err),
+            Some(OmeError::IfdOutOfRange { index: 3, count: 1 })
+        ));
+
+        let err = Region::new(0, 0, 5, 5).validate(4, 4).unwrap_err();
+        assert!(matches!(
+            OmeError::from_io(&err),
+            Some(OmeError::InvalidRegion { size_x: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn loc_out_of_bounds() {
+        let mut reader = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let md = reader.metadata().unwrap();
+
+        assert!(
+            md.validate(0, &Loc::new(0, 3, 0), Region::full(1979, 1979))
+                .is_ok()
+        );
+
+        let err = md
+            .validate(0, &Loc::new(0, 4, 0), Region::full(10, 10))
+            .unwrap_err();
+        assert!(matches!(
+            OmeError::from_io(&err),
+            Some(OmeError::OutOfBounds {
+                axis: "C",
+                index: 4,
+                size: 4
+            })
+        ));
+
+        let err = md
+            .validate(0, &Loc::new(0, 0, 0), Region::full(1980, 10))
+            .unwrap_err();
+        assert!(matches!(
+            OmeError::from_io(&err),
+            Some(OmeError::InvalidRegion { .. })
+        ));
+
+        // The offsets count as well as the size
+        let offset = Region::new(1970, 0, 10, 10);
+        assert!(md.validate(0, &Loc::new(0, 0, 0), offset).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use std::io::{self, Error};
+use std::path::PathBuf;
+
+use crate::format_in::{Axis, Dim, Metadata};
+
+// The file, series and plane index within that file holding a plane of the
+// aggregated dataset
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaneSource {
+    pub file: PathBuf,
+    pub series: u64,
+    pub plane: u64,
+}
+
+// Metadata of many files (e.g. one per timepoint) merged into a single
+// store, remembering which file contributed each plane
+#[derive(Debug)]
+pub struct AggregatedMetadata {
+    metadata: Metadata,
+    files: Vec<PathBuf>,
+    // Indexed by series, then by plane index of the aggregated series
+    sources: Vec<Vec<PlaneSource>>,
+}
+
+impl AggregatedMetadata {
+    // Concatenate the series of every file along `axis`. Files must hold the
+    // same number of series with matching sizes along every other axis and
+    // matching pixel types; the first file's metadata wins where they
+    // disagree, other files' original metadata is kept under
+    // "<file name> <key>".
+    pub fn new(parts: Vec<(PathBuf, Metadata)>, axis: Axis) -> io::Result<Self> {
+        let mut parts = parts.into_iter();
+        let (first_file, mut metadata) =
+            parts.next().ok_or(Error::other("No files to aggregate"))?;
+
+        // Per file and series: where the file starts along the axis, and the
+        // layout needed to find a plane within it
+        let layout = |md: &Metadata| {
+            md.series
+                .iter()
+                .map(|s| (s.dimension_order, s.dimensions.zct()))
+                .collect::<Vec<_>>()
+        };
+        let mut files = vec![first_file];
+        let mut offsets = vec![vec![0; metadata.series.len()]];
+        let mut layouts = vec![layout(&metadata)];
+
+        for (file, md) in parts {
+            if md.series.len() != metadata.series.len() {
+                return Err(Error::other(format!(
+                    "{} has {} series, expected {}",
+                    file.display(),
+                    md.series.len(),
+                    metadata.series.len()
+                )));
+            }
+
+            let mut file_offsets = Vec::new();
+            for (i, (agg, s)) in metadata.series.iter_mut().zip(md.series.iter()).enumerate() {
+                let (a, b) = (&agg.dimensions, &s.dimensions);
+
+                // Every size but the one concatenated along must match, or
+                // planes would be dropped or misplaced
+                let mut sizes = vec![
+                    ("SizeX", a.size_x(), b.size_x()),
+                    ("SizeY", a.size_y(), b.size_y()),
+                ];
+                for (other, name) in [(Axis::Z, "SizeZ"), (Axis::C, "SizeC"), (Axis::T, "SizeT")] {
+                    if other != axis {
+                        sizes.push((name, other.pick(a.zct()), other.pick(b.zct())));
+                    }
+                }
+                if let Some((name, expected, found)) = sizes.into_iter().find(|(_, e, f)| e != f) {
+                    return Err(Error::other(format!(
+                        "{} has {name} {found} in series {i}, {} has {expected}",
+                        file.display(),
+                        files[0].display()
+                    )));
+                }
+
+                // Channels concatenated along C may each have their own type
+                let types_match = match axis {
+                    Axis::C => agg.pixel_types.first() == s.pixel_types.first(),
+                    _ => agg.pixel_types == s.pixel_types,
+                };
+                if !types_match {
+                    return Err(Error::other(format!(
+                        "{} has pixel types {:?} in series {i}, {} has {:?}",
+                        file.display(),
+                        s.pixel_types,
+                        files[0].display(),
+                        agg.pixel_types
+                    )));
+                }
+
+                let offset = axis.pick(agg.dimensions.zct());
+                file_offsets.push(offset);
+
+                let (z, c, t) = a.zct();
+                let (dz, dc, dt) = b.zct();
+                agg.dimensions = match axis {
+                    Axis::Z => Dim::new(a.size_x(), a.size_y(), z + dz, c, t),
+                    Axis::C => Dim::new(a.size_x(), a.size_y(), z, c + dc, t),
+                    Axis::T => Dim::new(a.size_x(), a.size_y(), z, c, t + dt),
+                };
+                if axis == Axis::C {
+                    agg.pixel_types.extend_from_slice(&s.pixel_types);
+                    agg.channels.extend(s.channels.iter().cloned());
+                }
+
+                agg.planes.extend(s.planes.iter().cloned().map(|mut p| {
+                    match axis {
+                        Axis::Z => p.the_z += offset,
+                        Axis::C => p.the_c += offset,
+                        Axis::T => p.the_t += offset,
+                    }
+                    p
+                }));
+            }
+
+            let name = file
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            for (k, v) in md.global.original_metadata.iter() {
+                if metadata.global.original_metadata.get(k) != Some(v) {
+                    metadata
+                        .global
+                        .original_metadata
+                        .insert(format!("{name} {k}"), v.clone());
+                }
+            }
+
+            files.push(file);
+            offsets.push(file_offsets);
+            layouts.push(layout(&md));
+        }
+
+        // Resolve every aggregated plane back to the file it came from
+        let mut sources = Vec::with_capacity(metadata.series.len());
+        for (i, agg) in metadata.series.iter().enumerate() {
+            let (z, c, t) = agg.dimensions.zct();
+            let n_planes = z * c * t;
+            let mut series_sources = Vec::with_capacity(n_planes as usize);
+
+            for index in 0..n_planes {
+                let (z, c, t) = agg.index_to_zct(index)?;
+
+                // Offsets increase with the file index, so take the last
+                // file starting at or before this position
+                let pos = axis.pick((z, c, t));
+                let k = offsets.iter().rposition(|o| o[i] <= pos).unwrap_or(0);
+                let local = pos - offsets[k][i];
+                let zct = match axis {
+                    Axis::Z => (local, c, t),
+                    Axis::C => (z, local, t),
+                    Axis::T => (z, c, local),
+                };
+
+                let (order, sizes) = layouts[k][i];
+                series_sources.push(PlaneSource {
+                    file: files[k].clone(),
+                    series: i as u64,
+                    plane: order.zct_to_index(sizes, zct)?,
+                });
+            }
+
+            sources.push(series_sources);
+        }
+
+        Ok(AggregatedMetadata {
+            metadata,
+            files,
+            sources,
+        })
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    pub fn into_metadata(self) -> Metadata {
+        self.metadata
+    }
+
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    // Which file, series and plane holds plane (z, c, t) of a series
+    pub fn source(&self, series: u64, z: u64, c: u64, t: u64) -> io::Result<&PlaneSource> {
+        let index = self.metadata.series(series)?.zct_to_index(z, c, t)?;
+
+        self.sources
+            .get(series as usize)
+            .and_then(|s| s.get(index as usize))
+            .ok_or(Error::other("Invalid plane"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::{ByteOrder, GlobalMetadata, PixelType, SeriesMetadata};
+
+    fn timepoints(n: u64) -> Metadata {
+        let mut series = SeriesMetadata::new(Dim::new(4, 4, 2, 1, n), vec![PixelType::U8], false);
+        series.planes = (0..n).map(|t| crate::ome::Plane::new(0, 0, t)).collect();
+
+        let mut global = GlobalMetadata::new(ByteOrder::LE);
+        global
+            .original_metadata
+            .insert("Camera".into(), format!("cam{n}"));
+
+        Metadata {
+            global,
+            series: vec![series],
+        }
+    }
+
+    #[test]
+    fn aggregate_along_t() {
+        let parts = vec![
+            (PathBuf::from("t0.tif"), timepoints(2)),
+            (PathBuf::from("t2.tif"), timepoints(3)),
+        ];
+
+        let agg = AggregatedMetadata::new(parts, Axis::T).unwrap();
+        let series = agg.metadata().series(0).unwrap();
+
+        assert_eq!(series.core().size_t, 5);
+        assert_eq!(series.planes()[3].the_t, 3);
+        assert_eq!(
+            agg.source(0, 1, 0, 3).unwrap(),
+            &PlaneSource {
+                file: "t2.tif".into(),
+                series: 0,
+                plane: 3,
+            }
+        );
+        assert_eq!(
+            agg.source(0, 1, 0, 1).unwrap().file,
+            PathBuf::from("t0.tif")
+        );
+        assert_eq!(
+            agg.metadata().global().original_metadata()["t2.tif Camera"],
+            "cam3"
+        );
+    }
+
+    #[test]
+    fn mismatched_files_are_rejected() {
+        let mut other = timepoints(1);
+        other.series[0].dimensions = Dim::new(8, 4, 2, 1, 1);
+
+        let parts = vec![("a".into(), timepoints(1)), ("b".into(), other)];
+
+        assert!(AggregatedMetadata::new(parts, Axis::Z).is_err());
+
+        let mut deeper = timepoints(1);
+        deeper.series[0].dimensions = Dim::new(4, 4, 3, 1, 1);
+        let parts = vec![("a".into(), timepoints(1)), ("b".into(), deeper)];
+        let err = AggregatedMetadata::new(parts, Axis::T).unwrap_err();
+        assert_eq!(err.to_string(), "b has SizeZ 3 in series 0, a has 2");
+
+        let mut wider = timepoints(1);
+        wider.series[0].pixel_types = vec![PixelType::U16];
+        let parts = vec![("a".into(), timepoints(1)), ("b".into(), wider)];
+        assert!(AggregatedMetadata::new(parts, Axis::T).is_err());
+    }
+}
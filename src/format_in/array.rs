@@ -0,0 +1,60 @@
+use std::io;
+
+use ndarray::{Array2, Array3};
+
+use crate::format_in::{FormatReader, Loc, Pixel, Region};
+
+// A region of plane (z, c, t) as a (y, x) array
+pub fn open_array2<T: Pixel, R: FormatReader>(
+    reader: &mut R,
+    z: u64,
+    c: u64,
+    t: u64,
+    region: Region,
+) -> io::Result<Array2<T>> {
+    let pixels = reader.open_as::<T>(Loc::new(z, c, t), region)?;
+
+    Array2::from_shape_vec((region.h as usize, region.w as usize), pixels).map_err(io::Error::other)
+}
+
+// A region of every z plane at channel c and timepoint t as a (z, y, x)
+// array
+pub fn open_array3<T: Pixel, R: FormatReader>(
+    reader: &mut R,
+    c: u64,
+    t: u64,
+    region: Region,
+) -> io::Result<Array3<T>> {
+    let size_z = reader.size_z()?;
+    let mut pixels = Vec::with_capacity((size_z * region.area()) as usize);
+    for z in 0..size_z {
+        pixels.extend(reader.open_as::<T>(Loc::new(z, c, t), region)?);
+    }
+
+    Array3::from_shape_vec(
+        (size_z as usize, region.h as usize, region.w as usize),
+        pixels,
+    )
+    .map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[test]
+    fn arrays_are_row_major() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let region = Region::new(10, 20, 7, 3);
+
+        let pixels = tr.open_as::<u16>(Loc::new(0, 1, 0), region).unwrap();
+        let plane = open_array2::<u16, _>(&mut tr, 0, 1, 0, region).unwrap();
+        let stack = open_array3::<u16, _>(&mut tr, 1, 0, region).unwrap();
+
+        assert_eq!(plane.dim(), (3, 7));
+        assert_eq!(plane[[2, 1]], pixels[2 * 7 + 1]);
+        assert_eq!(stack.dim(), (1, 3, 7));
+        assert_eq!(stack[[0, 2, 1]], plane[[2, 1]]);
+    }
+}
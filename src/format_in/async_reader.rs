@@ -0,0 +1,99 @@
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::format_in::{FormatReader, Loc, Metadata, Region};
+
+// Async counterpart of FormatReader, for web services and remote storage
+// backends which mustn't block executor threads
+pub trait AsyncFormatReader {
+    fn set_id(&mut self, id: &Path) -> impl Future<Output = io::Result<()>> + Send;
+
+    fn close(&mut self) -> impl Future<Output = io::Result<()>> + Send;
+
+    fn set_series(&mut self, series: u64) -> impl Future<Output = io::Result<()>> + Send;
+
+    fn metadata(&mut self) -> impl Future<Output = io::Result<Metadata>> + Send;
+
+    fn open_bytes(
+        &mut self,
+        loc: Loc,
+        region: Region,
+    ) -> impl Future<Output = io::Result<Vec<u8>>> + Send;
+}
+
+// Runs a blocking FormatReader on tokio's blocking thread pool
+pub struct BlockingReader<R: FormatReader> {
+    reader: Arc<Mutex<R>>,
+}
+
+impl<R: FormatReader + Send + 'static> BlockingReader<R> {
+    pub fn new(reader: R) -> Self {
+        BlockingReader {
+            reader: Arc::new(Mutex::new(reader)),
+        }
+    }
+
+    async fn run<T, F>(&self, f: F) -> io::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut R) -> io::Result<T> + Send + 'static,
+    {
+        let reader = self.reader.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut reader = reader
+                .lock()
+                .map_err(|_| io::Error::other("Reader poisoned by a panicked read"))?;
+            f(&mut reader)
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+impl<R: FormatReader + Send + 'static> AsyncFormatReader for BlockingReader<R> {
+    async fn set_id(&mut self, id: &Path) -> io::Result<()> {
+        let id: PathBuf = id.into();
+        self.run(move |r| r.set_id(&id)).await
+    }
+
+    async fn close(&mut self) -> io::Result<()> {
+        self.run(|r| r.close()).await
+    }
+
+    async fn set_series(&mut self, series: u64) -> io::Result<()> {
+        self.run(move |r| r.set_series(series)).await
+    }
+
+    async fn metadata(&mut self) -> io::Result<Metadata> {
+        self.run(|r| r.metadata()).await
+    }
+
+    async fn open_bytes(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u8>> {
+        self.run(move |r| r.open_bytes(loc, region)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[tokio::test]
+    async fn blocking_reader_matches_sync_reads() {
+        let path = Path::new("assets/example_valid.tiff");
+        let (loc, region) = (Loc::new(0, 1, 0), Region::new(5, 5, 4, 4));
+
+        let mut reader = BlockingReader::new(TiffReader::default());
+        reader.set_id(path).await.unwrap();
+        let mut direct = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+
+        assert_eq!(reader.metadata().await.unwrap().series_count(), 1);
+        assert_eq!(
+            reader.open_bytes(loc, region).await.unwrap(),
+            direct.open_bytes(loc, region).unwrap()
+        );
+    }
+}
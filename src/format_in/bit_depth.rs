@@ -0,0 +1,134 @@
+use std::io;
+
+use crate::format_in::Pixel;
+
+// How values between two integers are resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    // Halves away from zero
+    #[default]
+    Nearest,
+    Floor,
+    Ceil,
+    // Towards zero
+    Truncate,
+}
+
+impl Rounding {
+    pub fn apply(&self, v: f64) -> f64 {
+        match self {
+            Rounding::Nearest => v.round(),
+            Rounding::Floor => v.floor(),
+            Rounding::Ceil => v.ceil(),
+            Rounding::Truncate => v.trunc(),
+        }
+    }
+}
+
+// What happens to values the target type can't hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    // Saturate at the type's limits; NaN becomes 0
+    #[default]
+    Clamp,
+    // Fail on the first such value
+    Error,
+}
+
+// Smallest and largest values of an integer type
+fn limits<T: Pixel>() -> (f64, f64) {
+    let bits = T::BITS as i32;
+    match T::SIGNED {
+        true => (-(2f64.powi(bits - 1)), 2f64.powi(bits - 1) - 1.0),
+        false => (0.0, 2f64.powi(bits) - 1.0),
+    }
+}
+
+// Round float samples into integer type T
+pub fn quantize<T: Pixel>(
+    values: &[f64],
+    rounding: Rounding,
+    overflow: Overflow,
+) -> io::Result<Vec<T>> {
+    if T::FLOAT {
+        return Err(io::Error::other(format!(
+            "Can't quantize to {}",
+            std::any::type_name::<T>()
+        )));
+    }
+
+    let (lo, hi) = limits::<T>();
+    values
+        .iter()
+        .map(|v| {
+            let r = rounding.apply(*v);
+            match overflow {
+                _ if (lo..=hi).contains(&r) => Ok(T::from_float(r)),
+                Overflow::Clamp if r.is_nan() => Ok(T::from_float(0.0)),
+                Overflow::Clamp => Ok(T::from_float(r.clamp(lo, hi))),
+                Overflow::Error => Err(io::Error::other(format!("{v} is outside [{lo}, {hi}]"))),
+            }
+        })
+        .collect()
+}
+
+// Map the display window [lo, hi] of 16 bit samples onto 0..=255, clamping
+// samples outside it
+pub fn window_to_u8(values: &[u16], (lo, hi): (f64, f64), rounding: Rounding) -> Vec<u8> {
+    let range = hi - lo;
+    values
+        .iter()
+        .map(|v| match range > 0.0 {
+            true => rounding.apply((((*v as f64 - lo) / range) * 255.0).clamp(0.0, 255.0)) as u8,
+            false => 0,
+        })
+        .collect()
+}
+
+// Stretch samples using only the low `bits` bits, e.g. 12 bit camera data
+// stored in 16 bit words, over the full 16 bit range. Larger values clamp
+pub fn expand_significant_bits(values: &[u16], bits: u16, rounding: Rounding) -> Vec<u16> {
+    let max = (2f64.powi(bits.clamp(1, 16) as i32) - 1.0).max(1.0);
+    let scale = u16::MAX as f64 / max;
+    values
+        .iter()
+        .map(|v| rounding.apply((*v as f64).min(max) * scale) as u16)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantize_with_policies() {
+        let values = [-1.5, 2.5, 300.7, f64::NAN];
+
+        assert_eq!(
+            quantize::<u8>(&values, Rounding::Nearest, Overflow::Clamp).unwrap(),
+            vec![0, 3, 255, 0]
+        );
+        assert_eq!(
+            quantize::<i16>(&values[..3], Rounding::Floor, Overflow::Error).unwrap(),
+            vec![-2, 2, 300]
+        );
+        assert_eq!(
+            quantize::<i8>(&values[..2], Rounding::Truncate, Overflow::Clamp).unwrap(),
+            vec![-1, 2]
+        );
+        assert!(quantize::<u8>(&values, Rounding::Ceil, Overflow::Error).is_err());
+        assert!(quantize::<f32>(&values, Rounding::Nearest, Overflow::Clamp).is_err());
+    }
+
+    #[test]
+    fn rescale_16_bit() {
+        assert_eq!(
+            window_to_u8(&[100, 150, 200, 4000], (100.0, 200.0), Rounding::Nearest),
+            vec![0, 128, 255, 255]
+        );
+        assert_eq!(
+            expand_significant_bits(&[0, 2048, 4095, 5000], 12, Rounding::Nearest),
+            vec![0, 32776, 65535, 65535]
+        );
+    }
+}
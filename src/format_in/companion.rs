@@ -0,0 +1,309 @@
+use std::{
+    fs,
+    io::{self, Error},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::format_in::{
+    FormatReader, Loc, Metadata, ReaderCache, ReaderOptions, Region, detect::FormatDetector,
+    missing_plane, tiff_reader::TiffReader, translate::ome_xml::metadata_from_ome,
+};
+use crate::ome::{self, Element, PlaneMap, TiffData};
+
+// Reads a `.companion.ome` or `.ome.xml` file: an OME-XML document whose TiffData
+// elements reference pixel data held in plain TIFF files alongside it
+#[derive(Default)]
+pub struct CompanionReader {
+    file: Option<PathBuf>,
+    dir: PathBuf,
+    // Translated once on set_id, with the plane map of each series
+    metadata: Option<Arc<Metadata>>,
+    planes: Vec<PlaneMap>,
+    series: u64,
+    options: ReaderOptions,
+    readers: ReaderCache<TiffReader>,
+}
+
+impl CompanionReader {
+    pub fn new(file: String) -> io::Result<Self> {
+        let mut reader = Self::default();
+        reader.set_id(Path::new(&file))?;
+        Ok(reader)
+    }
+
+    fn md(&self) -> io::Result<&Arc<Metadata>> {
+        self.metadata
+            .as_ref()
+            .ok_or(Error::other("Reader not initialized, call set_id"))
+    }
+
+    // Every TIFF file referenced by the companion
+    pub fn referenced_files(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files = self
+            .md()?
+            .series
+            .iter()
+            .flat_map(|s| s.tiff_data())
+            .filter_map(|td| td.file_name.as_ref())
+            .map(|f| self.dir.join(f))
+            .collect::<Vec<_>>();
+
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    // Resolve plane (z, c, t) of a series to the file and IFD holding it
+    pub fn locate(&self, series: u64, z: u64, c: u64, t: u64) -> io::Result<(PathBuf, u64)> {
+        self.md()?.series(series)?;
+        let loc = self.planes[series as usize]
+            .locate(z, c, t)
+            .ok_or(Error::other(format!(
+                "No TiffData for plane ({z}, {c}, {t}) of series {series}"
+            )))?;
+
+        let file = loc
+            .file_name
+            .ok_or(Error::other("TiffData without a file reference"))?;
+
+        Ok((self.dir.join(file), loc.ifd))
+    }
+
+    fn reader(&mut self, file: PathBuf) -> io::Result<&mut TiffReader> {
+        // TiffData IFDs index every IFD of the file, as series when flattened
+        let options = self.options.clone().with_flatten_resolutions(true);
+        self.readers.get_or_open(&file, |f| {
+            let mut reader = TiffReader::with_options(options);
+            reader.set_id(f)?;
+            Ok(reader)
+        })
+    }
+}
+
+// OME-XML documents whose pixels live in other files
+pub struct CompanionFormat;
+
+impl FormatDetector for CompanionFormat {
+    fn format_name(&self) -> &'static str {
+        "OME-XML companion"
+    }
+
+    fn suffixes(&self) -> &'static [&'static str] {
+        &["companion.ome", "ome.xml"]
+    }
+
+    fn is_this_type(&self, header: &[u8], path: &Path) -> bool {
+        let text = String::from_utf8_lossy(header);
+        let xml = text.trim_start().starts_with('<') && text.contains("<OME");
+
+        xml && self.has_suffix(path)
+    }
+
+    fn new_reader(&self) -> Box<dyn FormatReader> {
+        Box::new(CompanionReader::default())
+    }
+}
+
+impl FormatReader for CompanionReader {
+    fn set_id(&mut self, id: &Path) -> io::Result<()> {
+        self.close()?;
+        let md = metadata_from_ome(&Element::parse(&fs::read_to_string(id)?)?)?;
+        self.planes = md
+            .series
+            .iter()
+            .map(|s| s.plane_map())
+            .collect::<io::Result<_>>()?;
+        self.dir = id.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+        self.file = Some(id.to_path_buf());
+        self.readers = ReaderCache::new(self.options.max_open_files());
+
+        // Pixel bytes come straight from the TIFFs, so decode in their order
+        self.metadata = Some(Arc::new(md));
+        let files = self.referenced_files()?;
+        if let Some(file) = files.into_iter().find(|f| f.exists()) {
+            let order = self.reader(file)?.byte_order()?;
+            if let Some(md) = self.metadata.as_mut() {
+                Arc::make_mut(md).global.byte_order = order;
+            }
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.readers.close_all()?;
+        *self = Self {
+            options: self.options.clone(),
+            ..Default::default()
+        };
+        Ok(())
+    }
+
+    // The companion file followed by the TIFFs it references
+    fn used_files(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = self.file.iter().cloned().collect();
+        files.extend(self.referenced_files()?);
+        Ok(files)
+    }
+
+    fn options(&self) -> ReaderOptions {
+        self.options.clone()
+    }
+
+    fn set_options(&mut self, options: ReaderOptions) {
+        self.options = options;
+    }
+
+    fn series(&self) -> u64 {
+        self.series
+    }
+
+    fn set_series(&mut self, series: u64) -> io::Result<()> {
+        self.md()?.series(series)?;
+        self.series = series;
+        Ok(())
+    }
+
+    fn metadata(&mut self) -> io::Result<Metadata> {
+        Ok(Metadata::clone(self.md()?))
+    }
+
+    fn metadata_arc(&mut self) -> io::Result<Arc<Metadata>> {
+        self.md().cloned()
+    }
+
+    // Planes without TiffData, or whose file doesn't exist, are missing
+    fn plane_present(&mut self, loc: Loc) -> io::Result<bool> {
+        let Loc { z, c, t } = loc;
+        Ok(self
+            .locate(self.series, z, c, t)
+            .is_ok_and(|(file, _)| file.exists()))
+    }
+
+    fn open_bytes(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u8>> {
+        if !self.plane_present(loc)? {
+            return missing_plane(self, loc, region);
+        }
+
+        let Loc { z, c, t } = loc;
+        let (file, ifd) = self.locate(self.series, z, c, t)?;
+
+        let reader = self.reader(file)?;
+        reader.set_series(ifd)?;
+        reader.open_bytes(Loc::new(0, 0, 0), region)
+    }
+}
+
+// Write an OME-XML document as a companion file
+pub fn write_companion(path: &Path, root: &Element) -> io::Result<()> {
+    fs::write(
+        path,
+        format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{root}"),
+    )
+}
+
+// Point the Pixels of series `series` at the given TiffData, replacing any
+// existing TiffData/MetadataOnly elements
+pub fn set_tiff_data(root: &mut Element, series: usize, tiff_data: &[TiffData]) -> io::Result<()> {
+    let pixels = root
+        .elements_mut()
+        .filter(|e| e.local_name() == "Image")
+        .nth(series)
+        .and_then(|image| image.elements_mut().find(|e| e.local_name() == "Pixels"))
+        .ok_or(Error::other(format!("No Pixels for series {series}")))?;
+
+    pixels.children.retain(|n| {
+        !matches!(n, ome::xml::Node::Element(e) if matches!(e.local_name(), "TiffData" | "MetadataOnly"))
+    });
+    tiff_data.iter().for_each(|td| pixels.push(td.to_xml()));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::test_support::{TagValue, TempDir, TestIfd, tiff};
+
+    #[test]
+    fn companion_resolves_tiff_planes() {
+        let tiff = fs::canonicalize("assets/example_valid.tiff").unwrap();
+        let dir = TempDir::new();
+        let path = dir.join("test.companion.ome");
+
+        let mut root = Element::parse(
+            r#"<OME><Image ID="Image:0"><Pixels ID="Pixels:0" DimensionOrder="XYCZT"
+                Type="uint16" SizeX="1979" SizeY="1979" SizeZ="1" SizeC="1" SizeT="1"/>
+            </Image></OME>"#,
+        )
+        .unwrap();
+        let td = TiffData {
+            file_name: Some(tiff.to_string_lossy().into()),
+            plane_count: Some(1),
+            ..Default::default()
+        };
+        set_tiff_data(&mut root, 0, &[td]).unwrap();
+        write_companion(&path, &root).unwrap();
+
+        let mut companion = CompanionReader::new(path.to_string_lossy().into()).unwrap();
+        let mut direct = TiffReader::new(tiff.to_string_lossy().into()).unwrap();
+        let (loc, region) = (Loc::new(0, 0, 0), Region::new(100, 100, 10, 10));
+
+        assert_eq!(companion.referenced_files().unwrap(), vec![tiff.clone()]);
+        assert_eq!(companion.used_files().unwrap(), vec![path, tiff]);
+        assert_eq!(
+            companion.open_bytes(loc, region).unwrap(),
+            direct.open_bytes(loc, region).unwrap()
+        );
+    }
+
+    #[test]
+    fn companion_detected_by_either_suffix() {
+        let header = br#"<?xml version="1.0"?><OME xmlns="http://www.openmicroscopy.org"/>"#;
+
+        for name in ["a.companion.ome", "a.ome.xml", "A.OME.XML"] {
+            assert!(CompanionFormat.is_this_type(header, Path::new(name)));
+        }
+        assert!(!CompanionFormat.is_this_type(header, Path::new("a.xml")));
+    }
+
+    // An 8 bit 4x4 image and its 2x2 reduced resolution, filled with 1 and 2
+    fn pyramid_tiff() -> Vec<u8> {
+        tiff(vec![
+            TestIfd::filled(4, 4, 1),
+            TestIfd::filled(2, 2, 2).tag(254, TagValue::Long(1)),
+        ])
+    }
+
+    #[test]
+    fn tiff_data_ifds_ignore_pyramid_options() {
+        let dir = TempDir::new();
+        fs::write(dir.join("pyramid.tiff"), pyramid_tiff()).unwrap();
+        let path = dir.join("test.companion.ome");
+
+        let mut root = Element::parse(
+            r#"<OME><Image ID="Image:0"><Pixels ID="Pixels:0" DimensionOrder="XYCZT"
+                Type="uint8" SizeX="2" SizeY="2" SizeZ="1" SizeC="1" SizeT="1"/>
+            </Image></OME>"#,
+        )
+        .unwrap();
+        let td = TiffData {
+            file_name: Some("pyramid.tiff".into()),
+            ifd: 1,
+            plane_count: Some(1),
+            ..Default::default()
+        };
+        set_tiff_data(&mut root, 0, &[td]).unwrap();
+        write_companion(&path, &root).unwrap();
+
+        let mut companion = CompanionReader::default();
+        companion.set_options(ReaderOptions::new().with_flatten_resolutions(false));
+        companion.set_id(&path).unwrap();
+        assert_eq!(
+            companion
+                .open_bytes(Loc::new(0, 0, 0), Region::full(2, 2))
+                .unwrap(),
+            vec![2; 4]
+        );
+    }
+}
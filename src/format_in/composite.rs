@@ -0,0 +1,129 @@
+use std::io;
+
+use crate::format_in::{FormatReader, Loc, PixelSlice, Region, RenderPipeline, Scaling};
+use crate::ome::Color;
+
+// One channel of a composite: its samples, the color its full intensity is
+// drawn in, and the pipeline mapping samples from black to that color
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeChannel {
+    pub pixels: PixelSlice,
+    pub color: Color,
+    pub pipeline: RenderPipeline,
+}
+
+impl CompositeChannel {
+    // A channel windowed by scaling, e.g. Scaling::Percentile(0.1, 99.9)
+    pub fn new(pixels: PixelSlice, color: Color, scaling: Scaling) -> Self {
+        CompositeChannel {
+            pixels,
+            color,
+            pipeline: RenderPipeline::new().with_scaling(scaling),
+        }
+    }
+
+    pub fn with_window(mut self, lo: f64, hi: f64) -> Self {
+        self.pipeline = self.pipeline.with_window(lo, hi);
+        self
+    }
+
+    // Window, gamma and inversion of the channel, replacing its scaling
+    pub fn with_pipeline(mut self, pipeline: RenderPipeline) -> Self {
+        self.pipeline = pipeline;
+        self
+    }
+}
+
+// Blend channels of the same size into interleaved 8 bit RGB by adding
+// their colors, saturating at white, as fluorescence viewers do
+pub fn composite(channels: &[CompositeChannel]) -> io::Result<Vec<u8>> {
+    let n = channels.first().map_or(0, |ch| ch.pixels.len());
+    let mut rgb = vec![0.0; n * 3];
+
+    for (c, ch) in channels.iter().enumerate() {
+        if ch.pixels.len() != n {
+            return Err(io::Error::other(format!(
+                "Channel {c} has {} pixels, not {n}",
+                ch.pixels.len()
+            )));
+        }
+
+        let color = [ch.color.r, ch.color.g, ch.color.b].map(f64::from);
+        for (px, intensity) in rgb
+            .chunks_exact_mut(3)
+            .zip(ch.pipeline.intensities(&ch.pixels))
+        {
+            for (p, k) in px.iter_mut().zip(color) {
+                *p += intensity * k;
+            }
+        }
+    }
+
+    Ok(rgb.iter().map(|v| v.round().min(255.0) as u8).collect())
+}
+
+// Composite of every channel of plane (z, t) of the current series over
+// region, each in its display color and windowed by scaling
+pub fn open_composite<R: FormatReader + ?Sized>(
+    reader: &mut R,
+    z: u64,
+    t: u64,
+    region: Region,
+    scaling: Scaling,
+) -> io::Result<Vec<u8>> {
+    let md = reader.metadata_arc()?;
+    let series = md.series(reader.series())?;
+
+    let channels = (0..reader.size_c()?)
+        .map(|c| {
+            let pixels = reader.open_pixels(Loc::new(z, c, t), region)?;
+            Ok(CompositeChannel::new(
+                pixels,
+                series.display_color(c),
+                scaling,
+            ))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    composite(&channels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[test]
+    fn additive_blending() {
+        let red = CompositeChannel::new(
+            PixelSlice::U16(vec![0, 500, 1000]),
+            Color::RED,
+            Scaling::Global,
+        );
+        let yellow = CompositeChannel::new(
+            PixelSlice::U8(vec![255, 0, 255]),
+            Color::rgb(255, 255, 0),
+            Scaling::Global,
+        )
+        .with_window(0.0, 510.0);
+
+        assert_eq!(
+            composite(&[red.clone(), yellow]).unwrap(),
+            vec![128, 128, 0, 128, 0, 0, 255, 128, 0]
+        );
+
+        let short = CompositeChannel::new(PixelSlice::U8(vec![1]), Color::BLUE, Scaling::Global);
+        assert!(composite(&[red, short]).is_err());
+        assert!(composite(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn composite_of_example() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let region = Region::new(300, 300, 16, 8);
+
+        let rgb = open_composite(&mut tr, 0, 0, region, Scaling::Global).unwrap();
+        assert_eq!(rgb.len(), 16 * 8 * 3);
+        assert!(rgb.contains(&255));
+    }
+}
@@ -0,0 +1,276 @@
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::format_in::{FormatReader, ImageReader, Loc, PixelSlice, Region};
+
+// Dark frame subtraction and flat-field division, applied by open_pixels to
+// every region read when set with ReaderOptions::with_correction. Reference
+// planes cover the whole w x h plane; each sample becomes
+// (v - dark) * mean(flat - dark) / (flat - dark)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Correction {
+    pub(crate) size: (u64, u64),
+    pub(crate) dark: Option<Arc<Vec<f64>>>,
+    pub(crate) flat: Option<Arc<Vec<f64>>>,
+    // Per pixel factor derived from flat and dark
+    pub(crate) gain: Option<Arc<Vec<f64>>>,
+}
+
+impl Correction {
+    pub fn new(size: (u64, u64)) -> Self {
+        Correction {
+            size,
+            dark: None,
+            flat: None,
+            gain: None,
+        }
+    }
+
+    // Reference planes from the first plane of each file, e.g. companion
+    // dark.tif and flat.tif saved alongside an acquisition
+    pub fn from_files(dark: Option<&Path>, flat: Option<&Path>) -> io::Result<Self> {
+        let read = |path: &Path| -> io::Result<(PixelSlice, (u64, u64))> {
+            let mut reader = ImageReader::open(path)?;
+            let size = (reader.size_x()?, reader.size_y()?);
+            let pixels = reader.open_pixels(Loc::new(0, 0, 0), Region::full(size.0, size.1))?;
+            Ok((pixels, size))
+        };
+
+        let dark = dark.map(read).transpose()?;
+        let flat = flat.map(read).transpose()?;
+        let size = match (&dark, &flat) {
+            (Some((_, size)), _) | (None, Some((_, size))) => *size,
+            (None, None) => return Err(io::Error::other("No reference planes given")),
+        };
+
+        let mut correction = Correction::new(size);
+        if let Some((pixels, _)) = dark {
+            correction = correction.with_dark(&pixels)?;
+        }
+        if let Some((pixels, _)) = flat {
+            correction = correction.with_flat(&pixels)?;
+        }
+        Ok(correction)
+    }
+
+    // Subtract a plane taken with no light reaching the sensor
+    pub fn with_dark(mut self, dark: &PixelSlice) -> io::Result<Self> {
+        self.dark = Some(Arc::new(self.reference(dark)?));
+        self.update_gain();
+        Ok(self)
+    }
+
+    // Divide by a plane of an evenly lit field, scaled to keep the mean
+    // intensity
+    pub fn with_flat(mut self, flat: &PixelSlice) -> io::Result<Self> {
+        self.flat = Some(Arc::new(self.reference(flat)?));
+        self.update_gain();
+        Ok(self)
+    }
+
+    pub fn size(&self) -> (u64, u64) {
+        self.size
+    }
+
+    // Err unless the reader's current plane is the size of the reference
+    // planes, which are in full resolution pixels: reads of reduced
+    // pyramid levels would be corrected by the wrong reference pixels
+    pub(crate) fn check<R: FormatReader + ?Sized>(&self, reader: &mut R) -> io::Result<()> {
+        let (w, h) = reader.resolution_size()?;
+        let resolution = reader.resolution();
+        if resolution != 0 || (w, h) != self.size {
+            return Err(io::Error::other(format!(
+                "Correction of {}x{} planes doesn't apply to {w}x{h} planes at resolution {resolution}",
+                self.size.0, self.size.1
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn reference(&self, pixels: &PixelSlice) -> io::Result<Vec<f64>> {
+        let (w, h) = self.size;
+        if pixels.len() as u64 != w * h || pixels.pixel_type().is_complex() {
+            return Err(io::Error::other(format!(
+                "Reference plane of {} {} pixels doesn't cover {w}x{h}",
+                pixels.len(),
+                pixels.pixel_type().ome_name()
+            )));
+        }
+
+        Ok(pixels.to_f64())
+    }
+
+    fn update_gain(&mut self) {
+        let Some(flat) = &self.flat else {
+            return;
+        };
+
+        let signal = flat
+            .iter()
+            .enumerate()
+            .map(|(i, f)| f - self.dark.as_ref().map_or(0.0, |d| d[i]))
+            .collect::<Vec<_>>();
+        let mean = signal.iter().sum::<f64>() / signal.len().max(1) as f64;
+
+        // Pixels the flat doesn't reach are left as they are
+        let gain = signal
+            .iter()
+            .map(|s| if *s > 0.0 { mean / s } else { 1.0 })
+            .collect();
+        self.gain = Some(Arc::new(gain));
+    }
+
+    // Correct the samples of a region of the plane in place. Samples past
+    // the edges of the reference planes, e.g. padding, are left as they are
+    pub(crate) fn correct(&self, values: &mut [f64], region: Region) -> io::Result<()> {
+        if region.is_empty() || values.len() as u64 != region.area() {
+            return Err(io::Error::other(format!(
+                "{} samples don't cover {region:?}",
+                values.len()
+            )));
+        }
+
+        let (w, h) = self.size;
+        for (y, src) in (region.y..h).zip(values.chunks_exact_mut(region.w as usize)) {
+            for (x, v) in (region.x..w).zip(src.iter_mut()) {
+                let i = (y * w + x) as usize;
+                if let Some(dark) = &self.dark {
+                    *v -= dark[i];
+                }
+                if let Some(gain) = &self.gain {
+                    *v *= gain[i];
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Correct pixels read from region, keeping their type: integer samples
+    // are rounded and saturated, so values below the dark level become 0
+    pub fn apply(&self, pixels: &PixelSlice, region: Region) -> io::Result<PixelSlice> {
+        if pixels.pixel_type().is_complex() {
+            return Err(io::Error::other("Can't correct complex samples"));
+        }
+
+        let mut values = pixels.to_f64();
+        self.correct(&mut values, region)?;
+        Ok(PixelSlice::from_f64(&values, pixels.pixel_type()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::test_support::{TagValue, TestIfd, tiff};
+    use crate::format_in::tiff_reader::TiffReader;
+    use crate::format_in::{OutOfBounds, ReaderOptions};
+
+    #[test]
+    fn dark_and_flat_correction() {
+        let correction = Correction::new((2, 2))
+            .with_dark(&PixelSlice::U8(vec![10, 10, 20, 20]))
+            .unwrap()
+            .with_flat(&PixelSlice::U8(vec![110, 60, 120, 20]))
+            .unwrap();
+
+        // Flat signal is 100, 50, 100, 0 with a mean of 62.5
+        let pixels = PixelSlice::U16(vec![60, 60, 20, 5]);
+        assert_eq!(
+            correction.apply(&pixels, Region::full(2, 2)).unwrap(),
+            PixelSlice::U16(vec![31, 63, 0, 0])
+        );
+        assert_eq!(
+            correction
+                .apply(&PixelSlice::F32(vec![30.0]), Region::new(1, 1, 1, 1))
+                .unwrap(),
+            PixelSlice::F32(vec![10.0])
+        );
+        assert!(correction.apply(&pixels, Region::full(3, 2)).is_err());
+        assert!(Correction::new((3, 3)).with_flat(&pixels).is_err());
+    }
+
+    #[test]
+    fn correct_during_reads() {
+        let region = Region::new(300, 300, 20, 10);
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let raw = tr.open_as::<u16>(Loc::new(0, 1, 0), region).unwrap();
+
+        let dark = PixelSlice::U16(vec![100; 1979 * 1979]);
+        let correction = Correction::new((1979, 1979)).with_dark(&dark).unwrap();
+        tr.set_options(ReaderOptions::new().with_correction(correction));
+
+        let PixelSlice::U16(corrected) = tr.open_pixels(Loc::new(0, 1, 0), region).unwrap() else {
+            panic!("Expected u16 pixels");
+        };
+        let expected = raw
+            .iter()
+            .map(|v| v.saturating_sub(100))
+            .collect::<Vec<_>>();
+        assert_eq!(corrected, expected);
+    }
+
+    #[test]
+    fn correct_only_full_resolution() {
+        let bytes = tiff(vec![
+            TestIfd::filled(4, 4, 9),
+            TestIfd::filled(2, 2, 9).tag(254, TagValue::Long(1)),
+        ]);
+        let mut tr = TiffReader::from_source(std::io::Cursor::new(bytes)).unwrap();
+        let dark = PixelSlice::U8(vec![4; 16]);
+        let correction = Correction::new((4, 4)).with_dark(&dark).unwrap();
+        tr.set_options(
+            ReaderOptions::new()
+                .with_flatten_resolutions(false)
+                .with_correction(correction),
+        );
+        let loc = Loc::new(0, 0, 0);
+
+        let full = tr.open_pixels(loc, Region::full(4, 4)).unwrap();
+        assert_eq!(full, PixelSlice::U8(vec![5; 16]));
+        tr.set_resolution(1).unwrap();
+        assert!(tr.open_pixels(loc, Region::full(2, 2)).is_err());
+    }
+
+    #[test]
+    fn correct_reads_past_the_edges() {
+        let loc = Loc::new(0, 1, 0);
+        let (edge, inside) = (
+            Region::new(1970, 1975, 20, 10),
+            Region::new(1970, 1975, 9, 4),
+        );
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let expected = tr
+            .open_as::<u16>(loc, inside)
+            .unwrap()
+            .iter()
+            .map(|v| v.saturating_sub(100))
+            .collect::<Vec<_>>();
+
+        let dark = PixelSlice::U16(vec![100; 1979 * 1979]);
+        let correction = Correction::new((1979, 1979)).with_dark(&dark).unwrap();
+        let options = ReaderOptions::new().with_correction(correction);
+
+        tr.set_options(options.clone());
+        assert!(tr.open_pixels(loc, edge).is_err());
+
+        tr.set_options(options.clone().with_out_of_bounds(OutOfBounds::Clamp));
+        let clamped = tr.open_pixels(loc, edge).unwrap();
+        assert_eq!(clamped, PixelSlice::U16(expected.clone()));
+
+        tr.set_options(options.with_out_of_bounds(OutOfBounds::Pad(7.0)));
+        let PixelSlice::U16(padded) = tr.open_pixels(loc, edge).unwrap() else {
+            panic!("Expected u16 pixels");
+        };
+        assert_eq!(padded.len(), 200);
+        for (i, v) in padded.iter().enumerate() {
+            let (x, y) = (i % 20, i / 20);
+            match x < 9 && y < 4 {
+                true => assert_eq!(*v, expected[y * 9 + x]),
+                false => assert_eq!(*v, 7),
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    fs::File,
+    io::{self, Error, Read},
+    path::Path,
+};
+
+use crate::format_in::{
+    FormatReader, ReaderOptions, companion::CompanionFormat, tiff_reader::TiffFormat,
+};
+
+// Number of leading bytes handed to `is_this_type`
+pub const HEADER_LEN: usize = 512;
+
+// Recognises one file format and opens readers for it
+pub trait FormatDetector {
+    fn format_name(&self) -> &'static str;
+
+    // Lower case file suffixes, used when sniffing the content is inconclusive
+    fn suffixes(&self) -> &'static [&'static str];
+
+    // `header` holds up to HEADER_LEN leading bytes of the file
+    fn is_this_type(&self, header: &[u8], path: &Path) -> bool;
+
+    // A reader for this format, not yet given a file
+    fn new_reader(&self) -> Box<dyn FormatReader>;
+
+    fn open(&self, path: &Path, options: &ReaderOptions) -> io::Result<Box<dyn FormatReader>> {
+        let mut reader = self.new_reader();
+        reader.set_options(options.clone());
+        reader.set_id(path)?;
+        Ok(reader)
+    }
+
+    fn has_suffix(&self, path: &Path) -> bool {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        self.suffixes()
+            .iter()
+            .any(|s| name.ends_with(&format!(".{s}")))
+    }
+}
+
+// Detectors tried in order, the first to claim a file wins. More specific
+// formats (e.g. companion OME-XML) must come before generic ones.
+pub struct ReaderRegistry {
+    detectors: Vec<Box<dyn FormatDetector>>,
+}
+
+impl Default for ReaderRegistry {
+    fn default() -> Self {
+        ReaderRegistry {
+            detectors: vec![Box::new(CompanionFormat), Box::new(TiffFormat)],
+        }
+    }
+}
+
+impl ReaderRegistry {
+    pub fn empty() -> Self {
+        ReaderRegistry {
+            detectors: Vec::new(),
+        }
+    }
+
+    // Registered detectors take precedence over the built in ones
+    pub fn register(&mut self, detector: Box<dyn FormatDetector>) {
+        self.detectors.insert(0, detector);
+    }
+
+    pub fn detectors(&self) -> impl Iterator<Item = &dyn FormatDetector> {
+        self.detectors.iter().map(|d| d.as_ref())
+    }
+
+    // Choose a detector by content, falling back to the file suffix
+    pub fn detect(&self, path: &Path) -> io::Result<&dyn FormatDetector> {
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        File::open(path)?
+            .take(HEADER_LEN as u64)
+            .read_to_end(&mut header)?;
+
+        self.detectors()
+            .find(|d| d.is_this_type(&header, path))
+            .or_else(|| self.detectors().find(|d| d.has_suffix(path)))
+            .ok_or(Error::other(format!(
+                "Unrecognised file format: {}",
+                path.display()
+            )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_tiff_by_content() {
+        let registry = ReaderRegistry::default();
+        let detector = registry
+            .detect(Path::new("assets/example_valid.tiff"))
+            .unwrap();
+
+        assert_eq!(detector.format_name(), "TIFF");
+        assert!(registry.detect(Path::new("Cargo.toml")).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use std::{io, str::FromStr};
+
+// One of the Z, C and T axes of a series
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Z,
+    C,
+    T,
+}
+
+impl Axis {
+    // Pick this axis out of a (z, c, t) triple
+    pub(crate) fn pick(&self, zct: (u64, u64, u64)) -> u64 {
+        match self {
+            Axis::Z => zct.0,
+            Axis::C => zct.1,
+            Axis::T => zct.2,
+        }
+    }
+
+    // Position of this axis in a (z, c, t) triple
+    pub(crate) fn index(&self) -> usize {
+        match self {
+            Axis::Z => 0,
+            Axis::C => 1,
+            Axis::T => 2,
+        }
+    }
+}
+
+// Order in which Z, C and T vary across the linear plane index, fastest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DimensionOrder {
+    XYZCT,
+    XYZTC,
+    #[default]
+    XYCZT,
+    XYCTZ,
+    XYTZC,
+    XYTCZ,
+}
+
+impl DimensionOrder {
+    fn axes(&self) -> [char; 3] {
+        match self {
+            Self::XYZCT => ['Z', 'C', 'T'],
+            Self::XYZTC => ['Z', 'T', 'C'],
+            Self::XYCZT => ['C', 'Z', 'T'],
+            Self::XYCTZ => ['C', 'T', 'Z'],
+            Self::XYTZC => ['T', 'Z', 'C'],
+            Self::XYTCZ => ['T', 'C', 'Z'],
+        }
+    }
+
+    fn pick(axis: char, zct: (u64, u64, u64)) -> u64 {
+        match axis {
+            'Z' => zct.0,
+            'C' => zct.1,
+            _ => zct.2,
+        }
+    }
+
+    // Map a linear plane index to (z, c, t) given the sizes (size_z, size_c, size_t)
+    pub fn index_to_zct(&self, sizes: (u64, u64, u64), index: u64) -> io::Result<(u64, u64, u64)> {
+        let [a0, a1, a2] = self.axes();
+        let (n0, n1, n2) = (
+            Self::pick(a0, sizes),
+            Self::pick(a1, sizes),
+            Self::pick(a2, sizes),
+        );
+
+        if index >= n0 * n1 * n2 {
+            return Err(io::Error::other(format!(
+                "Plane index {index} out of range for sizes {sizes:?}"
+            )));
+        }
+
+        let coords = [index % n0, (index / n0) % n1, index / (n0 * n1)];
+        let mut zct = (0, 0, 0);
+        for (axis, v) in [a0, a1, a2].into_iter().zip(coords) {
+            match axis {
+                'Z' => zct.0 = v,
+                'C' => zct.1 = v,
+                _ => zct.2 = v,
+            }
+        }
+
+        Ok(zct)
+    }
+
+    // Map (z, c, t) to a linear plane index given the sizes (size_z, size_c, size_t)
+    pub fn zct_to_index(&self, sizes: (u64, u64, u64), zct: (u64, u64, u64)) -> io::Result<u64> {
+        if zct.0 >= sizes.0 || zct.1 >= sizes.1 || zct.2 >= sizes.2 {
+            return Err(io::Error::other(format!(
+                "Coordinates {zct:?} out of range for sizes {sizes:?}"
+            )));
+        }
+
+        let [a0, a1, a2] = self.axes();
+        let (n0, n1) = (Self::pick(a0, sizes), Self::pick(a1, sizes));
+
+        Ok(Self::pick(a0, zct) + n0 * (Self::pick(a1, zct) + n1 * Self::pick(a2, zct)))
+    }
+}
+
+impl FromStr for DimensionOrder {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "XYZCT" => Ok(Self::XYZCT),
+            "XYZTC" => Ok(Self::XYZTC),
+            "XYCZT" => Ok(Self::XYCZT),
+            "XYCTZ" => Ok(Self::XYCTZ),
+            "XYTZC" => Ok(Self::XYTZC),
+            "XYTCZ" => Ok(Self::XYTCZ),
+            _ => Err(io::Error::other(format!("Invalid dimension order: {s}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_round_trip() {
+        let sizes = (3, 2, 4);
+
+        for order in ["XYZCT", "XYZTC", "XYCZT", "XYCTZ", "XYTZC", "XYTCZ"] {
+            let order: DimensionOrder = order.parse().unwrap();
+            for i in 0..24 {
+                let zct = order.index_to_zct(sizes, i).unwrap();
+                assert_eq!(order.zct_to_index(sizes, zct).unwrap(), i);
+            }
+        }
+    }
+
+    #[test]
+    fn xyzct_ordering() {
+        let order = DimensionOrder::XYZCT;
+
+        assert_eq!(order.index_to_zct((3, 2, 4), 4).unwrap(), (1, 1, 0));
+        assert_eq!(order.zct_to_index((3, 2, 4), (2, 1, 3)).unwrap(), 23);
+        assert!(order.index_to_zct((3, 2, 4), 24).is_err());
+    }
+}
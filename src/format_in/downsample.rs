@@ -0,0 +1,148 @@
+use std::io;
+
+use crate::format_in::{Pixel, PixelSlice};
+
+// How a block of pixels becomes one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Downsample {
+    // The mean of the block, rounded for integer samples
+    Mean,
+    // The pixel at the centre of the block, keeping sample values intact
+    Nearest,
+}
+
+// Size of a w x h plane shrunk by factor; partial blocks at the right and
+// bottom edges still make a pixel
+pub fn downsampled_size((w, h): (u64, u64), factor: u64) -> (u64, u64) {
+    (w.div_ceil(factor), h.div_ceil(factor))
+}
+
+// Shrink a w x h plane by factor along both axes, e.g. 2 for 2x2 binning
+pub fn downsample(
+    pixels: &PixelSlice,
+    size: (u64, u64),
+    factor: u64,
+    method: Downsample,
+) -> io::Result<PixelSlice> {
+    let n = pixels.len();
+    if factor == 0 || n as u64 != size.0 * size.1 {
+        return Err(io::Error::other(format!(
+            "Can't shrink {n} pixels of a {}x{} plane by {factor}",
+            size.0, size.1
+        )));
+    }
+
+    let shrink = Shrink {
+        size,
+        factor,
+        method,
+    };
+    Ok(match pixels {
+        PixelSlice::U8(v) => PixelSlice::U8(shrink.apply(v, mean)),
+        PixelSlice::U16(v) => PixelSlice::U16(shrink.apply(v, mean)),
+        PixelSlice::U32(v) => PixelSlice::U32(shrink.apply(v, mean)),
+        PixelSlice::I8(v) => PixelSlice::I8(shrink.apply(v, mean)),
+        PixelSlice::I16(v) => PixelSlice::I16(shrink.apply(v, mean)),
+        PixelSlice::I32(v) => PixelSlice::I32(shrink.apply(v, mean)),
+        PixelSlice::F32(v) => PixelSlice::F32(shrink.apply(v, mean)),
+        PixelSlice::F64(v) => PixelSlice::F64(shrink.apply(v, mean)),
+        PixelSlice::C64(v) => PixelSlice::C64(shrink.apply(v, complex_mean)),
+        PixelSlice::C128(v) => PixelSlice::C128(shrink.apply(v, complex_mean)),
+    })
+}
+
+struct Shrink {
+    size: (u64, u64),
+    factor: u64,
+    method: Downsample,
+}
+
+impl Shrink {
+    fn apply<T: Copy>(&self, values: &[T], mean: impl Fn(&[T]) -> T) -> Vec<T> {
+        let (w, h) = self.size;
+        let f = self.factor;
+        let (out_w, out_h) = downsampled_size(self.size, f);
+        let mut out = Vec::with_capacity((out_w * out_h) as usize);
+        let mut block = Vec::with_capacity((f * f) as usize);
+
+        for by in 0..out_h {
+            let rows = by * f..((by + 1) * f).min(h);
+            for bx in 0..out_w {
+                let cols = bx * f..((bx + 1) * f).min(w);
+
+                match self.method {
+                    Downsample::Nearest => {
+                        let y = (rows.start + rows.end) / 2;
+                        let x = (cols.start + cols.end) / 2;
+                        out.push(values[(y * w + x) as usize]);
+                    }
+                    Downsample::Mean => {
+                        block.clear();
+                        for y in rows.clone() {
+                            let row = (y * w) as usize;
+                            block.extend_from_slice(
+                                &values[row + cols.start as usize..row + cols.end as usize],
+                            );
+                        }
+                        out.push(mean(&block));
+                    }
+                }
+            }
+        }
+
+        out
+    }
+}
+
+fn mean<T: Pixel + Into<f64>>(block: &[T]) -> T {
+    let m = block.iter().map(|v| (*v).into()).sum::<f64>() / block.len() as f64;
+    T::from_float(if T::FLOAT { m } else { m.round() })
+}
+
+fn complex_mean<T: Pixel + Into<f64>>(block: &[[T; 2]]) -> [T; 2] {
+    let part = |i: usize| {
+        let parts = block.iter().map(|v| v[i]).collect::<Vec<_>>();
+        mean(&parts)
+    };
+    [part(0), part(1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bin_every_type() {
+        // 3x3, so the right and bottom blocks are partial
+        let plane = (1..=9).collect::<Vec<u16>>();
+
+        let Ok(PixelSlice::U16(mean)) =
+            downsample(&PixelSlice::U16(plane.clone()), (3, 3), 2, Downsample::Mean)
+        else {
+            panic!("Expected u16 pixels");
+        };
+        assert_eq!(mean, vec![3, 5, 8, 9]);
+
+        let Ok(PixelSlice::U16(nearest)) =
+            downsample(&PixelSlice::U16(plane), (3, 3), 2, Downsample::Nearest)
+        else {
+            panic!("Expected u16 pixels");
+        };
+        assert_eq!(nearest, vec![5, 6, 8, 9]);
+
+        let floats = PixelSlice::F32(vec![0.5, -1.0, 2.0, 1.0]);
+        let Ok(PixelSlice::F32(mean)) = downsample(&floats, (2, 2), 2, Downsample::Mean) else {
+            panic!("Expected f32 pixels");
+        };
+        assert_eq!(mean, vec![0.625]);
+
+        let complex = PixelSlice::C64(vec![[1.0, 0.0], [0.0, 1.0]]);
+        let Ok(PixelSlice::C64(mean)) = downsample(&complex, (2, 1), 4, Downsample::Mean) else {
+            panic!("Expected complex pixels");
+        };
+        assert_eq!(mean, vec![[0.5, 0.5]]);
+
+        assert_eq!(downsampled_size((1979, 1979), 4), (495, 495));
+        assert!(downsample(&PixelSlice::I8(vec![0; 5]), (2, 2), 2, Downsample::Mean).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use std::io;
+
+use image::{DynamicImage, ImageBuffer, Luma, Rgb};
+
+use crate::format_in::{FormatReader, Loc, Pixel, PixelType, Region};
+
+// A region of plane (z, c, t) as a grayscale image, e.g. for thumbnails
+pub fn open_image<R: FormatReader>(
+    reader: &mut R,
+    z: u64,
+    c: u64,
+    t: u64,
+    region: Region,
+) -> io::Result<DynamicImage> {
+    let (w, h) = dimensions(region)?;
+    let loc = Loc::new(z, c, t);
+
+    let image = match pixel_type(reader, c)? {
+        PixelType::U8 => ImageBuffer::<Luma<u8>, _>::from_raw(w, h, reader.open_as(loc, region)?)
+            .map(DynamicImage::ImageLuma8),
+        PixelType::U16 => ImageBuffer::<Luma<u16>, _>::from_raw(w, h, reader.open_as(loc, region)?)
+            .map(DynamicImage::ImageLuma16),
+        pt => {
+            return Err(io::Error::other(format!(
+                "No image type for {}",
+                pt.ome_name()
+            )));
+        }
+    };
+
+    image.ok_or(io::Error::other("Region doesn't match the pixels read"))
+}
+
+// A region of the first three channels at (z, t) as an RGB image
+pub fn open_rgb_image<R: FormatReader>(
+    reader: &mut R,
+    z: u64,
+    t: u64,
+    region: Region,
+) -> io::Result<DynamicImage> {
+    let (w, h) = dimensions(region)?;
+    if reader.size_c()? < 3 {
+        return Err(io::Error::other("RGB images need at least 3 channels"));
+    }
+
+    let image = match pixel_type(reader, 0)? {
+        PixelType::U8 => {
+            ImageBuffer::<Rgb<u8>, _>::from_raw(w, h, interleave::<u8, R>(reader, z, t, region)?)
+                .map(DynamicImage::ImageRgb8)
+        }
+        PixelType::U16 => {
+            ImageBuffer::<Rgb<u16>, _>::from_raw(w, h, interleave::<u16, R>(reader, z, t, region)?)
+                .map(DynamicImage::ImageRgb16)
+        }
+        pt => {
+            return Err(io::Error::other(format!(
+                "No image type for {}",
+                pt.ome_name()
+            )));
+        }
+    };
+
+    image.ok_or(io::Error::other("Region doesn't match the pixels read"))
+}
+
+fn dimensions(region: Region) -> io::Result<(u32, u32)> {
+    let convert = |v: u64| u32::try_from(v).map_err(io::Error::other);
+    Ok((convert(region.w)?, convert(region.h)?))
+}
+
+fn pixel_type<R: FormatReader>(reader: &mut R, c: u64) -> io::Result<PixelType> {
+    reader
+        .metadata()?
+        .series(reader.series())?
+        .pixel_type(c)
+        .ok_or(io::Error::other("Error reading pixel type"))
+}
+
+// Channels 0, 1 and 2 interleaved as RGBRGB...
+fn interleave<T: Pixel, R: FormatReader>(
+    reader: &mut R,
+    z: u64,
+    t: u64,
+    region: Region,
+) -> io::Result<Vec<T>> {
+    let channels = (0..3)
+        .map(|c| reader.open_as::<T>(Loc::new(z, c, t), region))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    Ok((0..region.area() as usize)
+        .flat_map(|i| channels.iter().map(move |ch| ch[i]))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[test]
+    fn open_gray_and_rgb_images() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let region = Region::new(10, 20, 7, 3);
+
+        let gray = open_image(&mut tr, 0, 1, 0, region).unwrap();
+        let rgb = open_rgb_image(&mut tr, 0, 0, region).unwrap();
+        let pixels = tr.open_as::<u16>(Loc::new(0, 1, 0), region).unwrap();
+
+        assert_eq!((gray.width(), gray.height()), (7, 3));
+        assert_eq!(gray.as_luma16().unwrap().as_raw(), &pixels);
+        assert_eq!(rgb.as_rgb16().unwrap().as_raw()[4], pixels[1]);
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt;
+use std::io;
+
+use sha1::{Digest, Sha1};
+
+use crate::format_in::{FormatReader, Loc, Region};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha1,
+    // Much faster, for regression tests rather than tamper detection
+    Xxh3,
+}
+
+impl HashAlgorithm {
+    // Lowercase hex digest of bytes
+    pub fn digest(&self, bytes: &[u8]) -> String {
+        match self {
+            HashAlgorithm::Sha1 => {
+                let mut hasher = Sha1::new();
+                hasher.update(bytes);
+                hasher
+                    .finalize()
+                    .iter()
+                    .map(|b| format!("{b:02x}"))
+                    .collect()
+            }
+            HashAlgorithm::Xxh3 => format!("{:016x}", xxhash_rust::xxh3::xxh3_64(bytes)),
+        }
+    }
+}
+
+// Digest of the bytes of one plane
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaneDigest {
+    pub series: u64,
+    pub z: u64,
+    pub c: u64,
+    pub t: u64,
+    pub digest: String,
+}
+
+// One plane per line: "series z c t digest"
+impl fmt::Display for PlaneDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let PlaneDigest {
+            series,
+            z,
+            c,
+            t,
+            digest,
+        } = self;
+        write!(f, "{series} {z} {c} {t} {digest}")
+    }
+}
+
+// Digest of plane loc of the current series, as returned by open_bytes
+pub fn plane_digest<R: FormatReader + ?Sized>(
+    reader: &mut R,
+    loc: Loc,
+    algorithm: HashAlgorithm,
+) -> io::Result<String> {
+    let region = Region::full(reader.size_x()?, reader.size_y()?);
+    Ok(algorithm.digest(&reader.open_bytes(loc, region)?))
+}
+
+// Digests of every plane of every series, in series then z, c, t order
+pub fn manifest<R: FormatReader + ?Sized>(
+    reader: &mut R,
+    algorithm: HashAlgorithm,
+) -> io::Result<Vec<PlaneDigest>> {
+    let current = reader.series();
+    let mut digests = Vec::new();
+
+    for series in 0..reader.series_count()? {
+        reader.set_series(series)?;
+        let (size_z, size_c, size_t) = (reader.size_z()?, reader.size_c()?, reader.size_t()?);
+
+        for z in 0..size_z {
+            for c in 0..size_c {
+                for t in 0..size_t {
+                    let digest = plane_digest(reader, Loc::new(z, c, t), algorithm)?;
+                    digests.push(PlaneDigest {
+                        series,
+                        z,
+                        c,
+                        t,
+                        digest,
+                    });
+                }
+            }
+        }
+    }
+
+    reader.set_series(current)?;
+    Ok(digests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[test]
+    fn manifest_lists_every_plane() {
+        assert_eq!(
+            HashAlgorithm::Sha1.digest(b"abc"),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let digests = manifest(&mut tr, HashAlgorithm::Xxh3).unwrap();
+        let plane = tr.open_plane(0, 1, 0).unwrap();
+
+        assert_eq!(digests.len(), 4);
+        assert_eq!(digests[1].digest, HashAlgorithm::Xxh3.digest(&plane));
+        assert!(digests[1].to_string().starts_with("0 0 1 0 "));
+    }
+}
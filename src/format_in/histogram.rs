@@ -0,0 +1,237 @@
+use std::io;
+
+use crate::format_in::{FormatReader, Loc, PixelSlice, PixelType, Region, pixel};
+
+// Counts of samples in equal width bins spanning [min, max]. Samples outside
+// the range land in the first or last bin; NaNs aren't counted
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    min: f64,
+    max: f64,
+    counts: Vec<u64>,
+}
+
+impl Histogram {
+    pub fn new(bins: usize, min: f64, max: f64) -> io::Result<Self> {
+        if bins == 0 || min.is_nan() || max.is_nan() || max <= min {
+            return Err(io::Error::other(format!(
+                "Can't bin [{min}, {max}] into {bins} bins"
+            )));
+        }
+
+        Ok(Histogram {
+            min,
+            max,
+            counts: vec![0; bins],
+        })
+    }
+
+    // Bins spanning every value of an integer type. Floats have no natural
+    // range and need Histogram::new
+    pub fn for_pixel_type(pixel_type: PixelType, bins: usize) -> io::Result<Self> {
+        if pixel_type.is_float() {
+            return Err(io::Error::other(format!(
+                "{} samples need an explicit range",
+                pixel_type.ome_name()
+            )));
+        }
+
+        let bits = pixel_type.bits() as i32;
+        match pixel_type.is_signed() {
+            true => Histogram::new(bins, -(2f64.powi(bits - 1)), 2f64.powi(bits - 1) - 1.0),
+            false => Histogram::new(bins, 0.0, 2f64.powi(bits) - 1.0),
+        }
+    }
+
+    // Count more samples, e.g. the next tile of a plane
+    pub fn add(&mut self, pixels: &PixelSlice) {
+        self.add_values(&pixels.to_f64());
+    }
+
+    fn add_values(&mut self, values: &[f64]) {
+        let last = self.counts.len() - 1;
+        let scale = self.counts.len() as f64 / (self.max - self.min);
+
+        for v in values.iter().filter(|v| !v.is_nan()) {
+            let bin = ((v - self.min) * scale).max(0.0) as usize;
+            self.counts[bin.min(last)] += 1;
+        }
+    }
+
+    // Add the counts of a histogram with the same bins, e.g. one built on
+    // another thread
+    pub fn merge(&mut self, other: &Histogram) -> io::Result<()> {
+        if (self.min, self.max, self.bins()) != (other.min, other.max, other.bins()) {
+            return Err(io::Error::other("Histograms have different bins"));
+        }
+
+        self.counts
+            .iter_mut()
+            .zip(other.counts.iter())
+            .for_each(|(a, b)| *a += b);
+        Ok(())
+    }
+
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    pub fn bins(&self) -> usize {
+        self.counts.len()
+    }
+
+    // Lower and upper edge of bin i
+    pub fn bin_range(&self, i: usize) -> (f64, f64) {
+        let width = (self.max - self.min) / self.bins() as f64;
+        let lo = self.min + i as f64 * width;
+        (lo, lo + width)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    // Lower edge of the bin holding the p-th percentile (0 to 100), e.g.
+    // for a display window clipping outliers. None when empty
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * (total - 1) as f64).round() as u64;
+        let mut seen = 0;
+        let bin = self.counts.iter().position(|c| {
+            seen += c;
+            seen > rank
+        })?;
+
+        Some(self.bin_range(bin).0)
+    }
+}
+
+// Read plane loc of the current series in tiles of the reader's optimal size,
+// passing the samples of each to f
+pub(crate) fn each_tile<R, F>(reader: &mut R, loc: Loc, mut f: F) -> io::Result<()>
+where
+    R: FormatReader + ?Sized,
+    F: FnMut(&[f64]),
+{
+    let md = reader.metadata_arc()?;
+    let order = *md.byte_order();
+    let pixel_type = md
+        .series(reader.series())?
+        .pixel_type(loc.c)
+        .ok_or(io::Error::other("Error reading pixel type"))?;
+
+    let (size_x, size_y) = (reader.size_x()?, reader.size_y()?);
+    let (tile_w, tile_h) = reader.optimal_tile_size()?;
+    let (tile_w, tile_h) = (
+        tile_w.clamp(1, size_x.max(1)),
+        tile_h.clamp(1, size_y.max(1)),
+    );
+
+    for y in (0..size_y).step_by(tile_h as usize) {
+        for x in (0..size_x).step_by(tile_w as usize) {
+            let region = Region::new(x, y, tile_w.min(size_x - x), tile_h.min(size_y - y));
+            let bytes = reader.open_bytes(loc, region)?;
+            f(&pixel::decode::<f64>(&bytes, pixel_type, order)?);
+        }
+    }
+
+    Ok(())
+}
+
+// Histogram over the planes of the current series at locs. Integer samples
+// are binned over the range of their type; floats over their min and max,
+// found by a first pass
+fn histogram<R: FormatReader + ?Sized>(
+    reader: &mut R,
+    locs: &[Loc],
+    bins: usize,
+) -> io::Result<Histogram> {
+    let c = locs.first().map(|l| l.c).unwrap_or(0);
+    let pixel_type = reader
+        .metadata_arc()?
+        .series(reader.series())?
+        .pixel_type(c)
+        .ok_or(io::Error::other("Error reading pixel type"))?;
+
+    let mut histogram = match pixel_type.is_float() {
+        true => {
+            let (mut lo, mut hi) = (f64::INFINITY, f64::NEG_INFINITY);
+            for loc in locs {
+                each_tile(reader, *loc, |values| {
+                    for v in values.iter().filter(|v| !v.is_nan()) {
+                        (lo, hi) = (lo.min(*v), hi.max(*v));
+                    }
+                })?;
+            }
+            // A flat or empty channel still needs a non-empty range
+            let lo = if lo.is_finite() { lo } else { 0.0 };
+            Histogram::new(bins, lo, hi.max(lo + 1.0))?
+        }
+        false => Histogram::for_pixel_type(pixel_type, bins)?,
+    };
+
+    for loc in locs {
+        each_tile(reader, *loc, |values| histogram.add_values(values))?;
+    }
+
+    Ok(histogram)
+}
+
+// Histogram of plane loc of the current series
+pub fn plane_histogram<R: FormatReader + ?Sized>(
+    reader: &mut R,
+    loc: Loc,
+    bins: usize,
+) -> io::Result<Histogram> {
+    histogram(reader, &[loc], bins)
+}
+
+// Histogram of every plane of channel c of the current series
+pub fn channel_histogram<R: FormatReader + ?Sized>(
+    reader: &mut R,
+    c: u64,
+    bins: usize,
+) -> io::Result<Histogram> {
+    let (size_z, size_t) = (reader.size_z()?, reader.size_t()?);
+    let locs = (0..size_t)
+        .flat_map(|t| (0..size_z).map(move |z| Loc::new(z, c, t)))
+        .collect::<Vec<_>>();
+
+    histogram(reader, &locs, bins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[test]
+    fn accumulate_across_tiles() {
+        let mut h = Histogram::for_pixel_type(PixelType::U8, 4).unwrap();
+        h.add(&PixelSlice::U8(vec![0, 10, 100, 200]));
+        h.add(&PixelSlice::U8(vec![255, 63, 64]));
+
+        assert_eq!(h.counts(), &[3, 2, 0, 2]);
+        assert_eq!(h.bin_range(1).0, 63.75);
+        assert_eq!(h.percentile(50.0), Some(63.75));
+
+        let mut other = Histogram::new(4, 0.0, 255.0).unwrap();
+        other.add(&PixelSlice::F32(vec![f32::NAN, 1000.0]));
+        h.merge(&other).unwrap();
+        assert_eq!(h.total(), 8);
+        assert!(h.merge(&Histogram::new(2, 0.0, 255.0).unwrap()).is_err());
+    }
+
+    #[test]
+    fn channel_histogram_counts_every_pixel() {
+        let mut reader = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let h = channel_histogram(&mut reader, 1, 64).unwrap();
+
+        assert_eq!(h.total(), 1979 * 1979);
+        assert_eq!(h.bins(), 64);
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    borrow::Cow,
+    io::{self, Error},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::format_in::{
+    FormatReader, Loc, Lut, Metadata, ReaderOptions, Region, detect::ReaderRegistry,
+};
+
+// Opens any supported file, choosing the reader by sniffing its content
+#[derive(Default)]
+pub struct ImageReader {
+    registry: ReaderRegistry,
+    options: ReaderOptions,
+    reader: Option<Box<dyn FormatReader>>,
+    format: Option<&'static str>,
+}
+
+impl ImageReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_registry(registry: ReaderRegistry) -> Self {
+        ImageReader {
+            registry,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_options(options: ReaderOptions) -> Self {
+        ImageReader {
+            options,
+            ..Default::default()
+        }
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut reader = Self::new();
+        reader.set_id(path.as_ref())?;
+        Ok(reader)
+    }
+
+    // Name of the detected format, e.g. "TIFF"
+    pub fn format(&self) -> Option<&'static str> {
+        self.format
+    }
+
+    fn reader(&mut self) -> io::Result<&mut Box<dyn FormatReader>> {
+        self.reader
+            .as_mut()
+            .ok_or(Error::other("Reader not initialized, call set_id"))
+    }
+}
+
+impl FormatReader for ImageReader {
+    fn set_id(&mut self, id: &Path) -> io::Result<()> {
+        self.close()?;
+
+        let detector = self.registry.detect(id)?;
+        self.reader = Some(detector.open(id, &self.options)?);
+        self.format = Some(detector.format_name());
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        if let Some(mut reader) = self.reader.take() {
+            reader.close()?;
+        }
+        self.format = None;
+        Ok(())
+    }
+
+    fn used_files(&self) -> io::Result<Vec<PathBuf>> {
+        self.reader
+            .as_ref()
+            .map_or(Ok(Vec::new()), |r| r.used_files())
+    }
+
+    fn options(&self) -> ReaderOptions {
+        self.options.clone()
+    }
+
+    fn set_options(&mut self, options: ReaderOptions) {
+        self.options = options;
+    }
+
+    fn resolution(&self) -> u64 {
+        self.reader.as_ref().map_or(0, |r| r.resolution())
+    }
+
+    fn set_resolution(&mut self, resolution: u64) -> io::Result<()> {
+        self.reader()?.set_resolution(resolution)
+    }
+
+    fn resolution_size(&mut self) -> io::Result<(u64, u64)> {
+        self.reader()?.resolution_size()
+    }
+
+    fn series(&self) -> u64 {
+        self.reader.as_ref().map_or(0, |r| r.series())
+    }
+
+    fn set_series(&mut self, series: u64) -> io::Result<()> {
+        self.reader()?.set_series(series)
+    }
+
+    fn series_count(&mut self) -> io::Result<u64> {
+        self.reader()?.series_count()
+    }
+
+    fn metadata(&mut self) -> io::Result<Metadata> {
+        self.reader()?.metadata()
+    }
+
+    fn metadata_arc(&mut self) -> io::Result<Arc<Metadata>> {
+        self.reader()?.metadata_arc()
+    }
+
+    fn is_deferred(&self) -> bool {
+        self.reader.as_ref().is_some_and(|r| r.is_deferred())
+    }
+
+    fn open_bytes(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u8>> {
+        self.reader()?.open_bytes(loc, region)
+    }
+
+    fn open_bytes_into(&mut self, loc: Loc, region: Region, buf: &mut [u8]) -> io::Result<()> {
+        self.reader()?.open_bytes_into(loc, region, buf)
+    }
+
+    fn open_u16(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u16>> {
+        self.reader()?.open_u16(loc, region)
+    }
+
+    fn open_u16_into(&mut self, loc: Loc, region: Region, buf: &mut [u16]) -> io::Result<()> {
+        self.reader()?.open_u16_into(loc, region, buf)
+    }
+
+    fn open_bytes_cow(&mut self, loc: Loc, region: Region) -> io::Result<Cow<'_, [u8]>> {
+        self.reader()?.open_bytes_cow(loc, region)
+    }
+
+    fn plane_offset(&mut self, loc: Loc) -> io::Result<Option<u64>> {
+        self.reader()?.plane_offset(loc)
+    }
+
+    fn open_planes_bytes(&mut self, locs: &[Loc]) -> io::Result<Vec<Vec<u8>>> {
+        self.reader()?.open_planes_bytes(locs)
+    }
+
+    fn get_lut(&mut self, c: u64) -> io::Result<Option<Lut>> {
+        self.reader()?.get_lut(c)
+    }
+
+    fn plane_present(&mut self, loc: Loc) -> io::Result<bool> {
+        self.reader()?.plane_present(loc)
+    }
+
+    fn optimal_tile_size(&mut self) -> io::Result<(u64, u64)> {
+        self.reader()?.optimal_tile_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::PixelType;
+
+    #[test]
+    fn open_detected_tiff() {
+        let mut reader = ImageReader::open("assets/example_valid.tiff").unwrap();
+
+        assert_eq!(reader.format(), Some("TIFF"));
+        assert_eq!(reader.metadata().unwrap().series_count(), 1);
+        assert_eq!(reader.used_files().unwrap().len(), 1);
+        assert_eq!(reader.series_count().unwrap(), 1);
+        assert_eq!(reader.size_x().unwrap(), 1979);
+        assert_eq!(reader.size_c().unwrap(), 4);
+        assert_eq!(reader.image_count().unwrap(), 4);
+        assert_eq!(reader.pixel_type().unwrap(), PixelType::U16);
+        assert!(!reader.is_little_endian().unwrap());
+        assert!(reader.set_series(1).is_err());
+
+        reader.close().unwrap();
+        assert!(reader.metadata().is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use std::io;
+
+// Layouts of pixels with several samples each. Chunky (interleaved) data
+// keeps the samples of a pixel together, RGBRGB...; planar data holds the
+// plane of each sample in turn, RRR...GGG...BBB...
+
+// Number of pixels in len bytes of samples * bytes_per_sample byte pixels
+fn pixel_count(len: usize, samples: usize, bytes_per_sample: usize) -> io::Result<usize> {
+    let pixel = samples * bytes_per_sample;
+    if pixel == 0 || !len.is_multiple_of(pixel) {
+        return Err(io::Error::other(format!(
+            "{len} bytes don't hold whole pixels of {samples} {bytes_per_sample} byte samples"
+        )));
+    }
+
+    Ok(len / pixel)
+}
+
+// Copy the bytes_per_sample byte sample at byte offset `offset` of every
+// bytes_per_pixel byte pixel of chunky into out, one sample per pixel
+pub fn copy_sample(
+    chunky: &[u8],
+    bytes_per_pixel: usize,
+    offset: usize,
+    bytes_per_sample: usize,
+    out: &mut [u8],
+) -> io::Result<()> {
+    let pixels = pixel_count(chunky.len(), 1, bytes_per_pixel)?;
+    if offset + bytes_per_sample > bytes_per_pixel || out.len() != pixels * bytes_per_sample {
+        return Err(io::Error::other(format!(
+            "Can't copy {bytes_per_sample} byte samples at offset {offset} of {pixels} \
+             {bytes_per_pixel} byte pixels into {} bytes",
+            out.len()
+        )));
+    }
+
+    // Nothing to pick out when pixels hold a single sample
+    if bytes_per_sample == bytes_per_pixel {
+        out.copy_from_slice(chunky);
+        return Ok(());
+    }
+
+    // Fixed size copies of the common sample sizes compile to plain moves
+    // rather than a memcpy call per sample
+    match bytes_per_sample {
+        1 => copy_fixed::<1>(chunky, bytes_per_pixel, offset, out),
+        2 => copy_fixed::<2>(chunky, bytes_per_pixel, offset, out),
+        4 => copy_fixed::<4>(chunky, bytes_per_pixel, offset, out),
+        8 => copy_fixed::<8>(chunky, bytes_per_pixel, offset, out),
+        _ => {
+            for (px, dst) in chunky
+                .chunks_exact(bytes_per_pixel)
+                .zip(out.chunks_exact_mut(bytes_per_sample))
+            {
+                dst.copy_from_slice(&px[offset..offset + bytes_per_sample]);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn copy_fixed<const N: usize>(
+    chunky: &[u8],
+    bytes_per_pixel: usize,
+    offset: usize,
+    out: &mut [u8],
+) {
+    let (samples, _) = out.as_chunks_mut::<N>();
+    for (px, dst) in chunky.chunks_exact(bytes_per_pixel).zip(samples) {
+        dst.copy_from_slice(&px[offset..offset + N]);
+    }
+}
+
+pub fn chunky_to_planar(
+    chunky: &[u8],
+    samples: usize,
+    bytes_per_sample: usize,
+) -> io::Result<Vec<u8>> {
+    let pixels = pixel_count(chunky.len(), samples, bytes_per_sample)?;
+    let plane_len = pixels * bytes_per_sample;
+    let mut planar = vec![0; chunky.len()];
+
+    for s in 0..samples {
+        let plane = &mut planar[s * plane_len..(s + 1) * plane_len];
+        let offset = s * bytes_per_sample;
+        copy_sample(
+            chunky,
+            samples * bytes_per_sample,
+            offset,
+            bytes_per_sample,
+            plane,
+        )?;
+    }
+
+    Ok(planar)
+}
+
+pub fn planar_to_chunky(
+    planar: &[u8],
+    samples: usize,
+    bytes_per_sample: usize,
+) -> io::Result<Vec<u8>> {
+    let pixels = pixel_count(planar.len(), samples, bytes_per_sample)?;
+    let bytes_per_pixel = samples * bytes_per_sample;
+    let mut chunky = vec![0; planar.len()];
+    if pixels == 0 {
+        return Ok(chunky);
+    }
+
+    for (s, plane) in planar.chunks_exact(pixels * bytes_per_sample).enumerate() {
+        let offset = s * bytes_per_sample;
+        for (dst, src) in chunky
+            .chunks_exact_mut(bytes_per_pixel)
+            .zip(plane.chunks_exact(bytes_per_sample))
+        {
+            dst[offset..offset + bytes_per_sample].copy_from_slice(src);
+        }
+    }
+
+    Ok(chunky)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_layouts() {
+        // Two pixels of three 2 byte samples
+        let chunky = [1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6];
+        let planar = [1, 1, 4, 4, 2, 2, 5, 5, 3, 3, 6, 6];
+
+        assert_eq!(chunky_to_planar(&chunky, 3, 2).unwrap(), planar);
+        assert_eq!(planar_to_chunky(&planar, 3, 2).unwrap(), chunky);
+        assert!(chunky_to_planar(&chunky, 5, 1).is_err());
+        assert!(planar_to_chunky(&[], 4, 1).unwrap().is_empty());
+
+        for (samples, width) in [(1, 1), (4, 1), (3, 4), (2, 8)] {
+            let bytes = (0..samples * width * 5)
+                .map(|b| b as u8)
+                .collect::<Vec<_>>();
+            let planar = chunky_to_planar(&bytes, samples, width).unwrap();
+            assert_eq!(planar_to_chunky(&planar, samples, width).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn copy_one_sample() {
+        let mut out = [0; 4];
+        copy_sample(&[1, 2, 3, 4, 5, 6, 7, 8], 4, 2, 2, &mut out).unwrap();
+
+        assert_eq!(out, [3, 4, 7, 8]);
+        assert!(copy_sample(&[1, 2, 3, 4], 4, 3, 2, &mut out[..2]).is_err());
+
+        // Sizes without a fixed size copy
+        let mut out = [0; 6];
+        copy_sample(&[1, 2, 3, 4, 5, 6, 7, 8], 4, 1, 3, &mut out).unwrap();
+        assert_eq!(out, [2, 3, 4, 6, 7, 8]);
+        copy_sample(&[1, 2, 3, 4, 5, 6], 3, 2, 1, &mut out[..2]).unwrap();
+        assert_eq!(out[..2], [3, 6]);
+    }
+}
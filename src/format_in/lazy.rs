@@ -0,0 +1,112 @@
+use std::io;
+
+use crate::format_in::{FormatReader, Loc, PixelSlice, Region};
+
+// A region of a plane to be read later. Creating and slicing handles
+// touches no pixel data; only read and read_bytes do, through whichever
+// reader of the dataset is given, so pipelines can narrow a plane down
+// before deciding to decode it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LazyPlane {
+    series: u64,
+    resolution: u64,
+    loc: Loc,
+    region: Region,
+}
+
+impl LazyPlane {
+    pub fn new(series: u64, resolution: u64, loc: Loc, region: Region) -> Self {
+        LazyPlane {
+            series,
+            resolution,
+            loc,
+            region,
+        }
+    }
+
+    pub fn series(&self) -> u64 {
+        self.series
+    }
+
+    pub fn resolution(&self) -> u64 {
+        self.resolution
+    }
+
+    pub fn loc(&self) -> Loc {
+        self.loc
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    // The part of this region at sub, relative to its top left corner
+    pub fn slice(&self, sub: Region) -> io::Result<Self> {
+        sub.validate(self.region.w, self.region.h)?;
+        Ok(LazyPlane {
+            region: Region::new(self.region.x + sub.x, self.region.y + sub.y, sub.w, sub.h),
+            ..*self
+        })
+    }
+
+    // The same region of another plane, e.g. the next channel
+    pub fn at(&self, loc: Loc) -> Self {
+        LazyPlane { loc, ..*self }
+    }
+
+    // Run f with reader at this handle's series and resolution, restoring
+    // the reader's own afterwards
+    fn with_reader<R, T>(
+        &self,
+        reader: &mut R,
+        f: impl FnOnce(&mut R) -> io::Result<T>,
+    ) -> io::Result<T>
+    where
+        R: FormatReader + ?Sized,
+    {
+        let (series, resolution) = (reader.series(), reader.resolution());
+        reader.set_series(self.series)?;
+        reader.set_resolution(self.resolution)?;
+        let result = f(reader);
+        reader.set_series(series)?;
+        reader.set_resolution(resolution)?;
+        result
+    }
+
+    pub fn read_bytes<R: FormatReader + ?Sized>(&self, reader: &mut R) -> io::Result<Vec<u8>> {
+        self.with_reader(reader, |r| r.open_bytes(self.loc, self.region))
+    }
+
+    pub fn read<R: FormatReader + ?Sized>(&self, reader: &mut R) -> io::Result<PixelSlice> {
+        self.with_reader(reader, |r| r.open_pixels(self.loc, self.region))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[test]
+    fn slice_before_reading() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let plane = tr.lazy_plane(Loc::new(0, 1, 0)).unwrap();
+        assert_eq!(plane.region(), Region::full(1979, 1979));
+
+        let tile = plane
+            .slice(Region::new(1000, 1000, 100, 50))
+            .unwrap()
+            .slice(Region::new(10, 20, 30, 5))
+            .unwrap();
+        assert_eq!(tile.region(), Region::new(1010, 1020, 30, 5));
+        assert_eq!(
+            tile.read_bytes(&mut tr).unwrap(),
+            tr.open_bytes(Loc::new(0, 1, 0), tile.region()).unwrap()
+        );
+
+        let next = tile.at(Loc::new(0, 2, 0)).read(&mut tr).unwrap();
+        assert_eq!(next.len(), 150);
+        assert!(plane.slice(Region::new(1900, 0, 100, 1)).is_err());
+        assert!(tr.lazy_plane(Loc::new(0, 4, 0)).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::format_in::{PixelType, SeriesMetadata};
+use crate::ome::Color;
+
+// Red, green and blue values for each sample value of a channel, as used to
+// render it. 16 bit tables are indexed by 16 bit samples
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lut {
+    U8([Vec<u8>; 3]),
+    U16([Vec<u16>; 3]),
+}
+
+impl Lut {
+    // A ramp from black to color, for channels displayed in a single color
+    pub fn from_color(color: Color, pixel_type: PixelType) -> Option<Self> {
+        let ramp = |max: u32, v: u8| (0..=max).map(move |i| i * v as u32 / 255);
+
+        match pixel_type {
+            PixelType::U8 => Some(Lut::U8(
+                [color.r, color.g, color.b].map(|v| ramp(255, v).map(|a| a as u8).collect()),
+            )),
+            PixelType::U16 => Some(Lut::U16(
+                [color.r, color.g, color.b].map(|v| ramp(65535, v).map(|a| a as u16).collect()),
+            )),
+            _ => None,
+        }
+    }
+
+    // A TIFF ColorMap: every red value, then every green, then every blue
+    pub fn from_color_map(color_map: &[u16]) -> Option<Self> {
+        let n = color_map.len() / 3;
+        if n == 0 || !color_map.len().is_multiple_of(3) {
+            return None;
+        }
+
+        let mut channels = color_map.chunks_exact(n).map(|c| c.to_vec());
+        Some(Lut::U16([(); 3].map(|_| channels.next().unwrap())))
+    }
+
+    // The LUT of channel c from its OME color, if it has one
+    pub fn for_channel(series: &SeriesMetadata, c: u64) -> Option<Self> {
+        let color = series.channels().get(c as usize)?.color?;
+        Lut::from_color(color, series.pixel_type(c)?)
+    }
+
+    // Number of sample values the table covers
+    pub fn len(&self) -> usize {
+        match self {
+            Lut::U8(t) => t[0].len(),
+            Lut::U16(t) => t[0].len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 8 bit red, green and blue for sample value i; values past the end of
+    // the table take its last entry. None for an empty table
+    pub fn rgb8(&self, i: usize) -> Option<[u8; 3]> {
+        let i = i.min(self.len().checked_sub(1)?);
+        Some(match self {
+            Lut::U8(t) => [t[0][i], t[1][i], t[2][i]],
+            Lut::U16(t) => [t[0][i], t[1][i], t[2][i]].map(|v| (v >> 8) as u8),
+        })
+    }
+}
+
+// Named 256 entry tables for rendering a single channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    Gray,
+    Red,
+    Green,
+    Blue,
+    // Black through red and yellow to white
+    Hot,
+    // Perceptually uniform dark blue through green to yellow
+    Viridis,
+}
+
+impl Colormap {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "gray" | "grey" | "grays" => Some(Colormap::Gray),
+            "red" => Some(Colormap::Red),
+            "green" => Some(Colormap::Green),
+            "blue" => Some(Colormap::Blue),
+            "hot" => Some(Colormap::Hot),
+            "viridis" => Some(Colormap::Viridis),
+            _ => None,
+        }
+    }
+
+    // Colors at sample values between which the table is interpolated
+    fn stops(&self) -> &'static [(u8, [u8; 3])] {
+        match self {
+            Colormap::Gray => &[(0, [0, 0, 0]), (255, [255, 255, 255])],
+            Colormap::Red => &[(0, [0, 0, 0]), (255, [255, 0, 0])],
+            Colormap::Green => &[(0, [0, 0, 0]), (255, [0, 255, 0])],
+            Colormap::Blue => &[(0, [0, 0, 0]), (255, [0, 0, 255])],
+            Colormap::Hot => &[
+                (0, [0, 0, 0]),
+                (96, [255, 0, 0]),
+                (192, [255, 255, 0]),
+                (255, [255, 255, 255]),
+            ],
+            Colormap::Viridis => &[
+                (0, [68, 1, 84]),
+                (64, [59, 82, 139]),
+                (128, [33, 145, 140]),
+                (192, [94, 201, 98]),
+                (255, [253, 231, 37]),
+            ],
+        }
+    }
+
+    pub fn lut(&self) -> Lut {
+        let mut table = [(); 3].map(|_| Vec::with_capacity(256));
+
+        for pair in self.stops().windows(2) {
+            let ((a, from), (b, to)) = (pair[0], pair[1]);
+            let start = if a == 0 { 0 } else { a as u32 + 1 };
+            for i in start..=b as u32 {
+                let f = (i - a as u32) as f32 / (b - a) as f32;
+                for k in 0..3 {
+                    let v = from[k] as f32 + f * (to[k] as f32 - from[k] as f32);
+                    table[k].push(v.round() as u8);
+                }
+            }
+        }
+
+        Lut::U8(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_colormaps() {
+        let hot = Colormap::from_name("Hot").unwrap().lut();
+
+        assert_eq!(hot.len(), 256);
+        assert_eq!(hot.rgb8(0), Some([0, 0, 0]));
+        assert_eq!(hot.rgb8(96), Some([255, 0, 0]));
+        assert_eq!(hot.rgb8(1000), Some([255, 255, 255]));
+        assert_eq!(Colormap::Viridis.lut().len(), 256);
+        assert_eq!(
+            Lut::U16([vec![0x1234], vec![0xff00], vec![0]]).rgb8(3),
+            Some([0x12, 0xff, 0])
+        );
+    }
+
+    #[test]
+    fn color_ramps_and_color_maps() {
+        let Some(Lut::U8([r, g, b])) = Lut::from_color(Color::rgb(255, 128, 0), PixelType::U8)
+        else {
+            panic!("Expected an 8 bit LUT");
+        };
+        assert_eq!((r[255], g[255], b[255]), (255, 128, 0));
+        assert_eq!((r[0], g[128]), (0, 64));
+        assert!(Lut::from_color(Color::RED, PixelType::F32).is_none());
+
+        let lut = Lut::from_color_map(&[0, 1, 2, 3, 4, 5]).unwrap();
+        assert_eq!(lut, Lut::U16([vec![0, 1], vec![2, 3], vec![4, 5]]));
+        assert!(Lut::from_color_map(&[1, 2]).is_none());
+    }
+}
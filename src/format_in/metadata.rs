@@ -0,0 +1,504 @@
+use std::io;
+
+use crate::OmeError;
+
+use crate::format_in::{DimensionOrder, Loc, PixelType, Region, translate::OriginalMetadata};
+use crate::ome::{
+    Channel, Color, Dataset, Experimenter, Instrument, Modulo, ModuloAxis, Plane, PlaneMap, Plate,
+    Project, Roi, StageLabel, StructuredAnnotations, TiffData, Timestamp,
+};
+use crate::units::Length;
+
+// Sizes a reader builds a series from; see CoreMetadata for the public view
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Dim {
+    w: u64,
+    h: u64,
+    d: u64,
+    t: u64,
+    c: u64,
+}
+
+impl Dim {
+    pub(crate) fn new(w: u64, h: u64, d: u64, c: u64, t: u64) -> Self {
+        Self { w, h, d, t, c }
+    }
+
+    pub(crate) fn from_whc(w: u64, h: u64, c: u64) -> Self {
+        Self {
+            w,
+            h,
+            d: 1,
+            t: 1,
+            c,
+        }
+    }
+
+    pub(crate) fn size_x(&self) -> u64 {
+        self.w
+    }
+
+    pub(crate) fn size_y(&self) -> u64 {
+        self.h
+    }
+
+    pub(crate) fn size_z(&self) -> u64 {
+        self.d
+    }
+
+    pub(crate) fn size_c(&self) -> u64 {
+        self.c
+    }
+
+    pub(crate) fn size_t(&self) -> u64 {
+        self.t
+    }
+
+    // (size_z, size_c, size_t) as used for plane indexing
+    pub(crate) fn zct(&self) -> (u64, u64, u64) {
+        (self.d, self.c, self.t)
+    }
+}
+
+// Sizes and layout of one series, as Bio-Formats' CoreMetadata
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoreMetadata {
+    pub size_x: u64,
+    pub size_y: u64,
+    pub size_z: u64,
+    pub size_c: u64,
+    pub size_t: u64,
+    // Of the first channel
+    pub pixel_type: PixelType,
+    pub dimension_order: DimensionOrder,
+    pub rgb: bool,
+    pub interleaved: bool,
+    // Size of a thumbnail preserving the aspect ratio, at most 128 across
+    pub thumb_size_x: u64,
+    pub thumb_size_y: u64,
+    pub resolution_count: u64,
+}
+
+impl CoreMetadata {
+    // Number of (z, c, t) planes
+    pub fn image_count(&self) -> u64 {
+        self.size_z * self.size_c * self.size_t
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ByteOrder {
+    BE,
+    LE,
+}
+
+// How much metadata a reader should gather, mirroring Bio-Formats'
+// MetadataLevel. Lower levels skip the parsing of vendor metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MetadataLevel {
+    // Core dimensions and pixel layout only
+    Minimum,
+    // Everything translated into the model, original metadata discarded
+    NoOriginal,
+    #[default]
+    All,
+}
+
+// File-level information shared by every series
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlobalMetadata {
+    pub(crate) byte_order: ByteOrder,
+    pub(crate) rois: Vec<Roi>,
+    pub(crate) plates: Vec<Plate>,
+    pub(crate) annotations: StructuredAnnotations,
+    pub(crate) instruments: Vec<Instrument>,
+    pub(crate) experimenters: Vec<Experimenter>,
+    pub(crate) projects: Vec<Project>,
+    pub(crate) datasets: Vec<Dataset>,
+    pub(crate) original_metadata: OriginalMetadata,
+}
+
+// Core information describing the pixels of a single series
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeriesMetadata {
+    pub(crate) image_id: Option<String>,
+    pub(crate) name: Option<String>,
+    pub(crate) experimenter: Option<String>,
+    pub(crate) dimensions: Dim,
+    // We allow the sample type to vary between channels
+    pub(crate) pixel_types: Vec<PixelType>,
+    // Bits actually used within each sample, e.g. 12 bit data stored in 16
+    pub(crate) significant_bits: Option<u16>,
+    pub(crate) dimension_order: DimensionOrder,
+    pub(crate) interleaved: bool,
+    pub(crate) resolution_count: u64,
+    pub(crate) modulo: Vec<Modulo>,
+    pub(crate) acquisition_date: Option<Timestamp>,
+    pub(crate) stage_label: Option<StageLabel>,
+    // Width and height of a full resolution pixel
+    pub(crate) physical_size_x: Option<Length>,
+    pub(crate) physical_size_y: Option<Length>,
+    pub(crate) planes: Vec<Plane>,
+    pub(crate) channels: Vec<Channel>,
+    pub(crate) tiff_data: Vec<TiffData>,
+    pub(crate) original_metadata: OriginalMetadata,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metadata {
+    pub(crate) global: GlobalMetadata,
+    pub(crate) series: Vec<SeriesMetadata>,
+}
+
+impl GlobalMetadata {
+    pub(crate) fn new(byte_order: ByteOrder) -> Self {
+        GlobalMetadata {
+            byte_order,
+            rois: Vec::new(),
+            plates: Vec::new(),
+            annotations: Default::default(),
+            instruments: Vec::new(),
+            experimenters: Vec::new(),
+            projects: Vec::new(),
+            datasets: Vec::new(),
+            original_metadata: Default::default(),
+        }
+    }
+
+    pub fn byte_order(&self) -> &ByteOrder {
+        &self.byte_order
+    }
+
+    pub fn rois(&self) -> &[Roi] {
+        &self.rois
+    }
+
+    pub fn annotations(&self) -> &StructuredAnnotations {
+        &self.annotations
+    }
+
+    // Allows custom key/value metadata to be attached before writing
+    pub fn annotations_mut(&mut self) -> &mut StructuredAnnotations {
+        &mut self.annotations
+    }
+
+    // High content screening layout, empty for non-HCS data
+    pub fn plates(&self) -> &[Plate] {
+        &self.plates
+    }
+
+    // Microscope hardware, including the filters and dichroics referenced
+    // by channel light paths
+    pub fn instruments(&self) -> &[Instrument] {
+        &self.instruments
+    }
+
+    pub fn experimenters(&self) -> &[Experimenter] {
+        &self.experimenters
+    }
+
+    pub fn experimenter(&self, id: &str) -> Option<&Experimenter> {
+        self.experimenters.iter().find(|e| e.id == id)
+    }
+
+    pub fn projects(&self) -> &[Project] {
+        &self.projects
+    }
+
+    pub fn datasets(&self) -> &[Dataset] {
+        &self.datasets
+    }
+
+    // Datasets containing the Image with the given ID
+    pub fn datasets_for(&self, image_id: &str) -> impl Iterator<Item = &Dataset> {
+        self.datasets
+            .iter()
+            .filter(move |d| d.images.iter().any(|i| i == image_id))
+    }
+
+    pub fn original_metadata(&self) -> &OriginalMetadata {
+        &self.original_metadata
+    }
+}
+
+impl SeriesMetadata {
+    pub(crate) fn new(dimensions: Dim, pixel_types: Vec<PixelType>, interleaved: bool) -> Self {
+        let n_channels = dimensions.c as usize;
+
+        SeriesMetadata {
+            image_id: None,
+            name: None,
+            experimenter: None,
+            dimensions,
+            pixel_types,
+            significant_bits: None,
+            dimension_order: DimensionOrder::default(),
+            interleaved,
+            resolution_count: 1,
+            modulo: Vec::new(),
+            acquisition_date: None,
+            stage_label: None,
+            physical_size_x: None,
+            physical_size_y: None,
+            planes: Vec::new(),
+            channels: vec![Channel::default(); n_channels],
+            tiff_data: Vec::new(),
+            original_metadata: Default::default(),
+        }
+    }
+
+    // ID of the OME Image describing this series, where there is one
+    pub fn image_id(&self) -> Option<&str> {
+        self.image_id.as_deref()
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    // ID of the Experimenter who acquired the series
+    pub fn experimenter(&self) -> Option<&str> {
+        self.experimenter.as_deref()
+    }
+
+    pub(crate) fn dimensions(&self) -> &Dim {
+        &self.dimensions
+    }
+
+    pub fn core(&self) -> CoreMetadata {
+        let Dim { w, h, d, t, c } = self.dimensions;
+        let thumb = |v: u64| (v * 128 / w.max(h).max(128)).max(1);
+
+        CoreMetadata {
+            size_x: w,
+            size_y: h,
+            size_z: d,
+            size_c: c,
+            size_t: t,
+            pixel_type: self.pixel_type(0).unwrap_or(PixelType::U8),
+            dimension_order: self.dimension_order,
+            rgb: self.samples_per_pixel() > 1,
+            interleaved: self.interleaved,
+            thumb_size_x: thumb(w),
+            thumb_size_y: thumb(h),
+            resolution_count: self.resolution_count,
+        }
+    }
+
+    pub fn pixel_type(&self, c: u64) -> Option<PixelType> {
+        self.pixel_types.get(c as usize).copied()
+    }
+
+    pub fn bits_per_pixel(&self, c: u64) -> Option<u16> {
+        self.pixel_type(c).map(|pt| pt.bits())
+    }
+
+    // Falls back to the storage bits when the file doesn't say otherwise
+    pub fn significant_bits(&self, c: u64) -> Option<u16> {
+        let bits = self.bits_per_pixel(c)?;
+        Some(self.significant_bits.map_or(bits, |sb| sb.min(bits)))
+    }
+
+    pub fn dimension_order(&self) -> DimensionOrder {
+        self.dimension_order
+    }
+
+    // Whether the channels of a pixel are stored contiguously
+    pub fn is_interleaved(&self) -> bool {
+        self.interleaved
+    }
+
+    // Samples stored together in each pixel, e.g. 3 for RGB. Without an
+    // OME SamplesPerPixel, interleaved channels all share the pixel
+    pub fn samples_per_pixel(&self) -> u64 {
+        match self.channels.first().and_then(|ch| ch.samples_per_pixel) {
+            Some(n) => n.max(1),
+            None if self.interleaved => self.dimensions.size_c(),
+            None => 1,
+        }
+    }
+
+    pub fn resolution_count(&self) -> u64 {
+        self.resolution_count
+    }
+
+    // Map a linear plane index to its (z, c, t) coordinates
+    pub fn index_to_zct(&self, index: u64) -> io::Result<(u64, u64, u64)> {
+        self.dimension_order
+            .index_to_zct(self.dimensions.zct(), index)
+    }
+
+    // Map (z, c, t) coordinates to a linear plane index
+    pub fn zct_to_index(&self, z: u64, c: u64, t: u64) -> io::Result<u64> {
+        self.dimension_order
+            .zct_to_index(self.dimensions.zct(), (z, c, t))
+    }
+
+    pub fn modulo(&self, axis: ModuloAxis) -> Option<&Modulo> {
+        self.modulo.iter().find(|m| m.axis == axis)
+    }
+
+    // (size_z, size_c, size_t) with any Modulo sub-dimensions factored out.
+    // Err where a Modulo's length doesn't divide the size along its axis
+    pub fn effective_sizes(&self) -> io::Result<(u64, u64, u64)> {
+        let (z, c, t) = self.dimensions.zct();
+        let size = |axis, name, size: u64| -> io::Result<u64> {
+            let len = self.modulo(axis).map(|m| m.length()).unwrap_or(1);
+            match size % len {
+                0 => Ok(size / len),
+                _ => Err(io::Error::other(format!(
+                    "{name} of {size} isn't a multiple of the Modulo length {len}"
+                ))),
+            }
+        };
+
+        Ok((
+            size(ModuloAxis::Z, "SizeZ", z)?,
+            size(ModuloAxis::C, "SizeC", c)?,
+            size(ModuloAxis::T, "SizeT", t)?,
+        ))
+    }
+
+    pub fn acquisition_date(&self) -> Option<&Timestamp> {
+        self.acquisition_date.as_ref()
+    }
+
+    pub fn stage_label(&self) -> Option<&StageLabel> {
+        self.stage_label.as_ref()
+    }
+
+    pub fn physical_size_x(&self) -> Option<Length> {
+        self.physical_size_x
+    }
+
+    pub fn physical_size_y(&self) -> Option<Length> {
+        self.physical_size_y
+    }
+
+    // Physical (x, y, z) position of the series, from the StageLabel or
+    // else the first plane, axis by axis, for placing multi-position
+    // acquisitions
+    pub fn position(&self) -> (Option<Length>, Option<Length>, Option<Length>) {
+        let label = self.stage_label.as_ref();
+        let plane = self.planes.first();
+
+        (
+            label.and_then(|l| l.x).or(plane.and_then(|p| p.position_x)),
+            label.and_then(|l| l.y).or(plane.and_then(|p| p.position_y)),
+            label.and_then(|l| l.z).or(plane.and_then(|p| p.position_z)),
+        )
+    }
+
+    // Per-plane acquisition data, where the file provides it
+    pub fn planes(&self) -> &[Plane] {
+        &self.planes
+    }
+
+    pub fn plane(&self, z: u64, c: u64, t: u64) -> Option<&Plane> {
+        self.planes
+            .iter()
+            .find(|p| (p.the_z, p.the_c, p.the_t) == (z, c, t))
+    }
+
+    // OME TiffData elements describing where the planes are stored
+    pub fn tiff_data(&self) -> &[TiffData] {
+        &self.tiff_data
+    }
+
+    pub fn plane_map(&self) -> io::Result<PlaneMap> {
+        PlaneMap::new(&self.tiff_data, self.dimension_order, self.dimensions.zct())
+    }
+
+    pub fn channels(&self) -> &[Channel] {
+        &self.channels
+    }
+
+    pub fn channel(&self, c: u64) -> Option<&Channel> {
+        self.channels.get(c as usize)
+    }
+
+    // Color to render channel c with; falls back to red/green/blue for
+    // three channel series and white otherwise
+    pub fn display_color(&self, c: u64) -> Color {
+        self.channel(c)
+            .and_then(|ch| ch.color)
+            .unwrap_or(match (self.dimensions.c, c) {
+                (3, 0) => Color::RED,
+                (3, 1) => Color::GREEN,
+                (3, 2) => Color::BLUE,
+                _ => Color::WHITE,
+            })
+    }
+
+    // Display range for channel c; falls back to the full range of the
+    // channel's significant bits
+    pub fn display_window(&self, c: u64) -> (f64, f64) {
+        self.channel(c).and_then(|ch| ch.window).unwrap_or_else(|| {
+            let bits = self.significant_bits(c).unwrap_or(8) as i32;
+            (0.0, 2f64.powi(bits) - 1.0)
+        })
+    }
+
+    pub fn original_metadata(&self) -> &OriginalMetadata {
+        &self.original_metadata
+    }
+}
+
+impl Metadata {
+    pub fn global(&self) -> &GlobalMetadata {
+        &self.global
+    }
+
+    pub fn global_mut(&mut self) -> &mut GlobalMetadata {
+        &mut self.global
+    }
+
+    pub fn series_count(&self) -> u64 {
+        self.series.len() as u64
+    }
+
+    pub fn series(&self, series: u64) -> io::Result<&SeriesMetadata> {
+        self.series
+            .get(series as usize)
+            .ok_or(io::Error::other(format!("Invalid series: {series}")))
+    }
+
+    pub fn byte_order(&self) -> &ByteOrder {
+        self.global.byte_order()
+    }
+
+    // Check a read of region at loc fits within the series, naming the
+    // offending axis otherwise
+    pub fn validate(&self, series: u64, loc: &Loc, region: Region) -> io::Result<()> {
+        let dim = self.series(series)?.dimensions();
+
+        for (axis, index, size) in [
+            ("Z", loc.z, dim.size_z()),
+            ("C", loc.c, dim.size_c()),
+            ("T", loc.t, dim.size_t()),
+        ] {
+            if index >= size {
+                return Err(OmeError::OutOfBounds { axis, index, size }.into());
+            }
+        }
+
+        region.validate(dim.size_x(), dim.size_y())
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> io::Result<String> {
+        serde_json::to_string_pretty(self).map_err(io::Error::other)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> io::Result<Metadata> {
+        serde_json::from_str(json).map_err(io::Error::other)
+    }
+}
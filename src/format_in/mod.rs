@@ -1,119 +1,780 @@
-use std::{
-    collections::HashMap,
-    io::{self},
-};
+use std::borrow::Cow;
+use std::io::{self};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::OmeError;
+use crate::units::LengthUnit;
 
+pub mod aggregate;
+#[cfg(feature = "ndarray")]
+pub mod array;
+#[cfg(feature = "tokio")]
+pub mod async_reader;
+pub mod bit_depth;
+pub mod companion;
+pub mod composite;
+pub mod correction;
+pub mod detect;
+pub mod dimension_order;
+pub mod downsample;
+#[cfg(feature = "image")]
+pub mod dynamic_image;
+#[cfg(feature = "hash")]
+pub mod hash;
+pub mod histogram;
+pub mod image_reader;
+pub mod layout;
+pub mod lazy;
+pub mod lut;
+pub mod metadata;
+pub mod mosaic;
+pub mod options;
+pub mod pixel;
+pub mod pixel_type;
+pub mod pool;
+pub mod progress;
+pub mod projection;
+pub mod read_plan;
+pub mod reader_cache;
+pub mod region;
+pub mod remote;
+pub mod render;
+pub mod source;
+pub mod stack;
+pub mod stats;
+#[cfg(test)]
+pub(crate) mod test_support;
 pub mod tiff;
 pub mod tiff_reader;
+pub mod tile_cache;
+pub mod tiles;
+pub mod translate;
+pub mod wrappers;
 
-type ChannelSeries = (u64, u64);
-type ChannelSeriesMap<T> = HashMap<ChannelSeries, T>;
+#[cfg(feature = "ndarray")]
+pub use array::{open_array2, open_array3};
+#[cfg(feature = "tokio")]
+pub use async_reader::{AsyncFormatReader, BlockingReader};
+pub use bit_depth::{Overflow, Rounding};
+pub use composite::{CompositeChannel, composite, open_composite};
+pub use correction::Correction;
+pub use detect::{FormatDetector, ReaderRegistry};
+pub use dimension_order::{Axis, DimensionOrder};
+pub use downsample::{Downsample, downsample, downsampled_size};
+#[cfg(feature = "image")]
+pub use dynamic_image::{open_image, open_rgb_image};
+#[cfg(feature = "hash")]
+pub use hash::{HashAlgorithm, PlaneDigest, manifest, plane_digest};
+pub use histogram::{Histogram, channel_histogram, plane_histogram};
+pub use image_reader::ImageReader;
+pub use lazy::LazyPlane;
+pub use lut::{Colormap, Lut};
+pub(crate) use metadata::Dim;
+pub use metadata::{
+    ByteOrder, CoreMetadata, GlobalMetadata, Metadata, MetadataLevel, SeriesMetadata,
+};
+pub use mosaic::{Blend, MosaicTile, assemble};
+pub use options::ReaderOptions;
+pub use pixel::{Normalization, Pixel};
+pub use pixel_type::PixelType;
+pub use pool::{PooledReader, ReaderPool};
+pub use progress::Progress;
+pub use projection::{Projection, project_z};
+pub use read_plan::{IoProfile, ReadPlan};
+pub use reader_cache::ReaderCache;
+pub use region::{OutOfBounds, Region};
+pub use render::{Adjustment, RenderPipeline, Scaling, apply_colormap, apply_lut, to_u8};
+pub use source::{ReadAt, Source};
+pub use stats::{PlaneStats, plane_stats};
+pub use tile_cache::TileCache;
+pub use tiles::Tiles;
 
-#[derive(Clone, Copy, Default)]
+// A plane within a series, checked against its sizes with
+// Metadata::validate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct Loc {
-    x: u64,
-    y: u64,
     z: u64,
     c: u64,
     t: u64,
-    s: u64,
 }
 
 impl Loc {
-    fn new(x: u64, y: u64, z: u64, c: u64, t: u64, s: u64) -> Self {
-        Loc { x, y, z, c, t, s }
+    pub fn new(z: u64, c: u64, t: u64) -> Self {
+        Loc { z, c, t }
     }
 
-    fn channel_series(&self) -> ChannelSeries {
-        (self.c, self.s)
+    pub fn z(&self) -> u64 {
+        self.z
+    }
+
+    pub fn c(&self) -> u64 {
+        self.c
+    }
+
+    pub fn t(&self) -> u64 {
+        self.t
     }
 }
 
-#[derive(Debug)]
-pub struct Dim {
-    w: u64,
-    h: u64,
-    d: u64,
-    t: u64,
-    c: u64,
+#[derive(Debug, Clone, PartialEq)]
+pub enum PixelSlice {
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+    I8(Vec<i8>),
+    I16(Vec<i16>),
+    I32(Vec<i32>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    // [real, imaginary] pairs
+    C64(Vec<[f32; 2]>),
+    C128(Vec<[f64; 2]>),
 }
 
-impl Dim {
-    fn from_whc(w: u64, h: u64, d: u64) -> Self {
-        Self {
-            w,
-            h,
-            d,
-            t: 1,
-            c: 1,
+impl PixelSlice {
+    // Samples as f64; the magnitude of complex samples
+    pub fn to_f64(&self) -> Vec<f64> {
+        match self {
+            PixelSlice::U8(v) => v.iter().map(|a| *a as f64).collect(),
+            PixelSlice::U16(v) => v.iter().map(|a| *a as f64).collect(),
+            PixelSlice::U32(v) => v.iter().map(|a| *a as f64).collect(),
+            PixelSlice::I8(v) => v.iter().map(|a| *a as f64).collect(),
+            PixelSlice::I16(v) => v.iter().map(|a| *a as f64).collect(),
+            PixelSlice::I32(v) => v.iter().map(|a| *a as f64).collect(),
+            PixelSlice::F32(v) => v.iter().map(|a| *a as f64).collect(),
+            PixelSlice::F64(v) => v.clone(),
+            PixelSlice::C64(v) => v.iter().map(|[re, im]| re.hypot(*im) as f64).collect(),
+            PixelSlice::C128(v) => v.iter().map(|[re, im]| re.hypot(*im)).collect(),
         }
     }
-}
 
-#[derive(Debug)]
-pub enum ByteOrder {
-    BE,
-    LE,
-}
+    // Values as pixel_type, rounded and saturated for integer types. Complex
+    // types take the values as their real part
+    pub fn from_f64(values: &[f64], pixel_type: PixelType) -> Self {
+        macro_rules! cast {
+            ($variant:ident, $t:ty) => {
+                PixelSlice::$variant(values.iter().map(|v| v.round() as $t).collect())
+            };
+        }
 
-#[derive(Debug)]
-pub struct Metadata {
-    dimensions: HashMap<u64, Dim>,
-    bits_per_pixel: ChannelSeriesMap<u16>,
-    byte_order: ByteOrder,
-}
+        match pixel_type {
+            PixelType::U8 => cast!(U8, u8),
+            PixelType::U16 => cast!(U16, u16),
+            PixelType::U32 => cast!(U32, u32),
+            PixelType::I8 => cast!(I8, i8),
+            PixelType::I16 => cast!(I16, i16),
+            PixelType::I32 => cast!(I32, i32),
+            PixelType::F16 | PixelType::F32 => {
+                PixelSlice::F32(values.iter().map(|v| *v as f32).collect())
+            }
+            PixelType::F64 => PixelSlice::F64(values.to_vec()),
+            PixelType::C64 => PixelSlice::C64(values.iter().map(|v| [*v as f32, 0.0]).collect()),
+            PixelType::C128 => PixelSlice::C128(values.iter().map(|v| [*v, 0.0]).collect()),
+        }
+    }
+
+    pub fn pixel_type(&self) -> PixelType {
+        match self {
+            PixelSlice::U8(_) => PixelType::U8,
+            PixelSlice::U16(_) => PixelType::U16,
+            PixelSlice::U32(_) => PixelType::U32,
+            PixelSlice::I8(_) => PixelType::I8,
+            PixelSlice::I16(_) => PixelType::I16,
+            PixelSlice::I32(_) => PixelType::I32,
+            PixelSlice::F32(_) => PixelType::F32,
+            PixelSlice::F64(_) => PixelType::F64,
+            PixelSlice::C64(_) => PixelType::C64,
+            PixelSlice::C128(_) => PixelType::C128,
+        }
+    }
 
-impl Metadata {
-    // We allow the bit depth to vary between channels/series
-    fn bits_per_pixel(&self, cs: ChannelSeries) -> Option<&u16> {
-        self.bits_per_pixel.get(&cs)
+    // Number of samples
+    pub fn len(&self) -> usize {
+        match self {
+            PixelSlice::U8(v) => v.len(),
+            PixelSlice::U16(v) => v.len(),
+            PixelSlice::U32(v) => v.len(),
+            PixelSlice::I8(v) => v.len(),
+            PixelSlice::I16(v) => v.len(),
+            PixelSlice::I32(v) => v.len(),
+            PixelSlice::F32(v) => v.len(),
+            PixelSlice::F64(v) => v.len(),
+            PixelSlice::C64(v) => v.len(),
+            PixelSlice::C128(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
-    fn byte_order(&self) -> &ByteOrder {
-        &self.byte_order
+    // Transform every sample in place, keeping the type: results are
+    // rounded and saturated for integer types. Both parts of complex
+    // samples are transformed
+    pub fn apply(&mut self, f: impl Fn(f64) -> f64) {
+        fn each<T: Pixel + Into<f64>>(values: &mut [T], f: &impl Fn(f64) -> f64) {
+            for v in values.iter_mut() {
+                let r = f((*v).into());
+                *v = T::from_float(if T::FLOAT { r } else { r.round() });
+            }
+        }
+
+        match self {
+            PixelSlice::U8(v) => each(v, &f),
+            PixelSlice::U16(v) => each(v, &f),
+            PixelSlice::U32(v) => each(v, &f),
+            PixelSlice::I8(v) => each(v, &f),
+            PixelSlice::I16(v) => each(v, &f),
+            PixelSlice::I32(v) => each(v, &f),
+            PixelSlice::F32(v) => each(v, &f),
+            PixelSlice::F64(v) => each(v, &f),
+            PixelSlice::C64(v) => each(v.as_flattened_mut(), &f),
+            PixelSlice::C128(v) => each(v.as_flattened_mut(), &f),
+        }
+    }
+
+    // A transformed copy, see apply
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> Self {
+        let mut mapped = self.clone();
+        mapped.apply(f);
+        mapped
     }
 }
 
-#[derive(Debug)]
-pub enum PixelSlice {
-    U8(Vec<u8>),
-    U16(Vec<u16>),
-    // and so on ...
+impl From<&PixelSlice> for Vec<f64> {
+    fn from(pixels: &PixelSlice) -> Self {
+        pixels.to_f64()
+    }
+}
+
+impl From<Vec<f64>> for PixelSlice {
+    fn from(values: Vec<f64>) -> Self {
+        PixelSlice::F64(values)
+    }
 }
 
 pub trait FormatReader {
     // ----------------- Required -------------------
 
+    // Initialize the reader from a file, closing any previous one
+    fn set_id(&mut self, id: &Path) -> io::Result<()>;
+
+    // Release open files, set_id must be called again before further reads
+    fn close(&mut self) -> io::Result<()>;
+
+    // Every file backing the current dataset, including the one given to
+    // set_id, e.g. so a multi-file dataset can be copied as a whole
+    fn used_files(&self) -> io::Result<Vec<PathBuf>>;
+
+    // Series subsequent reads refer to, 0 after set_id
+    fn series(&self) -> u64;
+
+    fn set_series(&mut self, series: u64) -> io::Result<()>;
+
     fn metadata(&mut self) -> io::Result<Metadata>;
 
-    // Read rectangular portion of image data at given location
+    // The metadata without copying it, for the reads and accessors which
+    // consult it on every call. Readers caching their metadata return the
+    // cached copy
+    fn metadata_arc(&mut self) -> io::Result<Arc<Metadata>> {
+        Ok(Arc::new(self.metadata()?))
+    }
+
+    // Whether metadata() is still partial, e.g. under fast init until the
+    // first pixel read, so it shouldn't be kept beyond the call
+    fn is_deferred(&self) -> bool {
+        false
+    }
+
+    // Read a region of the plane at the given location
     // returns bytes, image metadata should be used to decode bytes
-    fn open_bytes(&mut self, origin: Loc, h: u64, w: u64) -> io::Result<Vec<u8>>;
+    fn open_bytes(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u8>>;
 
     // ----------------- Derived -------------------
 
-    // Read rectangular portion of image data at given location
-    // returns PixelSlice
-    fn open_pixels(&mut self, origin: Loc, h: u64, w: u64) -> io::Result<PixelSlice> {
-        let bytes = self.open_bytes(origin, h, w)?;
-        let md = self.metadata()?;
-
-        let bbp = md
-            .bits_per_pixel(origin.channel_series())
-            .ok_or(io::Error::other("Error reading bpp"))?;
-
-        match bbp {
-            8 => Ok(PixelSlice::U8(bytes)),
-            16 => Ok(PixelSlice::U16(
-                bytes
-                    .chunks_exact(2)
-                    .map(|a| match md.byte_order {
-                        ByteOrder::LE => u16::from_le_bytes([a[0], a[1]]),
-                        ByteOrder::BE => u16::from_be_bytes([a[0], a[1]]),
-                    })
-                    .collect(),
-            )),
-            _ => Err(io::Error::other("Unsupported PixelSlice Format")),
+    fn options(&self) -> ReaderOptions {
+        ReaderOptions::default()
+    }
+
+    // Takes effect from the next set_id
+    fn set_options(&mut self, _options: ReaderOptions) {}
+
+    // Pyramid level reads refer to, 0 being full resolution. Readers
+    // without pyramids, or flattening them into series, only have level 0
+    fn resolution(&self) -> u64 {
+        0
+    }
+
+    fn set_resolution(&mut self, resolution: u64) -> io::Result<()> {
+        match resolution {
+            0 => Ok(()),
+            _ => Err(io::Error::other(format!("Invalid resolution {resolution}"))),
+        }
+    }
+
+    fn resolution_count(&mut self) -> io::Result<u64> {
+        Ok(self
+            .metadata_arc()?
+            .series(self.series())?
+            .resolution_count())
+    }
+
+    // (size_x, size_y) at the current resolution
+    fn resolution_size(&mut self) -> io::Result<(u64, u64)> {
+        Ok((self.size_x()?, self.size_y()?))
+    }
+
+    // The region a read of region returns under the out of bounds policy:
+    // clamped to the plane if asked, otherwise unchanged
+    fn bounded_region(&mut self, region: Region) -> io::Result<Region> {
+        if self.options().out_of_bounds() != OutOfBounds::Clamp || region.is_empty() {
+            return Ok(region);
+        }
+
+        let (w, h) = self.resolution_size()?;
+        match region.intersection(&Region::full(w, h)) {
+            Some(clamped) => Ok(clamped),
+            None => region.validate(w, h).map(|_| region),
+        }
+    }
+
+    // As open_bytes, but borrowing the bytes where the reader already holds
+    // the region exactly as stored, e.g. uncompressed rows of a TIFF in
+    // memory, rather than copying them
+    fn open_bytes_cow(&mut self, loc: Loc, region: Region) -> io::Result<Cow<'_, [u8]>> {
+        Ok(Cow::Owned(self.open_bytes(loc, region)?))
+    }
+
+    // As open_bytes, but into a caller-provided buffer so hot loops can
+    // reuse it. buf must hold exactly region.area() samples of channel c;
+    // a clamped read fills the front of it
+    fn open_bytes_into(&mut self, loc: Loc, region: Region, buf: &mut [u8]) -> io::Result<()> {
+        let bounded = self.bounded_region(region)?;
+        let bytes = self.open_bytes(loc, bounded)?;
+        let per_pixel = bytes.len() / (bounded.area() as usize).max(1);
+        let buf = match buf.len() == region.area() as usize * per_pixel {
+            true => &mut buf[..bytes.len()],
+            false => buf,
+        };
+        if bytes.len() != buf.len() {
+            return Err(io::Error::other(format!(
+                "Buffer holds {} bytes, region needs {}",
+                buf.len(),
+                bytes.len()
+            )));
+        }
+
+        buf.copy_from_slice(&bytes);
+        Ok(())
+    }
+
+    // A region of a 16 bit unsigned plane, in native byte order
+    fn open_u16(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u16>> {
+        let order = u16_order(self, loc)?;
+        let bytes = self.open_bytes(loc, region)?;
+        Ok(pixel::decode_u16(&bytes, order))
+    }
+
+    // As open_u16 into a buffer of the caller's. Readers which copy samples
+    // out of decoded strips or tiles swap their bytes as they go, rather
+    // than through a buffer of bytes
+    fn open_u16_into(&mut self, loc: Loc, region: Region, buf: &mut [u16]) -> io::Result<()> {
+        let order = u16_order(self, loc)?;
+        self.open_bytes_into(loc, region, pixel::u16_bytes_mut(buf))?;
+        pixel::u16_to_native(buf, order);
+        Ok(())
+    }
+
+    fn series_count(&mut self) -> io::Result<u64> {
+        Ok(self.metadata_arc()?.series_count())
+    }
+
+    // Sizes and layout of the current series
+    fn core_metadata(&mut self) -> io::Result<CoreMetadata> {
+        Ok(self.metadata_arc()?.series(self.series())?.core())
+    }
+
+    fn size_x(&mut self) -> io::Result<u64> {
+        Ok(self.core_metadata()?.size_x)
+    }
+
+    fn size_y(&mut self) -> io::Result<u64> {
+        Ok(self.core_metadata()?.size_y)
+    }
+
+    fn size_z(&mut self) -> io::Result<u64> {
+        Ok(self.core_metadata()?.size_z)
+    }
+
+    fn size_c(&mut self) -> io::Result<u64> {
+        Ok(self.core_metadata()?.size_c)
+    }
+
+    fn size_t(&mut self) -> io::Result<u64> {
+        Ok(self.core_metadata()?.size_t)
+    }
+
+    // Number of (z, c, t) planes in the current series
+    fn image_count(&mut self) -> io::Result<u64> {
+        Ok(self.core_metadata()?.image_count())
+    }
+
+    // Sample type of the first channel of the current series
+    fn pixel_type(&mut self) -> io::Result<PixelType> {
+        self.metadata_arc()?
+            .series(self.series())?
+            .pixel_type(0)
+            .ok_or(io::Error::other("Error reading pixel type"))
+    }
+
+    // Whether channels of the current series are samples within a pixel,
+    // as in RGB, rather than separate planes
+    fn is_rgb(&mut self) -> io::Result<bool> {
+        Ok(self.samples_per_pixel()? > 1)
+    }
+
+    fn is_interleaved(&mut self) -> io::Result<bool> {
+        Ok(self.metadata_arc()?.series(self.series())?.is_interleaved())
+    }
+
+    fn samples_per_pixel(&mut self) -> io::Result<u64> {
+        Ok(self
+            .metadata_arc()?
+            .series(self.series())?
+            .samples_per_pixel())
+    }
+
+    // Lookup table channel c of the current series is rendered through,
+    // None for plain grayscale
+    fn get_lut(&mut self, c: u64) -> io::Result<Option<Lut>> {
+        let md = self.metadata_arc()?;
+        Ok(Lut::for_channel(md.series(self.series())?, c))
+    }
+
+    // Whether plane loc of the current series is stored at all, e.g. a
+    // multi-file dataset may lack some of its files
+    fn plane_present(&mut self, _loc: Loc) -> io::Result<bool> {
+        Ok(true)
+    }
+
+    // Every plane of the current series which isn't stored
+    fn missing_planes(&mut self) -> io::Result<Vec<Loc>> {
+        let core = self.core_metadata()?;
+        let mut missing = Vec::new();
+
+        for t in 0..core.size_t {
+            for c in 0..core.size_c {
+                for z in 0..core.size_z {
+                    let loc = Loc::new(z, c, t);
+                    if !self.plane_present(loc)? {
+                        missing.push(loc);
+                    }
+                }
+            }
+        }
+
+        Ok(missing)
+    }
+
+    fn is_little_endian(&mut self) -> io::Result<bool> {
+        Ok(*self.metadata_arc()?.byte_order() == ByteOrder::LE)
+    }
+
+    // (width, height) of the blocks the current series is stored in, e.g.
+    // TIFF strips; reads aligned to these avoid decoding data twice
+    fn optimal_tile_size(&mut self) -> io::Result<(u64, u64)> {
+        Ok((self.size_x()?, self.size_y()?))
+    }
+
+    // Walk every plane of a series in tiles of roughly tile_w by tile_h,
+    // aligned to optimal_tile_size where the requested size allows
+    fn tiles(&mut self, series: u64, tile_w: u64, tile_h: u64) -> io::Result<Tiles<'_, Self>>
+    where
+        Self: Sized,
+    {
+        Tiles::new(self, series, tile_w, tile_h)
+    }
+
+    // A handle to the whole of plane loc at the current series and
+    // resolution, read only when asked; see LazyPlane
+    fn lazy_plane(&mut self, loc: Loc) -> io::Result<LazyPlane> {
+        let series = self.series();
+        self.metadata_arc()?
+            .validate(series, &loc, Region::new(0, 0, 1, 1))?;
+        let (w, h) = self.resolution_size()?;
+        Ok(LazyPlane::new(
+            series,
+            self.resolution(),
+            loc,
+            Region::full(w, h),
+        ))
+    }
+
+    // Read the whole of plane (z, c, t) of the current series
+    fn open_plane(&mut self, z: u64, c: u64, t: u64) -> io::Result<Vec<u8>> {
+        let region = Region::full(self.size_x()?, self.size_y()?);
+        self.open_plane_region(z, c, t, region)
+    }
+
+    fn open_plane_region(&mut self, z: u64, c: u64, t: u64, region: Region) -> io::Result<Vec<u8>> {
+        self.open_bytes(Loc::new(z, c, t), region)
+    }
+
+    // Switch to the coarsest pyramid level whose pixels are no wider than
+    // um_per_pixel, e.g. for a viewer's zoom, returning the µm per pixel of
+    // that level. Full resolution is used when even that is coarser
+    fn select_resolution(&mut self, um_per_pixel: f64) -> io::Result<f64> {
+        let full = self
+            .metadata_arc()?
+            .series(self.series())?
+            .physical_size_x()
+            .and_then(|size| size.value_in(LengthUnit::Micrometer))
+            .ok_or(io::Error::other("No physical pixel size"))?;
+        let size_x = self.size_x()?;
+
+        let (mut level, mut level_size) = (0, full);
+        for l in 1..self.resolution_count()? {
+            self.set_resolution(l)?;
+            let size = full * size_x as f64 / self.resolution_size()?.0 as f64;
+            if size > um_per_pixel {
+                break;
+            }
+            (level, level_size) = (l, size);
         }
+
+        self.set_resolution(level)?;
+        Ok(level_size)
+    }
+
+    // Read every plane of a series into one buffer, ordered XYZCT: x varies
+    // fastest, then y, z, c and finally t. Fails rather than allocating
+    // beyond options().memory_limit() or what the system can provide
+    fn open_stack(&mut self, series: u64) -> io::Result<Vec<u8>> {
+        let current = self.series();
+        self.set_series(series)?;
+        let stack = stack::read_stack(self);
+        self.set_series(current)?;
+        stack
     }
+
+    // Read a full resolution region at roughly `scale` (0 to 1) times its
+    // size, e.g. for a zoomed out view. The smallest pyramid level holding
+    // enough detail is read and decimated to region.scaled(scale)
+    fn open_region_scaled(&mut self, loc: Loc, region: Region, scale: f64) -> io::Result<Vec<u8>> {
+        if !(scale > 0.0 && scale <= 1.0) {
+            return Err(io::Error::other(format!("Invalid scale {scale}")));
+        }
+
+        let (size_x, size_y) = (self.size_x()?, self.size_y()?);
+        region.validate(size_x, size_y)?;
+        let out = region.scaled(scale);
+        let bytes_per_sample = self
+            .metadata_arc()?
+            .series(self.series())?
+            .pixel_type(loc.c)
+            .ok_or(io::Error::other("Error reading pixel type"))?
+            .bytes();
+
+        // Levels shrink, so stop at the first too small for the scale
+        let current = self.resolution();
+        let (mut level, mut level_size) = (0, (size_x, size_y));
+        for l in 1..self.resolution_count()? {
+            self.set_resolution(l)?;
+            let (w, h) = self.resolution_size()?;
+            if w < out.w * size_x / region.w || h < out.h * size_y / region.h {
+                break;
+            }
+            (level, level_size) = (l, (w, h));
+        }
+
+        // The region in level coordinates, rounded outwards
+        let (lw, lh) = level_size;
+        let (x0, y0) = (region.x * lw / size_x, region.y * lh / size_y);
+        let x1 = ((region.x + region.w) * lw).div_ceil(size_x).min(lw);
+        let y1 = ((region.y + region.h) * lh).div_ceil(size_y).min(lh);
+        let src = Region::new(x0, y0, x1 - x0, y1 - y0);
+
+        self.set_resolution(level)?;
+        let bytes = self.open_bytes(loc, src);
+        self.set_resolution(current)?;
+
+        Ok(region::resample(
+            &bytes?,
+            (src.w, src.h),
+            (out.w, out.h),
+            bytes_per_sample,
+        ))
+    }
+
+    // Where plane loc of the current series starts in the file, for bulk
+    // reads to be ordered by; None where unknown
+    fn plane_offset(&mut self, _loc: Loc) -> io::Result<Option<u64>> {
+        Ok(None)
+    }
+
+    // Bytes of the whole planes locs of the current series at the current
+    // resolution, in the order asked. Planes are read forwards through the
+    // file where plane_offset knows the order; readers may plan further,
+    // e.g. sharing decoding between planes stored together
+    fn open_planes_bytes(&mut self, locs: &[Loc]) -> io::Result<Vec<Vec<u8>>> {
+        let (w, h) = self.resolution_size()?;
+        let mut order = Vec::with_capacity(locs.len());
+        for (i, loc) in locs.iter().enumerate() {
+            order.push((self.plane_offset(*loc)?.unwrap_or(u64::MAX), i));
+        }
+        order.sort();
+
+        let mut planes = vec![Vec::new(); locs.len()];
+        for (_, i) in order {
+            planes[i] = self.open_bytes(locs[i], Region::full(w, h))?;
+        }
+        Ok(planes)
+    }
+
+    // As open_pixels for the whole of each of locs, much as many calls to
+    // it would but with the reads planned together; see open_planes_bytes
+    fn open_planes(&mut self, locs: &[Loc]) -> io::Result<Vec<PixelSlice>> {
+        let (w, h) = self.resolution_size()?;
+        let planes = self.open_planes_bytes(locs)?;
+
+        locs.iter()
+            .zip(planes)
+            .map(|(loc, bytes)| to_pixels(self, *loc, Region::full(w, h), bytes))
+            .collect()
+    }
+
+    // Read a region of the plane at the given location, decoded into T.
+    // Samples are widened where T holds every value the file can store
+    fn open_as<T: Pixel>(&mut self, loc: Loc, region: Region) -> io::Result<Vec<T>>
+    where
+        Self: Sized,
+    {
+        let bytes = self.open_bytes(loc, region)?;
+        let md = self.metadata_arc()?;
+        let pixel_type = md
+            .series(self.series())?
+            .pixel_type(loc.c)
+            .ok_or(io::Error::other("Error reading pixel type"))?;
+
+        pixel::decode(&bytes, pixel_type, *md.byte_order())
+    }
+
+    // Read a region of the plane at the given location
+    // returns PixelSlice, f32 in [0, 1] if the options ask to normalize.
+    // Any correction in the options is applied first, to the region the
+    // out of bounds policy reads
+    fn open_pixels(&mut self, loc: Loc, region: Region) -> io::Result<PixelSlice> {
+        let region = self.bounded_region(region)?;
+        if self.options().normalization().is_none() && u16_order(self, loc).is_ok() {
+            let pixels = PixelSlice::U16(self.open_u16(loc, region)?);
+            return match self.options().correction() {
+                Some(correction) => {
+                    correction.check(self)?;
+                    correction.apply(&pixels, region)
+                }
+                None => Ok(pixels),
+            };
+        }
+
+        let bytes = self.open_bytes(loc, region)?;
+        to_pixels(self, loc, region, bytes)
+    }
+}
+
+// Byte order of the 16 bit unsigned samples of channel loc.c, failing for
+// samples of any other type
+pub(crate) fn u16_order<R: FormatReader + ?Sized>(
+    reader: &mut R,
+    loc: Loc,
+) -> io::Result<ByteOrder> {
+    let md = reader.metadata_arc()?;
+    let pixel_type = md
+        .series(reader.series())?
+        .pixel_type(loc.c)
+        .ok_or(io::Error::other("Error reading pixel type"))?;
+    if pixel_type != PixelType::U16 {
+        return Err(io::Error::other(format!(
+            "{} samples aren't 16 bit unsigned",
+            pixel_type.ome_name()
+        )));
+    }
+    Ok(*md.byte_order())
+}
+
+// Pixels of region of plane loc from the bytes the reader returned for it,
+// as open_pixels gives them. region is the one read, after any clamping
+pub(crate) fn to_pixels<R: FormatReader + ?Sized>(
+    reader: &mut R,
+    loc: Loc,
+    region: Region,
+    bytes: Vec<u8>,
+) -> io::Result<PixelSlice> {
+    let md = reader.metadata_arc()?;
+    let order = *md.byte_order();
+
+    let series = md.series(reader.series())?;
+    let pixel_type = series
+        .pixel_type(loc.c)
+        .ok_or(io::Error::other("Error reading pixel type"))?;
+    let options = reader.options();
+    if let Some(correction) = options.correction() {
+        correction.check(reader)?;
+    }
+
+    if let Some(normalization) = options.normalization() {
+        let mut values = pixel::decode::<f64>(&bytes, pixel_type, order)?;
+        if let Some(correction) = options.correction() {
+            correction.correct(&mut values, region)?;
+        }
+        let bits = series.significant_bits(loc.c).unwrap_or(pixel_type.bits());
+        return Ok(PixelSlice::F32(pixel::normalize(
+            &values,
+            pixel_type,
+            bits,
+            normalization,
+        )));
+    }
+
+    let pixels = match pixel_type {
+        PixelType::U8 => PixelSlice::U8(bytes),
+        PixelType::U16 => PixelSlice::U16(pixel::decode_u16(&bytes, order)),
+        PixelType::U32 => PixelSlice::U32(pixel::decode(&bytes, pixel_type, order)?),
+        PixelType::I8 => PixelSlice::I8(pixel::decode(&bytes, pixel_type, order)?),
+        PixelType::I16 => PixelSlice::I16(pixel::decode(&bytes, pixel_type, order)?),
+        PixelType::I32 => PixelSlice::I32(pixel::decode(&bytes, pixel_type, order)?),
+        PixelType::F16 | PixelType::F32 => {
+            PixelSlice::F32(pixel::decode(&bytes, pixel_type, order)?)
+        }
+        PixelType::F64 => PixelSlice::F64(pixel::decode(&bytes, pixel_type, order)?),
+        PixelType::C64 => PixelSlice::C64(pixel::decode_complex(&bytes, pixel_type, order)?),
+        PixelType::C128 => PixelSlice::C128(pixel::decode_complex(&bytes, pixel_type, order)?),
+    };
+
+    match options.correction() {
+        Some(correction) => correction.apply(&pixels, region),
+        None => Ok(pixels),
+    }
+}
+
+// What a multi-file reader returns for a plane whose file is missing: zeros
+// if the options ask for them, otherwise OmeError::PlaneMissing
+pub(crate) fn missing_plane<R: FormatReader + ?Sized>(
+    reader: &mut R,
+    loc: Loc,
+    region: Region,
+) -> io::Result<Vec<u8>> {
+    let series = reader.series();
+    if !reader.options().fill_missing_planes() {
+        let Loc { z, c, t } = loc;
+        return Err(OmeError::PlaneMissing { series, z, c, t }.into());
+    }
+
+    let pixel_type = reader
+        .metadata_arc()?
+        .series(series)?
+        .pixel_type(loc.c)
+        .ok_or(io::Error::other("Error reading pixel type"))?;
+
+    Ok(vec![0; (region.w * region.h) as usize * pixel_type.bytes()])
 }
@@ -0,0 +1,165 @@
+use std::io;
+
+use crate::format_in::PixelSlice;
+
+// One field of a mosaic: its pixels and where its top left corner sits, in
+// pixels of the assembled plane. Positions may be fractional or negative
+#[derive(Debug)]
+pub struct MosaicTile {
+    pub x: f64,
+    pub y: f64,
+    pub w: u64,
+    pub h: u64,
+    pub pixels: PixelSlice,
+}
+
+impl MosaicTile {
+    // A tile at a stage position, e.g. from a Plane's PositionX/PositionY,
+    // with both position and pixel size in the same unit
+    pub fn at_stage_position(
+        (stage_x, stage_y): (f64, f64),
+        (pixel_w, pixel_h): (f64, f64),
+        (w, h): (u64, u64),
+        pixels: PixelSlice,
+    ) -> Self {
+        MosaicTile {
+            x: stage_x / pixel_w,
+            y: stage_y / pixel_h,
+            w,
+            h,
+            pixels,
+        }
+    }
+}
+
+// How overlapping tiles combine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blend {
+    // Tiles are placed at the nearest whole pixel, later tiles covering
+    // earlier ones
+    Nearest,
+    // Overlaps fade linearly from one tile to the other, weighting each
+    // pixel by its distances to the edges of its tile
+    Linear,
+}
+
+// A plane holding every tile, its top left at the smallest tile position.
+// Returns the pixels, of the tiles' type, and the (width, height); pixels no
+// tile covers are 0
+pub fn assemble(tiles: &[MosaicTile], blend: Blend) -> io::Result<(PixelSlice, (u64, u64))> {
+    let first = tiles
+        .first()
+        .ok_or(io::Error::other("No tiles to assemble"))?;
+    let pixel_type = first.pixels.pixel_type();
+
+    for tile in tiles {
+        if tile.pixels.pixel_type() != pixel_type || tile.pixels.len() as u64 != tile.w * tile.h {
+            return Err(io::Error::other(format!(
+                "Tile at ({}, {}) doesn't hold {}x{} {} pixels",
+                tile.x,
+                tile.y,
+                tile.w,
+                tile.h,
+                pixel_type.ome_name()
+            )));
+        }
+    }
+
+    let origin = |pos: fn(&MosaicTile) -> f64| {
+        tiles
+            .iter()
+            .map(|t| pos(t).round() as i64)
+            .min()
+            .unwrap_or(0)
+    };
+    let (x0, y0) = (origin(|t| t.x), origin(|t| t.y));
+    let extent = |pos: fn(&MosaicTile) -> f64, len: fn(&MosaicTile) -> u64, o: i64| {
+        tiles
+            .iter()
+            .map(|t| (pos(t).round() as i64 - o) as u64 + len(t))
+            .max()
+            .unwrap_or(0)
+    };
+    let (w, h) = (extent(|t| t.x, |t| t.w, x0), extent(|t| t.y, |t| t.h, y0));
+
+    let mut sum = vec![0.0; (w * h) as usize];
+    let mut weight = vec![0.0; (w * h) as usize];
+
+    for tile in tiles {
+        let (tx, ty) = (
+            (tile.x.round() as i64 - x0) as u64,
+            (tile.y.round() as i64 - y0) as u64,
+        );
+        let values = tile.pixels.to_f64();
+
+        for (row, src) in values.chunks_exact(tile.w.max(1) as usize).enumerate() {
+            let start = ((ty + row as u64) * w + tx) as usize;
+            for (col, v) in src.iter().enumerate() {
+                let i = start + col;
+                match blend {
+                    Blend::Nearest => (sum[i], weight[i]) = (*v, 1.0),
+                    Blend::Linear => {
+                        let (col, row) = (col as u64, row as u64);
+                        let wx = (col + 1).min(tile.w - col);
+                        let wy = (row + 1).min(tile.h - row);
+                        sum[i] += (wx * wy) as f64 * v;
+                        weight[i] += (wx * wy) as f64;
+                    }
+                }
+            }
+        }
+    }
+
+    let values = sum
+        .iter()
+        .zip(weight.iter())
+        .map(|(s, w)| if *w > 0.0 { s / w } else { 0.0 })
+        .collect::<Vec<_>>();
+
+    Ok((PixelSlice::from_f64(&values, pixel_type), (w, h)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile(x: f64, y: f64, value: u8) -> MosaicTile {
+        MosaicTile {
+            x,
+            y,
+            w: 4,
+            h: 2,
+            pixels: PixelSlice::U8(vec![value; 8]),
+        }
+    }
+
+    #[test]
+    fn assemble_overlapping_tiles() {
+        // Two 4x2 tiles overlapping by two columns, the second 0.4 px lower
+        let tiles = [tile(10.0, 5.0, 100), tile(12.2, 5.4, 200)];
+
+        let (PixelSlice::U8(nearest), size) = assemble(&tiles, Blend::Nearest).unwrap() else {
+            panic!("Expected u8 pixels");
+        };
+        assert_eq!(size, (6, 2));
+        assert_eq!(&nearest[..6], &[100, 100, 200, 200, 200, 200]);
+
+        let (PixelSlice::U8(linear), _) = assemble(&tiles, Blend::Linear).unwrap() else {
+            panic!("Expected u8 pixels");
+        };
+        // Column 2 is 2 px from the first tile's edge and 1 from the second's
+        assert_eq!(&linear[..6], &[100, 100, 133, 167, 200, 200]);
+
+        let mut bad = tile(0.0, 0.0, 1);
+        bad.pixels = PixelSlice::U16(vec![0; 8]);
+        assert!(assemble(&[tile(0.0, 0.0, 1), bad], Blend::Nearest).is_err());
+
+        let staged = MosaicTile::at_stage_position(
+            (50.0, 25.0),
+            (0.5, 0.5),
+            (4, 2),
+            tile(0.0, 0.0, 1).pixels,
+        );
+        assert_eq!((staged.x, staged.y), (100.0, 50.0));
+    }
+}
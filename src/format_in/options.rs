@@ -0,0 +1,238 @@
+use crate::format_in::{
+    Correction, IoProfile, MetadataLevel, Normalization, OutOfBounds, Progress, TileCache,
+};
+
+// Settings shared by every reader, given before set_id. Readers ignore
+// options which don't apply to their format
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReaderOptions {
+    pub(crate) lenient: bool,
+    pub(crate) metadata_level: MetadataLevel,
+    pub(crate) flatten_resolutions: bool,
+    pub(crate) group_files: bool,
+    pub(crate) tile_size: Option<(u64, u64)>,
+    pub(crate) fast_init: bool,
+    pub(crate) progress: Progress,
+    pub(crate) memory_limit: Option<u64>,
+    pub(crate) max_open_files: usize,
+    pub(crate) normalization: Option<Normalization>,
+    pub(crate) fill_missing_planes: bool,
+    pub(crate) correction: Option<Correction>,
+    pub(crate) out_of_bounds: OutOfBounds,
+    pub(crate) prefetch: bool,
+    pub(crate) prefetch_budget: u64,
+    pub(crate) tile_cache: Option<TileCache>,
+    pub(crate) io_profile: IoProfile,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        ReaderOptions {
+            lenient: false,
+            metadata_level: MetadataLevel::default(),
+            flatten_resolutions: true,
+            group_files: true,
+            tile_size: None,
+            fast_init: false,
+            progress: Progress::default(),
+            memory_limit: None,
+            max_open_files: 64,
+            normalization: None,
+            fill_missing_planes: false,
+            correction: None,
+            out_of_bounds: OutOfBounds::default(),
+            prefetch: false,
+            prefetch_budget: 64 << 20,
+            tile_cache: None,
+            io_profile: IoProfile::default(),
+        }
+    }
+}
+
+impl ReaderOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Skip malformed optional metadata rather than failing to open
+    pub fn with_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    pub fn with_metadata_level(mut self, level: MetadataLevel) -> Self {
+        self.metadata_level = level;
+        self
+    }
+
+    // Present each pyramid level as a series of its own, as Bio-Formats
+    // does by default
+    pub fn with_flatten_resolutions(mut self, flatten: bool) -> Self {
+        self.flatten_resolutions = flatten;
+        self
+    }
+
+    // Open every file of a multi-file dataset rather than only the one given,
+    // e.g. when only some of a numbered group has been copied
+    pub fn with_group_files(mut self, group: bool) -> Self {
+        self.group_files = group;
+        self
+    }
+
+    // Overrides the reader's optimal tile size
+    pub fn with_tile_size(mut self, w: u64, h: u64) -> Self {
+        self.tile_size = Some((w, h));
+        self
+    }
+
+    // Report only the core metadata of the first image until pixels are
+    // first read, so directory scans needn't parse whole files
+    pub fn with_fast_init(mut self, fast_init: bool) -> Self {
+        self.fast_init = fast_init;
+        self
+    }
+
+    // Receives progress of metadata parsing and bulk reads, and can cancel
+    // them
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    // Largest buffer, in bytes, bulk reads such as open_stack may allocate
+    pub fn with_memory_limit(mut self, bytes: u64) -> Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    // Files of a multi-file dataset kept open at once
+    pub fn with_max_open_files(mut self, n: usize) -> Self {
+        self.max_open_files = n;
+        self
+    }
+
+    // Have open_pixels return f32 in [0, 1] whatever the stored type, e.g.
+    // as input to ML models
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = Some(normalization);
+        self
+    }
+
+    // Read planes missing from a multi-file dataset as zeros rather than
+    // failing with OmeError::PlaneMissing
+    pub fn with_fill_missing_planes(mut self, fill: bool) -> Self {
+        self.fill_missing_planes = fill;
+        self
+    }
+
+    // Dark frame and flat-field correction of every region open_pixels
+    // reads, before any normalization
+    pub fn with_correction(mut self, correction: Correction) -> Self {
+        self.correction = Some(correction);
+        self
+    }
+
+    // How reads of regions past the edges of the plane are served, rather
+    // than every caller clipping them first
+    pub fn with_out_of_bounds(mut self, policy: OutOfBounds) -> Self {
+        self.out_of_bounds = policy;
+        self
+    }
+
+    // Read the next plane's data on a background thread while the current
+    // one is decoded, for callers reading planes in order
+    pub fn with_prefetch(mut self, prefetch: bool) -> Self {
+        self.prefetch = prefetch;
+        self
+    }
+
+    // Largest plane, in stored bytes, prefetch reads ahead; 64 MiB unless
+    // set. Larger planes are read when asked for
+    pub fn with_prefetch_budget(mut self, bytes: u64) -> Self {
+        self.prefetch_budget = bytes;
+        self
+    }
+
+    // Keep decoded strips and tiles in `cache` for later reads of the same
+    // ones, e.g. by viewers panning across a plane
+    pub fn with_tile_cache(mut self, cache: TileCache) -> Self {
+        self.tile_cache = Some(cache);
+        self
+    }
+
+    // What reads cost on the device holding the files, which decides how
+    // the strips of a region are read, e.g. IoProfile::NETWORK for remote
+    // files
+    pub fn with_io_profile(mut self, profile: IoProfile) -> Self {
+        self.io_profile = profile;
+        self
+    }
+
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    pub fn metadata_level(&self) -> MetadataLevel {
+        self.metadata_level
+    }
+
+    pub fn flatten_resolutions(&self) -> bool {
+        self.flatten_resolutions
+    }
+
+    pub fn group_files(&self) -> bool {
+        self.group_files
+    }
+
+    pub fn tile_size(&self) -> Option<(u64, u64)> {
+        self.tile_size
+    }
+
+    pub fn fast_init(&self) -> bool {
+        self.fast_init
+    }
+
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    pub fn memory_limit(&self) -> Option<u64> {
+        self.memory_limit
+    }
+
+    pub fn max_open_files(&self) -> usize {
+        self.max_open_files
+    }
+
+    pub fn normalization(&self) -> Option<Normalization> {
+        self.normalization
+    }
+
+    pub fn fill_missing_planes(&self) -> bool {
+        self.fill_missing_planes
+    }
+
+    pub fn correction(&self) -> Option<&Correction> {
+        self.correction.as_ref()
+    }
+
+    pub fn out_of_bounds(&self) -> OutOfBounds {
+        self.out_of_bounds
+    }
+
+    pub fn prefetch(&self) -> bool {
+        self.prefetch
+    }
+
+    pub fn prefetch_budget(&self) -> u64 {
+        self.prefetch_budget
+    }
+
+    pub fn tile_cache(&self) -> Option<&TileCache> {
+        self.tile_cache.as_ref()
+    }
+
+    pub fn io_profile(&self) -> IoProfile {
+        self.io_profile
+    }
+}
@@ -0,0 +1,556 @@
+use std::io;
+
+use crate::format_in::{ByteOrder, PixelType};
+
+// A primitive samples can be decoded into. Samples are widened losslessly
+// into any type able to represent every value of the stored type
+pub trait Pixel: Copy {
+    // Bits of an integer the type represents exactly, sign included
+    const BITS: u16;
+    const SIGNED: bool;
+    const FLOAT: bool;
+
+    fn from_unsigned(v: u64) -> Self;
+
+    fn from_signed(v: i64) -> Self;
+
+    fn from_float(v: f64) -> Self;
+}
+
+macro_rules! impl_pixel {
+    ($($t:ty => $bits:expr, $signed:expr, $float:expr);*) => {
+        $(impl Pixel for $t {
+            const BITS: u16 = $bits;
+            const SIGNED: bool = $signed;
+            const FLOAT: bool = $float;
+
+            fn from_unsigned(v: u64) -> Self {
+                v as $t
+            }
+
+            fn from_signed(v: i64) -> Self {
+                v as $t
+            }
+
+            fn from_float(v: f64) -> Self {
+                v as $t
+            }
+        })*
+    };
+}
+
+impl_pixel!(
+    u8 => 8, false, false;
+    u16 => 16, false, false;
+    u32 => 32, false, false;
+    u64 => 64, false, false;
+    i8 => 8, true, false;
+    i16 => 16, true, false;
+    i32 => 32, true, false;
+    i64 => 64, true, false;
+    f32 => 24, true, true;
+    f64 => 53, true, true
+);
+
+// How open_pixels maps samples into f32 in [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    // Divide by the largest value the significant bits can hold, e.g. 4095
+    // for 12 bit data. Signed samples are offset by the smallest value.
+    // Floats have no such range and are scaled by their min and max
+    SignificantBits,
+    // Scale the smallest sample read to 0 and the largest to 1
+    MinMax,
+}
+
+// Samples of pixel_type, with significant_bits used, mapped into [0, 1]
+pub(crate) fn normalize(
+    values: &[f64],
+    pixel_type: PixelType,
+    significant_bits: u16,
+    normalization: Normalization,
+) -> Vec<f32> {
+    let (lo, hi) = match normalization {
+        Normalization::SignificantBits if !pixel_type.is_float() => {
+            let bits = significant_bits.clamp(1, pixel_type.bits()) as i32;
+            match pixel_type.is_signed() {
+                true => (-(2f64.powi(bits - 1)), 2f64.powi(bits - 1) - 1.0),
+                false => (0.0, 2f64.powi(bits) - 1.0),
+            }
+        }
+        _ => values
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(*v), hi.max(*v))
+            }),
+    };
+
+    let range = hi - lo;
+    values
+        .iter()
+        .map(|v| match range > 0.0 {
+            true => ((v - lo) / range).clamp(0.0, 1.0) as f32,
+            false => 0.0,
+        })
+        .collect()
+}
+
+// Whether T holds every value of the stored type
+fn widens_to<T: Pixel>(pixel_type: PixelType) -> bool {
+    let bits = pixel_type.bits();
+    match pixel_type {
+        // Only the parts of a complex sample decode, see decode_complex
+        PixelType::C64 | PixelType::C128 => false,
+        PixelType::F16 | PixelType::F32 => T::FLOAT,
+        PixelType::F64 => T::FLOAT && T::BITS == 53,
+        _ if pixel_type.is_signed() => T::SIGNED && bits <= T::BITS,
+        // An unsigned value needs one more bit in a signed integer
+        _ if T::SIGNED && !T::FLOAT => bits < T::BITS,
+        _ => bits <= T::BITS,
+    }
+}
+
+// Decode bytes holding samples of pixel_type into T
+pub(crate) fn decode<T: Pixel>(
+    bytes: &[u8],
+    pixel_type: PixelType,
+    order: ByteOrder,
+) -> io::Result<Vec<T>> {
+    if !widens_to::<T>(pixel_type) {
+        return Err(io::Error::other(format!(
+            "Can't widen {} samples to {}",
+            pixel_type.ome_name(),
+            std::any::type_name::<T>()
+        )));
+    }
+
+    let n = pixel_type.bytes();
+    let shift = 64 - pixel_type.bits() as u32;
+
+    Ok(bytes
+        .chunks_exact(n)
+        .map(|s| {
+            let mut buf = [0; 8];
+            let v = match order {
+                ByteOrder::LE => {
+                    buf[..n].copy_from_slice(s);
+                    u64::from_le_bytes(buf)
+                }
+                ByteOrder::BE => {
+                    buf[8 - n..].copy_from_slice(s);
+                    u64::from_be_bytes(buf)
+                }
+            };
+
+            match pixel_type {
+                PixelType::F16 => T::from_float(f16_to_f32(v as u16) as f64),
+                PixelType::F32 => T::from_float(f32::from_bits(v as u32) as f64),
+                PixelType::F64 => T::from_float(f64::from_bits(v)),
+                // Sign extend from the stored width
+                _ if pixel_type.is_signed() => T::from_signed(((v << shift) as i64) >> shift),
+                _ => T::from_unsigned(v),
+            }
+        })
+        .collect())
+}
+
+// An IEEE 754 half precision value, exactly, as f32
+pub(crate) fn f16_to_f32(h: u16) -> f32 {
+    let sign = ((h >> 15) as u32) << 31;
+    let exp = ((h >> 10) & 0x1f) as u32;
+    let frac = (h & 0x3ff) as u32;
+
+    let bits = match exp {
+        // Zero and subnormals, frac * 2^-24
+        0 => {
+            let v = frac as f32 * 2f32.powi(-24);
+            return if sign == 0 { v } else { -v };
+        }
+        // Infinity and NaN
+        0x1f => sign | 0x7f80_0000 | (frac << 13),
+        // Rebias the exponent from 15 to 127
+        _ => sign | ((exp + 112) << 23) | (frac << 13),
+    };
+    f32::from_bits(bits)
+}
+
+// The half precision value nearest v, ties to even, as other conversions
+// round. Values beyond the half range become infinity
+fn f32_to_f16(v: f32) -> u16 {
+    let bits = v.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+    let frac = bits & 0x7f_ffff;
+
+    // Drop the low `shift` bits of m, rounding to nearest, ties to even.
+    // A carry out of the fraction moves on to the exponent
+    let round = |m: u32, shift: u32| {
+        let (kept, rest, half) = (m >> shift, m & ((1 << shift) - 1), 1 << (shift - 1));
+        kept + (rest > half || (rest == half && kept & 1 == 1)) as u32
+    };
+
+    match exp {
+        _ if v.is_nan() => sign | 0x7e00,
+        e if e >= 0x1f => sign | 0x7c00,
+        // Below half of the smallest subnormal
+        e if e < -10 => sign,
+        // Subnormal, the implicit leading bit made explicit
+        e if e <= 0 => sign | round(frac | 0x80_0000, (14 - e) as u32) as u16,
+        e => sign | round(((e as u32) << 23) | frac, 13).min(0x7c00) as u16,
+    }
+}
+
+// One sample of pixel_type holding v, rounded and saturated for integer
+// types, in order. Complex samples take v as their real part
+pub(crate) fn encode(v: f64, pixel_type: PixelType, order: ByteOrder) -> Vec<u8> {
+    macro_rules! bytes {
+        ($t:ty, $v:expr) => {{
+            let v: f64 = $v;
+            let s = <$t>::from_float(if <$t>::FLOAT { v } else { v.round() });
+            match order {
+                ByteOrder::LE => s.to_le_bytes().to_vec(),
+                ByteOrder::BE => s.to_be_bytes().to_vec(),
+            }
+        }};
+    }
+
+    match pixel_type {
+        PixelType::U8 => bytes!(u8, v),
+        PixelType::U16 => bytes!(u16, v),
+        PixelType::U32 => bytes!(u32, v),
+        PixelType::I8 => bytes!(i8, v),
+        PixelType::I16 => bytes!(i16, v),
+        PixelType::I32 => bytes!(i32, v),
+        PixelType::F16 => bytes!(u16, f32_to_f16(v as f32) as f64),
+        PixelType::F32 => bytes!(f32, v),
+        PixelType::F64 => bytes!(f64, v),
+        PixelType::C64 => [bytes!(f32, v), bytes!(f32, 0.0)].concat(),
+        PixelType::C128 => [bytes!(f64, v), bytes!(f64, 0.0)].concat(),
+    }
+}
+
+// Decode 16 bit unsigned samples. Planes of hundreds of MB go through
+// here, so unlike decode this works on whole blocks of samples
+pub fn decode_u16(bytes: &[u8], order: ByteOrder) -> Vec<u16> {
+    match is_native(order) {
+        true => bytes
+            .chunks_exact(2)
+            .map(|s| u16::from_ne_bytes([s[0], s[1]]))
+            .collect(),
+        false => swap16(bytes),
+    }
+}
+
+pub(crate) fn is_native(order: ByteOrder) -> bool {
+    match order {
+        ByteOrder::LE => cfg!(target_endian = "little"),
+        ByteOrder::BE => cfg!(target_endian = "big"),
+    }
+}
+
+// The bytes of 16 bit samples, for reads to write them in place
+pub(crate) fn u16_bytes_mut(samples: &mut [u16]) -> &mut [u8] {
+    // Safety: any bytes are a valid u16 and u8 needs no alignment
+    unsafe { std::slice::from_raw_parts_mut(samples.as_mut_ptr().cast(), samples.len() * 2) }
+}
+
+// Put 16 bit samples read in the given byte order in native order
+pub(crate) fn u16_to_native(samples: &mut [u16], order: ByteOrder) {
+    if !is_native(order) {
+        swap16_in_place(u16_bytes_mut(samples));
+    }
+}
+
+// Swap the bytes of each 16 bit sample in place
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+pub(crate) fn swap16_in_place(bytes: &mut [u8]) {
+    if is_x86_feature_detected!("ssse3") {
+        // Safety: SSSE3 is available
+        unsafe { simd::swap16_in_place(bytes) }
+    } else {
+        swap16_in_place_scalar(bytes)
+    }
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+pub(crate) fn swap16_in_place(bytes: &mut [u8]) {
+    swap16_in_place_scalar(bytes)
+}
+
+fn swap16_in_place_scalar(bytes: &mut [u8]) {
+    for s in bytes.as_chunks_mut::<2>().0 {
+        s.swap(0, 1);
+    }
+}
+
+// 16 bit samples with their bytes swapped
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+fn swap16(bytes: &[u8]) -> Vec<u16> {
+    if is_x86_feature_detected!("ssse3") {
+        // Safety: SSSE3 is available
+        unsafe { simd::swap16(bytes) }
+    } else {
+        swap16_scalar(bytes)
+    }
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+fn swap16(bytes: &[u8]) -> Vec<u16> {
+    swap16_scalar(bytes)
+}
+
+fn swap16_scalar(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|s| u16::from_ne_bytes([s[1], s[0]]))
+        .collect()
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd {
+    use std::arch::x86_64::{
+        __m128i, _mm_loadu_si128, _mm_setr_epi8, _mm_shuffle_epi8, _mm_storeu_si128,
+    };
+
+    // Swap 8 samples at a time with a byte shuffle
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn swap16(bytes: &[u8]) -> Vec<u16> {
+        let n = bytes.len() / 2;
+        let mut out = vec![0u16; n];
+        let mask = _mm_setr_epi8(1, 0, 3, 2, 5, 4, 7, 6, 9, 8, 11, 10, 13, 12, 15, 14);
+
+        let mut i = 0;
+        while i + 8 <= n {
+            // Safety: 16 bytes from 2 * i and 8 samples from i are in
+            // bounds; both accesses are unaligned
+            unsafe {
+                let v = _mm_loadu_si128(bytes.as_ptr().add(2 * i) as *const __m128i);
+                let swapped = _mm_shuffle_epi8(v, mask);
+                _mm_storeu_si128(out.as_mut_ptr().add(i) as *mut __m128i, swapped);
+            }
+            i += 8;
+        }
+        for (j, s) in bytes[2 * i..].chunks_exact(2).enumerate() {
+            out[i + j] = u16::from_ne_bytes([s[1], s[0]]);
+        }
+
+        out
+    }
+
+    // As swap16, over 16 bytes at a time of the samples themselves
+    #[target_feature(enable = "ssse3")]
+    pub(super) unsafe fn swap16_in_place(bytes: &mut [u8]) {
+        let mask = _mm_setr_epi8(1, 0, 3, 2, 5, 4, 7, 6, 9, 8, 11, 10, 13, 12, 15, 14);
+
+        let mut blocks = bytes.chunks_exact_mut(16);
+        for block in &mut blocks {
+            // Safety: the block is 16 bytes; both accesses are unaligned
+            unsafe {
+                let v = _mm_loadu_si128(block.as_ptr() as *const __m128i);
+                let swapped = _mm_shuffle_epi8(v, mask);
+                _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, swapped);
+            }
+        }
+        super::swap16_in_place_scalar(blocks.into_remainder());
+    }
+}
+
+// Decode bytes holding complex samples into [real, imaginary] pairs of T
+pub(crate) fn decode_complex<T: Pixel>(
+    bytes: &[u8],
+    pixel_type: PixelType,
+    order: ByteOrder,
+) -> io::Result<Vec<[T; 2]>> {
+    if !pixel_type.is_complex() {
+        return Err(io::Error::other(format!(
+            "{} samples aren't complex",
+            pixel_type.ome_name()
+        )));
+    }
+
+    let parts = decode::<T>(bytes, pixel_type.component(), order)?;
+    Ok(parts.chunks_exact(2).map(|p| [p[0], p[1]]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_with_widening() {
+        let bytes = [1, 2, 3, 4];
+
+        assert_eq!(
+            decode::<u16>(&bytes, PixelType::U16, ByteOrder::BE).unwrap(),
+            vec![0x0102, 0x0304]
+        );
+        assert_eq!(
+            decode::<u32>(&bytes, PixelType::U16, ByteOrder::LE).unwrap(),
+            vec![0x0201, 0x0403]
+        );
+        assert_eq!(
+            decode::<f32>(&bytes, PixelType::U8, ByteOrder::LE).unwrap(),
+            vec![1.0, 2.0, 3.0, 4.0]
+        );
+        assert!(decode::<u8>(&bytes, PixelType::U16, ByteOrder::LE).is_err());
+        assert!(decode::<i16>(&bytes, PixelType::U16, ByteOrder::LE).is_err());
+    }
+
+    #[test]
+    fn normalize_to_unit_range() {
+        let values = [0.0, 1023.0, 4095.0];
+
+        assert_eq!(
+            normalize(&values, PixelType::U16, 12, Normalization::SignificantBits),
+            vec![0.0, 1023.0 / 4095.0, 1.0]
+        );
+        assert_eq!(
+            normalize(&values, PixelType::U16, 16, Normalization::MinMax),
+            vec![0.0, 1023.0 / 4095.0, 1.0]
+        );
+        assert_eq!(
+            normalize(
+                &[-128.0, 127.0],
+                PixelType::I8,
+                8,
+                Normalization::SignificantBits
+            ),
+            vec![0.0, 1.0]
+        );
+        assert_eq!(
+            normalize(
+                &[2.5, 2.5],
+                PixelType::F32,
+                32,
+                Normalization::SignificantBits
+            ),
+            vec![0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn decode_signed_and_float() {
+        assert_eq!(
+            decode::<i32>(&[0xff, 0xfe], PixelType::I8, ByteOrder::LE).unwrap(),
+            vec![-1, -2]
+        );
+        assert_eq!(
+            decode::<f64>(&1.5f32.to_be_bytes(), PixelType::F32, ByteOrder::BE).unwrap(),
+            vec![1.5]
+        );
+        assert!(decode::<u32>(&[0xff], PixelType::I8, ByteOrder::LE).is_err());
+        assert!(decode::<f32>(&[0; 8], PixelType::F64, ByteOrder::LE).is_err());
+    }
+
+    #[test]
+    fn decode_u16_matches_decode() {
+        let bytes = (0..=255).chain(0..=42).collect::<Vec<u8>>();
+
+        for order in [ByteOrder::LE, ByteOrder::BE] {
+            assert_eq!(
+                decode_u16(&bytes, order),
+                decode::<u16>(&bytes, PixelType::U16, order).unwrap()
+            );
+        }
+    }
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[test]
+    fn simd_swap_matches_scalar() {
+        if !is_x86_feature_detected!("ssse3") {
+            return;
+        }
+
+        // Lengths either side of whole 16 byte blocks, some with a byte over
+        for len in [0, 1, 2, 15, 16, 17, 30, 33, 301] {
+            let bytes = (0..len).map(|i| (i * 7) as u8).collect::<Vec<u8>>();
+            // Safety: SSSE3 is available
+            let swapped = unsafe { simd::swap16(&bytes) };
+            assert_eq!(swapped, swap16_scalar(&bytes));
+
+            let mut vectored = bytes.clone();
+            // Safety: as above
+            unsafe { simd::swap16_in_place(&mut vectored) };
+            let mut scalar = bytes.clone();
+            swap16_in_place_scalar(&mut scalar);
+            assert_eq!(vectored, scalar);
+        }
+    }
+
+    #[test]
+    fn decode_complex_pairs() {
+        let bytes = [1.5f32, -2.0, 0.0, 4.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            decode_complex::<f64>(&bytes, PixelType::C64, ByteOrder::LE).unwrap(),
+            vec![[1.5, -2.0], [0.0, 4.0]]
+        );
+        assert!(decode::<f64>(&bytes, PixelType::C64, ByteOrder::LE).is_err());
+        assert!(decode_complex::<f32>(&bytes, PixelType::C128, ByteOrder::LE).is_err());
+    }
+
+    #[test]
+    fn decode_half_floats() {
+        let halves = [0x3c00u16, 0xc000, 0x3555, 0x0001, 0x7bff, 0xfc00];
+        let bytes = halves
+            .iter()
+            .flat_map(|h| h.to_be_bytes())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            decode::<f32>(&bytes, PixelType::F16, ByteOrder::BE).unwrap(),
+            vec![
+                1.0,
+                -2.0,
+                0.333_251_95,
+                2f32.powi(-24),
+                65504.0,
+                f32::NEG_INFINITY
+            ]
+        );
+        assert!(f16_to_f32(0x7e00).is_nan());
+        assert_eq!(f32_to_f16(-2.0), 0xc000);
+        assert_eq!(f32_to_f16(1e6), 0x7c00);
+        // Ties go to the even neighbour
+        assert_eq!(f32_to_f16(1.0 + 2f32.powi(-11)), 0x3c00);
+        assert_eq!(f32_to_f16(1.0 + 3.0 * 2f32.powi(-11)), 0x3c02);
+        assert_eq!(f32_to_f16(0.1), 0x2e66);
+        assert_eq!(f32_to_f16(65519.0), 0x7bff);
+        assert_eq!(f32_to_f16(65520.0), 0x7c00);
+        assert_eq!(f32_to_f16(2f32.powi(-24) * 0.75), 0x0001);
+        assert_eq!(f32_to_f16(-(2f32.powi(-25))), 0x8000);
+        assert_eq!(
+            encode(1.5, PixelType::F16, ByteOrder::BE),
+            encode(0x3e00 as f64, PixelType::U16, ByteOrder::BE)
+        );
+        assert!(decode::<u32>(&bytes, PixelType::F16, ByteOrder::BE).is_err());
+    }
+
+    #[test]
+    fn map_pixel_slices() {
+        use crate::format_in::PixelSlice;
+
+        let pixels = PixelSlice::U8(vec![10, 100, 200]);
+        assert_eq!(pixels.map(|v| v * 1.5), PixelSlice::U8(vec![15, 150, 255]));
+        assert_eq!(pixels.map(|v| v - 150.0), PixelSlice::U8(vec![0, 0, 50]));
+
+        let mut floats = PixelSlice::F32(vec![0.5, -1.0]);
+        floats.apply(f64::abs);
+        assert_eq!(floats, PixelSlice::F32(vec![0.5, 1.0]));
+
+        let mut complex = PixelSlice::C64(vec![[1.0, -2.0]]);
+        complex.apply(|v| v * 2.0);
+        assert_eq!(complex, PixelSlice::C64(vec![[2.0, -4.0]]));
+
+        let values = Vec::<f64>::from(&PixelSlice::I16(vec![-3, 7]));
+        assert_eq!(values, vec![-3.0, 7.0]);
+        assert_eq!(
+            PixelSlice::from_f64(&[-0.6, 1.4], PixelType::I8),
+            PixelSlice::I8(vec![-1, 1])
+        );
+        assert_eq!(PixelSlice::from(values), PixelSlice::F64(vec![-3.0, 7.0]));
+    }
+}
@@ -0,0 +1,138 @@
+// Storage type of a sample. Byte order is a property of the file, see
+// Metadata::byte_order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PixelType {
+    U8,
+    U16,
+    U32,
+    I8,
+    I16,
+    I32,
+    // Half precision, decoded to f32 by open_pixels. OME has no such type,
+    // so it is described as float
+    F16,
+    F32,
+    F64,
+    // Pairs of (real, imaginary) f32 or f64, e.g. FFT output
+    C64,
+    C128,
+}
+
+impl PixelType {
+    // From TIFF BitsPerSample and SampleFormat (1 unsigned, 2 signed,
+    // 3 floating point, 6 complex floating point)
+    pub fn from_tiff(bits: u16, sample_format: u16) -> Option<Self> {
+        match (sample_format, bits) {
+            (1, 8) => Some(Self::U8),
+            (1, 16) => Some(Self::U16),
+            (1, 32) => Some(Self::U32),
+            (2, 8) => Some(Self::I8),
+            (2, 16) => Some(Self::I16),
+            (2, 32) => Some(Self::I32),
+            (3, 16) => Some(Self::F16),
+            (3, 32) => Some(Self::F32),
+            (3, 64) => Some(Self::F64),
+            (6, 64) => Some(Self::C64),
+            (6, 128) => Some(Self::C128),
+            _ => None,
+        }
+    }
+
+    // From the Type attribute of OME-XML Pixels
+    pub fn from_ome(name: &str) -> Option<Self> {
+        match name {
+            "uint8" => Some(Self::U8),
+            "uint16" => Some(Self::U16),
+            "uint32" => Some(Self::U32),
+            "int8" => Some(Self::I8),
+            "int16" => Some(Self::I16),
+            "int32" => Some(Self::I32),
+            "float" => Some(Self::F32),
+            "double" => Some(Self::F64),
+            "complex" => Some(Self::C64),
+            "double-complex" => Some(Self::C128),
+            _ => None,
+        }
+    }
+
+    pub fn ome_name(&self) -> &'static str {
+        match self {
+            Self::U8 => "uint8",
+            Self::U16 => "uint16",
+            Self::U32 => "uint32",
+            Self::I8 => "int8",
+            Self::I16 => "int16",
+            Self::I32 => "int32",
+            Self::F16 | Self::F32 => "float",
+            Self::F64 => "double",
+            Self::C64 => "complex",
+            Self::C128 => "double-complex",
+        }
+    }
+
+    pub fn bits(&self) -> u16 {
+        match self {
+            Self::U8 | Self::I8 => 8,
+            Self::U16 | Self::I16 | Self::F16 => 16,
+            Self::U32 | Self::I32 | Self::F32 => 32,
+            Self::F64 | Self::C64 => 64,
+            Self::C128 => 128,
+        }
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.bits() as usize / 8
+    }
+
+    pub fn is_signed(&self) -> bool {
+        !matches!(self, Self::U8 | Self::U16 | Self::U32)
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(
+            self,
+            Self::F16 | Self::F32 | Self::F64 | Self::C64 | Self::C128
+        )
+    }
+
+    pub fn is_complex(&self) -> bool {
+        matches!(self, Self::C64 | Self::C128)
+    }
+
+    // Type of the real and imaginary parts of a complex sample
+    pub fn component(&self) -> Self {
+        match self {
+            Self::C64 => Self::F32,
+            Self::C128 => Self::F64,
+            pt => *pt,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiff_and_ome_names_agree() {
+        for (bits, format, name) in [(8, 1, "uint8"), (16, 2, "int16"), (32, 3, "float")] {
+            let pt = PixelType::from_tiff(bits, format).unwrap();
+
+            assert_eq!(pt.ome_name(), name);
+            assert_eq!(PixelType::from_ome(name), Some(pt));
+            assert_eq!(pt.bits(), bits);
+        }
+
+        assert!(PixelType::I32.is_signed() && !PixelType::I32.is_float());
+        assert_eq!(PixelType::from_tiff(12, 1), None);
+        // Only floats are "double"; OME has no 64 bit integer type
+        assert_eq!(PixelType::from_tiff(64, 1), None);
+        assert_eq!(PixelType::from_tiff(64, 2), None);
+        assert_eq!(PixelType::from_tiff(64, 3).unwrap().ome_name(), "double");
+        assert_eq!(PixelType::from_tiff(128, 6), Some(PixelType::C128));
+        assert_eq!(PixelType::from_ome("complex").unwrap().bytes(), 8);
+        assert_eq!(PixelType::from_tiff(16, 3), Some(PixelType::F16));
+        assert_eq!(PixelType::F16.ome_name(), "float");
+    }
+}
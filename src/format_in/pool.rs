@@ -0,0 +1,134 @@
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+
+use crate::format_in::FormatReader;
+
+// N readers opened on the same file, lent to worker threads one at a time
+// so tiles can be read in parallel despite the &mut self reader API
+pub struct ReaderPool<R: FormatReader> {
+    path: PathBuf,
+    size: usize,
+    idle: Mutex<Vec<R>>,
+    available: Condvar,
+}
+
+impl<R: FormatReader + Default> ReaderPool<R> {
+    pub fn new(path: impl AsRef<Path>, size: usize) -> io::Result<Self> {
+        if size == 0 {
+            return Err(io::Error::other("A reader pool needs at least one reader"));
+        }
+
+        let path = path.as_ref().to_path_buf();
+        let readers = (0..size)
+            .map(|_| {
+                let mut reader = R::default();
+                reader.set_id(&path)?;
+                Ok(reader)
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(ReaderPool {
+            path,
+            size,
+            idle: Mutex::new(readers),
+            available: Condvar::new(),
+        })
+    }
+}
+
+impl<R: FormatReader> ReaderPool<R> {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // Borrow a reader, blocking until one is idle. It returns to the pool
+    // when the guard is dropped
+    pub fn get(&self) -> io::Result<PooledReader<'_, R>> {
+        let mut idle = self.idle.lock().map_err(|_| poisoned())?;
+        loop {
+            if let Some(reader) = idle.pop() {
+                return Ok(PooledReader {
+                    pool: self,
+                    reader: Some(reader),
+                });
+            }
+            idle = self.available.wait(idle).map_err(|_| poisoned())?;
+        }
+    }
+
+    // Run f with a borrowed reader
+    pub fn with<T>(&self, f: impl FnOnce(&mut R) -> io::Result<T>) -> io::Result<T> {
+        let mut reader = self.get()?;
+        f(&mut reader)
+    }
+}
+
+fn poisoned() -> io::Error {
+    io::Error::other("Reader pool poisoned by a panicked worker")
+}
+
+pub struct PooledReader<'a, R: FormatReader> {
+    pool: &'a ReaderPool<R>,
+    reader: Option<R>,
+}
+
+impl<R: FormatReader> Deref for PooledReader<'_, R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        self.reader.as_ref().unwrap()
+    }
+}
+
+impl<R: FormatReader> DerefMut for PooledReader<'_, R> {
+    fn deref_mut(&mut self) -> &mut R {
+        self.reader.as_mut().unwrap()
+    }
+}
+
+impl<R: FormatReader> Drop for PooledReader<'_, R> {
+    fn drop(&mut self) {
+        if let (Some(reader), Ok(mut idle)) = (self.reader.take(), self.pool.idle.lock()) {
+            idle.push(reader);
+            self.pool.available.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+    use crate::format_in::{Loc, Region};
+
+    #[test]
+    fn pool_serves_parallel_reads() {
+        let pool = ReaderPool::<TiffReader>::new("assets/example_valid.tiff", 2).unwrap();
+        let regions = (0..6)
+            .map(|i| Region::new(i * 300, i * 300, 16, 16))
+            .collect::<Vec<_>>();
+
+        let tiles = std::thread::scope(|s| {
+            let handles = regions
+                .iter()
+                .map(|r| s.spawn(|| pool.with(|reader| reader.open_bytes(Loc::new(0, 0, 0), *r))))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        let mut direct = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        for (tile, region) in tiles.iter().zip(regions) {
+            assert_eq!(tile, &direct.open_bytes(Loc::new(0, 0, 0), region).unwrap());
+        }
+        assert_eq!(pool.idle.lock().unwrap().len(), pool.size());
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::OmeError;
+
+type Callback = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+// Reports how far long operations (metadata parsing, bulk reads) have got
+// and lets another thread abort them. Clones share the cancellation flag
+#[derive(Clone, Default)]
+pub struct Progress {
+    callback: Option<Callback>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Called with (steps done, total steps) as work proceeds
+    pub fn with_callback(mut self, callback: impl Fn(u64, u64) + Send + Sync + 'static) -> Self {
+        self.callback = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    // Report progress, failing with OmeError::Cancelled once cancelled
+    pub fn step(&self, done: u64, total: u64) -> io::Result<()> {
+        if self.is_cancelled() {
+            return Err(OmeError::Cancelled.into());
+        }
+        if let Some(callback) = &self.callback {
+            callback(done, total);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("callback", &self.callback.is_some())
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+// Equal when sharing a cancellation flag, i.e. clones of one another
+impl PartialEq for Progress {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.cancelled, &other.cancelled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+    use crate::format_in::{FormatReader, ReaderOptions};
+    use std::sync::Mutex;
+
+    #[test]
+    fn cancel_bulk_read() {
+        let steps = Arc::new(Mutex::new(Vec::new()));
+        let seen = steps.clone();
+        let progress = Progress::new().with_callback(move |done, total| {
+            seen.lock().unwrap().push((done, total));
+        });
+
+        let options = ReaderOptions::new().with_progress(progress.clone());
+        let mut reader = TiffReader::with_options(options);
+        reader
+            .set_id(std::path::Path::new("assets/example_valid.tiff"))
+            .unwrap();
+        reader.metadata().unwrap();
+        assert_eq!(steps.lock().unwrap().last(), Some(&(1, 1)));
+
+        let mut tiles = reader.tiles(0, 1979, 1000).unwrap();
+        assert!(tiles.next().unwrap().is_ok());
+        progress.cancel();
+
+        let err = tiles.next().unwrap().unwrap_err();
+        assert!(matches!(OmeError::from_io(&err), Some(OmeError::Cancelled)));
+        assert!(tiles.next().is_none());
+    }
+}
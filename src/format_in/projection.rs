@@ -0,0 +1,121 @@
+use std::io;
+
+use crate::format_in::{FormatReader, Loc, PixelSlice, Region, pixel};
+
+// How the Z planes at each pixel combine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    // Maximum intensity projection
+    Max,
+    Min,
+    Mean,
+    Sum,
+}
+
+// Project a region of channel c at timepoint t of the current series along
+// Z. The region is read one tile at a time through every Z plane, so only
+// the projection and a single tile are held in memory. Max and Min keep the
+// sample type; Mean and Sum are f64
+pub fn project_z<R: FormatReader + ?Sized>(
+    reader: &mut R,
+    c: u64,
+    t: u64,
+    region: Region,
+    projection: Projection,
+) -> io::Result<PixelSlice> {
+    let md = reader.metadata_arc()?;
+    let order = *md.byte_order();
+    let s = md.series(reader.series())?;
+    let pixel_type = s
+        .pixel_type(c)
+        .ok_or(io::Error::other("Error reading pixel type"))?;
+    let size_z = s.dimensions().size_z();
+    region.validate(s.dimensions().size_x(), s.dimensions().size_y())?;
+
+    let (tile_w, tile_h) = reader.optimal_tile_size()?;
+    let (tile_w, tile_h) = (
+        tile_w.clamp(1, region.w.max(1)),
+        tile_h.clamp(1, region.h.max(1)),
+    );
+    let tiles = region.w.div_ceil(tile_w) * region.h.div_ceil(tile_h);
+
+    let start = match projection {
+        Projection::Max => f64::NEG_INFINITY,
+        Projection::Min => f64::INFINITY,
+        Projection::Mean | Projection::Sum => 0.0,
+    };
+    let mut acc = vec![start; region.area() as usize];
+
+    let progress = reader.options().progress().clone();
+    let mut done = 0;
+    for ty in (0..region.h).step_by(tile_h as usize) {
+        for tx in (0..region.w).step_by(tile_w as usize) {
+            let (w, h) = (tile_w.min(region.w - tx), tile_h.min(region.h - ty));
+            let tile = Region::new(region.x + tx, region.y + ty, w, h);
+
+            for z in 0..size_z {
+                progress.step(done, tiles * size_z)?;
+                let bytes = reader.open_bytes(Loc::new(z, c, t), tile)?;
+                let values = pixel::decode::<f64>(&bytes, pixel_type, order)?;
+
+                for (row, src) in values.chunks_exact(w as usize).enumerate() {
+                    let i = ((ty + row as u64) * region.w + tx) as usize;
+                    for (a, v) in acc[i..i + w as usize].iter_mut().zip(src) {
+                        *a = match projection {
+                            Projection::Max => a.max(*v),
+                            Projection::Min => a.min(*v),
+                            Projection::Mean | Projection::Sum => *a + v,
+                        };
+                    }
+                }
+                done += 1;
+            }
+        }
+    }
+
+    Ok(match projection {
+        Projection::Max | Projection::Min => PixelSlice::from_f64(&acc, pixel_type),
+        Projection::Mean => PixelSlice::F64(acc.iter().map(|v| v / size_z as f64).collect()),
+        Projection::Sum => PixelSlice::F64(acc),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::Axis;
+    use crate::format_in::tiff_reader::TiffReader;
+    use crate::format_in::wrappers::DimensionSwapper;
+
+    #[test]
+    fn project_channels_as_z() {
+        // Present the four channels of the example as four Z planes
+        let tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let mut reader = DimensionSwapper::new(tr, Axis::C, Axis::Z, Axis::T).unwrap();
+        let region = Region::new(300, 300, 40, 30);
+
+        let planes = (0..4)
+            .map(|z| reader.open_as::<u16>(Loc::new(z, 0, 0), region).unwrap())
+            .collect::<Vec<_>>();
+        let max = (0..planes[0].len())
+            .map(|i| planes.iter().map(|p| p[i]).max().unwrap())
+            .collect::<Vec<_>>();
+        let sum = (0..planes[0].len())
+            .map(|i| planes.iter().map(|p| p[i] as f64).sum::<f64>())
+            .collect::<Vec<_>>();
+
+        let PixelSlice::U16(projected) =
+            project_z(&mut reader, 0, 0, region, Projection::Max).unwrap()
+        else {
+            panic!("Expected u16 pixels");
+        };
+        assert_eq!(projected, max);
+
+        let PixelSlice::F64(projected) =
+            project_z(&mut reader, 0, 0, region, Projection::Sum).unwrap()
+        else {
+            panic!("Expected f64 pixels");
+        };
+        assert_eq!(projected, sum);
+    }
+}
@@ -0,0 +1,179 @@
+use std::ops::{Range, RangeInclusive};
+use std::time::Duration;
+
+use crate::format_in::tiff::StripTable;
+
+// What reads cost on the device holding a file: a fixed latency per read
+// and the bytes transferred per second. Planning weighs fewer, larger reads
+// against reading only the bytes wanted with it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IoProfile {
+    pub latency: Duration,
+    pub bytes_per_second: f64,
+}
+
+impl IoProfile {
+    pub const SSD: IoProfile = IoProfile::new(Duration::from_micros(100), 1e9);
+    pub const HDD: IoProfile = IoProfile::new(Duration::from_millis(8), 150e6);
+    // Object stores and HTTP servers, read with range requests
+    pub const NETWORK: IoProfile = IoProfile::new(Duration::from_millis(50), 50e6);
+
+    pub const fn new(latency: Duration, bytes_per_second: f64) -> Self {
+        IoProfile {
+            latency,
+            bytes_per_second,
+        }
+    }
+
+    // Seconds to make n reads of bytes in total
+    pub fn cost(&self, n: u64, bytes: u64) -> f64 {
+        n as f64 * self.latency.as_secs_f64() + bytes as f64 / self.bytes_per_second
+    }
+
+    // Bytes costing as much to read through as a read of their own
+    fn break_even_gap(&self) -> u64 {
+        (self.latency.as_secs_f64() * self.bytes_per_second) as u64
+    }
+}
+
+impl Default for IoProfile {
+    fn default() -> Self {
+        IoProfile::SSD
+    }
+}
+
+// How the strips holding a region are read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadPlan {
+    // Only the region's columns of each row, a read per row. Uncompressed
+    // strips only
+    Rows,
+    // Each strip in a read of its own
+    Strips,
+    // Runs of strips lying close together in the file, each in one read
+    // spanning them. Reading a whole plane at once is a single run of
+    // every strip
+    Coalesced(Vec<Range<u64>>),
+}
+
+// The cheapest plan for reading strips of table, of which rows rows of
+// row_len bytes are wanted, only wanted_len bytes of each
+pub fn plan_strip_reads(
+    profile: &IoProfile,
+    table: &StripTable,
+    strips: RangeInclusive<u64>,
+    compressed: bool,
+    rows: u64,
+    row_len: usize,
+    wanted_len: usize,
+) -> ReadPlan {
+    let n_strips = strips.end() - strips.start() + 1;
+    let strip_bytes = strips
+        .clone()
+        .filter_map(|i| table.get(i))
+        .fold(0u64, |sum, (_, byte_count)| sum.saturating_add(byte_count));
+
+    let runs = runs(profile, table, strips);
+    let run_bytes = runs
+        .iter()
+        .filter_map(|run| span(table, run))
+        .fold(0u64, |sum, span| {
+            sum.saturating_add(span.end.saturating_sub(span.start))
+        });
+
+    // Uncompressed strips are read only as far as the wanted rows
+    let strips_cost = match compressed {
+        true => profile.cost(n_strips, strip_bytes),
+        false => profile.cost(n_strips, rows * row_len as u64),
+    };
+    let mut plans = vec![
+        (strips_cost, ReadPlan::Strips),
+        (
+            profile.cost(runs.len() as u64, run_bytes),
+            ReadPlan::Coalesced(runs),
+        ),
+    ];
+    if !compressed && wanted_len < row_len {
+        plans.push((profile.cost(rows, rows * wanted_len as u64), ReadPlan::Rows));
+    }
+
+    // Strips win ties, reading no more than they hold
+    plans
+        .into_iter()
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or(ReadPlan::Strips, |(_, plan)| plan)
+}
+
+// Strips split where the gap to the next costs more to read through than
+// another read, or where the next lies earlier in the file
+fn runs(profile: &IoProfile, table: &StripTable, strips: RangeInclusive<u64>) -> Vec<Range<u64>> {
+    let max_gap = profile.break_even_gap();
+    let mut runs: Vec<Range<u64>> = Vec::new();
+
+    for i in strips {
+        // Left for the read to report
+        let Some((offset, _)) = table.get(i) else {
+            runs.push(i..i + 1);
+            continue;
+        };
+        let joins = runs.last().and_then(|run| {
+            let (prev, count) = table.get(run.end - 1)?;
+            let end = prev.saturating_add(count);
+            Some(offset >= end && offset - end <= max_gap)
+        });
+        match (joins, runs.last_mut()) {
+            (Some(true), Some(run)) => run.end = i + 1,
+            _ => runs.push(i..i + 1),
+        }
+    }
+
+    runs
+}
+
+// Bytes of the file a run of strips spans
+pub(crate) fn span(table: &StripTable, run: &Range<u64>) -> Option<Range<u64>> {
+    let (start, _) = table.get(run.start)?;
+    let (last, count) = table.get(run.end.checked_sub(1)?)?;
+    // Offsets and counts come from the file
+    Some(start..last.checked_add(count)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // n strips of len bytes, gap bytes apart
+    fn table(n: u64, len: u64, gap: u64) -> StripTable {
+        StripTable {
+            offsets: (0..n).map(|i| 8 + i * (len + gap)).collect(),
+            byte_counts: vec![len; n as usize],
+        }
+    }
+
+    #[test]
+    fn plan_follows_shape_and_device() {
+        let ssd = IoProfile::SSD;
+        // 64 strips of 8 rows of 16 KB
+        let contiguous = table(64, 8 << 14, 0);
+
+        // A few pixels of one row
+        let plan = plan_strip_reads(&ssd, &contiguous, 3..=3, false, 1, 1 << 14, 64);
+        assert_eq!(plan, ReadPlan::Rows);
+
+        // A column down the whole plane, rather than a read per row
+        let plan = plan_strip_reads(&ssd, &contiguous, 0..=63, false, 512, 1 << 14, 2);
+        assert!(matches!(plan, ReadPlan::Coalesced(runs) if runs.len() == 1 && runs[0] == (0..64)));
+
+        // Compressed strips far apart are read one by one from an SSD but
+        // together over a network
+        let scattered = table(4, 1 << 10, 1 << 24);
+        let plan = plan_strip_reads(&ssd, &scattered, 0..=3, true, 32, 1 << 14, 1 << 14);
+        assert_eq!(plan, ReadPlan::Strips);
+
+        let close = table(4, 1 << 10, 1 << 20);
+        let network = IoProfile::NETWORK;
+        let plan = plan_strip_reads(&network, &close, 0..=3, true, 32, 1 << 14, 1 << 14);
+        assert!(matches!(plan, ReadPlan::Coalesced(runs) if runs.len() == 1 && runs[0] == (0..4)));
+        assert_eq!(span(&close, &(0..2)), Some(8..8 + (2 << 10) + (1 << 20)));
+    }
+}
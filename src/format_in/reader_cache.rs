@@ -0,0 +1,140 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::format_in::{FormatReader, ReaderOptions};
+
+// Readers of the files of a multi-file dataset, keeping at most `capacity`
+// open and closing the least recently used beyond that, so datasets of
+// thousands of files stay within the OS limit on open descriptors
+pub struct ReaderCache<R: FormatReader> {
+    capacity: usize,
+    readers: HashMap<PathBuf, R>,
+    // Least recently used first
+    order: VecDeque<PathBuf>,
+}
+
+impl<R: FormatReader> Default for ReaderCache<R> {
+    fn default() -> Self {
+        Self::new(ReaderOptions::default().max_open_files())
+    }
+}
+
+impl<R: FormatReader> ReaderCache<R> {
+    pub fn new(capacity: usize) -> Self {
+        ReaderCache {
+            capacity: capacity.max(1),
+            readers: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Readers currently open
+    pub fn len(&self) -> usize {
+        self.readers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.readers.is_empty()
+    }
+
+    // The reader of `file`, opened with `open` unless still cached
+    pub fn get_or_open(
+        &mut self,
+        file: &Path,
+        open: impl FnOnce(&Path) -> io::Result<R>,
+    ) -> io::Result<&mut R> {
+        if let Some(i) = self.order.iter().position(|f| f == file) {
+            let f = self.order.remove(i).unwrap();
+            self.order.push_back(f);
+        } else {
+            // Room is made before opening, so no more than `capacity`
+            // readers are ever open at once
+            while self.readers.len() >= self.capacity {
+                let Some(oldest) = self.order.pop_front() else {
+                    break;
+                };
+                if let Some(mut evicted) = self.readers.remove(&oldest) {
+                    evicted.close()?;
+                }
+            }
+            let reader = open(file)?;
+            self.readers.insert(file.to_path_buf(), reader);
+            self.order.push_back(file.to_path_buf());
+        }
+
+        Ok(self.readers.get_mut(file).unwrap())
+    }
+
+    pub fn close_all(&mut self) -> io::Result<()> {
+        self.order.clear();
+        for (_, mut reader) in self.readers.drain() {
+            reader.close()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[test]
+    fn least_recently_used_reader_is_closed() {
+        let mut cache = ReaderCache::new(2);
+        let mut opened = Vec::new();
+        let mut get = |cache: &mut ReaderCache<TiffReader>, name: &str| {
+            cache
+                .get_or_open(Path::new(name), |f| {
+                    opened.push(f.to_path_buf());
+                    TiffReader::new("assets/example_valid.tiff".into())
+                })
+                .unwrap()
+                .size_x()
+                .unwrap()
+        };
+
+        for name in ["a", "b", "a", "c", "a", "b"] {
+            assert_eq!(get(&mut cache, name), 1979);
+            assert!(cache.len() <= 2);
+        }
+
+        // "b" was evicted by "c", which "b" evicted in turn
+        let opened = opened
+            .iter()
+            .map(|f| f.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(opened, ["a", "b", "c", "b"]);
+
+        cache.close_all().unwrap();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn evicted_before_opening() {
+        let mut cache = ReaderCache::<TiffReader>::new(2);
+        for name in ["a", "b"] {
+            cache
+                .get_or_open(Path::new(name), |_| {
+                    TiffReader::new("assets/example_valid.tiff".into())
+                })
+                .unwrap();
+        }
+
+        // At the cap, the oldest reader is closed before "c" is opened, so
+        // a failed open leaves only "b"
+        let result = cache.get_or_open(Path::new("c"), |_| {
+            Err(io::Error::other("Too many open files"))
+        });
+        assert!(result.is_err());
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.order, [PathBuf::from("b")]);
+    }
+}
@@ -0,0 +1,173 @@
+use std::io::{self, Error};
+
+use crate::OmeError;
+
+// What a read does with a region extending past the edges of the plane
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutOfBounds {
+    // Fail with OmeError::InvalidRegion
+    #[default]
+    Error,
+    // Read only the part inside the plane, returning fewer pixels
+    Clamp,
+    // Return the whole region, with pixels outside the plane set to the
+    // given sample value, e.g. 0 for tiled viewers
+    Pad(f64),
+}
+
+// Rectangular XY extent of a plane, in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Region {
+    pub x: u64,
+    pub y: u64,
+    pub w: u64,
+    pub h: u64,
+}
+
+impl Region {
+    pub fn new(x: u64, y: u64, w: u64, h: u64) -> Self {
+        Region { x, y, w, h }
+    }
+
+    // The whole of a size_x by size_y plane
+    pub fn full(size_x: u64, size_y: u64) -> Self {
+        Region::new(0, 0, size_x, size_y)
+    }
+
+    pub fn area(&self) -> u64 {
+        self.w * self.h
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.w == 0 || self.h == 0
+    }
+
+    // (x + w, y + h), None where either overflows
+    fn end(&self) -> Option<(u64, u64)> {
+        Some((self.x.checked_add(self.w)?, self.y.checked_add(self.h)?))
+    }
+
+    // Regions whose ends overflow, e.g. from user input, lie outside every
+    // region
+    pub fn contains(&self, other: &Region) -> bool {
+        let (Some((x_end, y_end)), Some((other_x_end, other_y_end))) = (self.end(), other.end())
+        else {
+            return false;
+        };
+
+        other.x >= self.x && other.y >= self.y && other_x_end <= x_end && other_y_end <= y_end
+    }
+
+    pub fn intersection(&self, other: &Region) -> Option<Region> {
+        let ((self_x_end, self_y_end), (other_x_end, other_y_end)) = (self.end()?, other.end()?);
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let x_end = self_x_end.min(other_x_end);
+        let y_end = self_y_end.min(other_y_end);
+
+        (x < x_end && y < y_end).then(|| Region::new(x, y, x_end - x, y_end - y))
+    }
+
+    pub fn intersects(&self, other: &Region) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    // The region in a plane scaled by `scale`, at least one pixel across
+    pub fn scaled(&self, scale: f64) -> Region {
+        let scale_len = |v: u64| ((v as f64 * scale).round() as u64).max(1);
+        Region::new(
+            (self.x as f64 * scale) as u64,
+            (self.y as f64 * scale) as u64,
+            scale_len(self.w),
+            scale_len(self.h),
+        )
+    }
+
+    // Err unless the region is non-empty and lies within a size_x by size_y
+    // plane
+    pub fn validate(&self, size_x: u64, size_y: u64) -> io::Result<()> {
+        if self.is_empty() {
+            return Err(Error::other(format!("Empty region: {self:?}")));
+        }
+
+        if !Region::full(size_x, size_y).contains(self) {
+            return Err(OmeError::InvalidRegion {
+                region: *self,
+                size_x,
+                size_y,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+// Copy inner, the samples of a region within outer, into its place in the
+// samples of outer
+pub(crate) fn paste(
+    inner: &[u8],
+    inner_region: Region,
+    out: &mut [u8],
+    outer_region: Region,
+    bytes_per_sample: usize,
+) {
+    let row_len = inner_region.w as usize * bytes_per_sample;
+    let (dx, dy) = (
+        inner_region.x - outer_region.x,
+        inner_region.y - outer_region.y,
+    );
+
+    for (row, src) in inner.chunks_exact(row_len).enumerate() {
+        let start = ((dy + row as u64) * outer_region.w + dx) as usize * bytes_per_sample;
+        out[start..start + row_len].copy_from_slice(src);
+    }
+}
+
+// Nearest neighbour resampling of src_w by src_h samples to dst_w by dst_h
+pub(crate) fn resample(
+    bytes: &[u8],
+    (src_w, src_h): (u64, u64),
+    (dst_w, dst_h): (u64, u64),
+    bytes_per_sample: usize,
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity((dst_w * dst_h) as usize * bytes_per_sample);
+
+    for y in 0..dst_h {
+        let sy = (2 * y + 1) * src_h / (2 * dst_h);
+        for x in 0..dst_w {
+            let sx = (2 * x + 1) * src_w / (2 * dst_w);
+            let i = (sy * src_w + sx) as usize * bytes_per_sample;
+            out.extend_from_slice(&bytes[i..i + bytes_per_sample]);
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn region_intersection_and_validation() {
+        let a = Region::new(0, 0, 10, 10);
+        let b = Region::new(5, 8, 10, 10);
+
+        assert_eq!(a.intersection(&b), Some(Region::new(5, 8, 5, 2)));
+        assert!(!a.intersects(&Region::new(10, 0, 5, 5)));
+        assert!(a.validate(10, 10).is_ok());
+        assert!(b.validate(10, 10).is_err());
+        assert!(Region::new(1, 1, 0, 4).validate(10, 10).is_err());
+
+        let huge = Region::new(u64::MAX, 1, 2, 2);
+        assert!(!a.contains(&huge));
+        assert_eq!(a.intersection(&huge), None);
+        assert!(huge.validate(10, 10).is_err());
+        assert!(
+            Region::new(0, 0, u64::MAX, u64::MAX)
+                .validate(10, 10)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use std::io::{self, Read};
+
+use crate::format_in::remote::RangeFetcher;
+
+// Fetches byte ranges of a file on a web server with HTTP range requests
+pub struct HttpFetcher {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl HttpFetcher {
+    pub fn new(url: impl Into<String>) -> Self {
+        HttpFetcher {
+            url: url.into(),
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl RangeFetcher for HttpFetcher {
+    fn size(&mut self) -> io::Result<u64> {
+        let response = self
+            .agent
+            .head(&self.url)
+            .call()
+            .map_err(io::Error::other)?;
+
+        response
+            .header("Content-Length")
+            .and_then(|l| l.parse().ok())
+            .ok_or(io::Error::other(format!(
+                "{} didn't report a Content-Length",
+                self.url
+            )))
+    }
+
+    fn fetch(&mut self, start: u64, end: u64) -> io::Result<Vec<u8>> {
+        if start >= end {
+            return Ok(Vec::new());
+        }
+
+        let response = self
+            .agent
+            .get(&self.url)
+            .set("Range", &format!("bytes={start}-{}", end - 1))
+            .call()
+            .map_err(io::Error::other)?;
+
+        // A 200 means the server ignored the range and sent everything
+        if response.status() != 206 {
+            return Err(io::Error::other(format!(
+                "{} doesn't support range requests",
+                self.url
+            )));
+        }
+
+        let mut bytes = Vec::with_capacity((end - start) as usize);
+        response.into_reader().read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Seek, SeekFrom};
+
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "object_store")]
+pub mod store;
+
+#[cfg(feature = "http")]
+pub use http::HttpFetcher;
+#[cfg(feature = "object_store")]
+pub use store::ObjectStoreFetcher;
+
+// Fetches byte ranges of a remote object, e.g. with HTTP range requests
+pub trait RangeFetcher: Send {
+    // Total length of the object in bytes
+    fn size(&mut self) -> io::Result<u64>;
+
+    // Bytes start..end of the object
+    fn fetch(&mut self, start: u64, end: u64) -> io::Result<Vec<u8>>;
+}
+
+const DEFAULT_BLOCK_SIZE: u64 = 64 * 1024;
+const DEFAULT_MAX_BLOCKS: usize = 256;
+
+// A seekable view of a remote object which fetches fixed size blocks on
+// demand and keeps the most recent ones, so a reader touches only the
+// headers and tiles it needs rather than downloading the whole file
+pub struct RangeSource<F: RangeFetcher> {
+    fetcher: F,
+    len: u64,
+    pos: u64,
+    block_size: u64,
+    max_blocks: usize,
+    blocks: HashMap<u64, Vec<u8>>,
+    // Cached block indices, least recently fetched first
+    order: VecDeque<u64>,
+}
+
+impl<F: RangeFetcher> RangeSource<F> {
+    pub fn new(mut fetcher: F) -> io::Result<Self> {
+        Ok(RangeSource {
+            len: fetcher.size()?,
+            fetcher,
+            pos: 0,
+            block_size: DEFAULT_BLOCK_SIZE,
+            max_blocks: DEFAULT_MAX_BLOCKS,
+            blocks: HashMap::new(),
+            order: VecDeque::new(),
+        })
+    }
+
+    // Bytes fetched per request
+    pub fn with_block_size(mut self, block_size: u64) -> Self {
+        self.block_size = block_size.max(1);
+        self.blocks.clear();
+        self.order.clear();
+        self
+    }
+
+    // Number of blocks kept in memory
+    pub fn with_max_blocks(mut self, max_blocks: usize) -> Self {
+        self.max_blocks = max_blocks.max(1);
+        self
+    }
+
+    pub fn fetcher(&self) -> &F {
+        &self.fetcher
+    }
+
+    fn block(&mut self, index: u64) -> io::Result<&[u8]> {
+        if !self.blocks.contains_key(&index) {
+            let start = index * self.block_size;
+            let end = (start + self.block_size).min(self.len);
+            let bytes = self.fetcher.fetch(start, end)?;
+            if bytes.len() as u64 != end - start {
+                return Err(io::Error::other(format!(
+                    "Fetched {} bytes for range {start}..{end}",
+                    bytes.len()
+                )));
+            }
+
+            if self.order.len() >= self.max_blocks
+                && let Some(oldest) = self.order.pop_front()
+            {
+                self.blocks.remove(&oldest);
+            }
+            self.blocks.insert(index, bytes);
+            self.order.push_back(index);
+        }
+
+        Ok(&self.blocks[&index])
+    }
+}
+
+impl<F: RangeFetcher> Read for RangeSource<F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let offset = (self.pos % self.block_size) as usize;
+        let block = self.block(self.pos / self.block_size)?;
+        let n = buf.len().min(block.len() - offset);
+        buf[..n].copy_from_slice(&block[offset..offset + n]);
+
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<F: RangeFetcher> Seek for RangeSource<F> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::End(d) => self.len.checked_add_signed(d),
+            SeekFrom::Current(d) => self.pos.checked_add_signed(d),
+        };
+
+        self.pos = pos.ok_or(io::Error::other("Seek before the start of the source"))?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+    use crate::format_in::{FormatReader, Loc, Region};
+
+    // Serves a file from memory, counting the bytes requested
+    struct MemoryFetcher {
+        bytes: Vec<u8>,
+        fetched: u64,
+    }
+
+    impl RangeFetcher for MemoryFetcher {
+        fn size(&mut self) -> io::Result<u64> {
+            Ok(self.bytes.len() as u64)
+        }
+
+        fn fetch(&mut self, start: u64, end: u64) -> io::Result<Vec<u8>> {
+            self.fetched += end - start;
+            Ok(self.bytes[start as usize..end as usize].to_vec())
+        }
+    }
+
+    #[test]
+    fn range_source_fetches_lazily() {
+        let bytes = std::fs::read("assets/example_valid.tiff").unwrap();
+        let fetcher = MemoryFetcher {
+            bytes: bytes.clone(),
+            fetched: 0,
+        };
+        let mut source = RangeSource::new(fetcher).unwrap().with_block_size(4096);
+
+        let mut buf = [0; 16];
+        source.seek(SeekFrom::Start(10_000)).unwrap();
+        source.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, &bytes[10_000..10_016]);
+        assert_eq!(source.fetcher().fetched, 4096);
+
+        let mut remote = TiffReader::from_source(source).unwrap();
+        let mut local = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let (loc, region) = (Loc::new(0, 0, 0), Region::new(0, 1000, 16, 16));
+
+        assert_eq!(
+            remote.open_bytes(loc, region).unwrap(),
+            local.open_bytes(loc, region).unwrap()
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::io;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use object_store::{ObjectStore, path::Path};
+use tokio::runtime::Runtime;
+
+use crate::format_in::remote::RangeFetcher;
+
+// Fetches byte ranges of an object in S3, GCS, Azure or any other
+// object_store backend. Large ranges are split into parts fetched
+// `concurrency` at a time, and failed requests are retried with backoff
+pub struct ObjectStoreFetcher {
+    store: Arc<dyn ObjectStore>,
+    path: Path,
+    runtime: Runtime,
+    concurrency: usize,
+    part_size: u64,
+    retries: u32,
+}
+
+impl ObjectStoreFetcher {
+    pub fn new(store: Arc<dyn ObjectStore>, path: impl Into<Path>) -> io::Result<Self> {
+        Ok(ObjectStoreFetcher {
+            store,
+            path: path.into(),
+            runtime: tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?,
+            concurrency: 4,
+            part_size: 1024 * 1024,
+            retries: 3,
+        })
+    }
+
+    // Parts of a range requested at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    // Ranges longer than this are split across requests
+    pub fn with_part_size(mut self, part_size: u64) -> Self {
+        self.part_size = part_size.max(1);
+        self
+    }
+
+    // Extra attempts made for each failed request
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    async fn get(&self, range: Range<u64>) -> io::Result<Vec<u8>> {
+        let mut attempt = 0;
+        loop {
+            match self.store.get_range(&self.path, range.clone()).await {
+                Ok(bytes) => return Ok(bytes.to_vec()),
+                Err(_) if attempt < self.retries => {
+                    tokio::time::sleep(backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(io::Error::other(e)),
+            }
+        }
+    }
+}
+
+// Wait before retry attempt + 1, doubling from 100 ms. Capped so that any
+// number of retries waits a finite, representable time
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_millis(100u64.saturating_mul(1 << attempt.min(16)))
+}
+
+impl RangeFetcher for ObjectStoreFetcher {
+    fn size(&mut self) -> io::Result<u64> {
+        let meta = self
+            .runtime
+            .block_on(self.store.head(&self.path))
+            .map_err(io::Error::other)?;
+
+        Ok(meta.size)
+    }
+
+    fn fetch(&mut self, start: u64, end: u64) -> io::Result<Vec<u8>> {
+        let parts = (start..end)
+            .step_by(self.part_size as usize)
+            .map(|s| s..(s + self.part_size).min(end))
+            .collect::<Vec<_>>();
+
+        let mut bytes = Vec::with_capacity((end - start) as usize);
+        for batch in parts.chunks(self.concurrency) {
+            let fetched = self.runtime.block_on(futures::future::try_join_all(
+                batch.iter().map(|r| self.get(r.clone())),
+            ))?;
+            fetched.into_iter().for_each(|b| bytes.extend(b));
+        }
+
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::remote::RangeSource;
+    use crate::format_in::tiff_reader::TiffReader;
+    use crate::format_in::{FormatReader, Loc, Region};
+    use object_store::memory::InMemory;
+
+    #[test]
+    fn read_tiff_from_object_store() {
+        let bytes = std::fs::read("assets/example_valid.tiff").unwrap();
+        let store = Arc::new(InMemory::new());
+        let path = Path::from("data/example_valid.tiff");
+
+        let fetcher = ObjectStoreFetcher::new(store.clone(), path.clone())
+            .unwrap()
+            .with_part_size(1000)
+            .with_concurrency(3);
+        fetcher
+            .runtime
+            .block_on(store.put(&path, bytes.into()))
+            .unwrap();
+
+        let source = RangeSource::new(fetcher).unwrap();
+        let mut remote = TiffReader::from_source(source).unwrap();
+        let mut local = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let (loc, region) = (Loc::new(0, 2, 0), Region::new(300, 400, 12, 12));
+
+        assert_eq!(
+            remote.open_bytes(loc, region).unwrap(),
+            local.open_bytes(loc, region).unwrap()
+        );
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        assert_eq!(backoff(0), Duration::from_millis(100));
+        assert_eq!(backoff(3), Duration::from_millis(800));
+        assert_eq!(backoff(64), backoff(16));
+        assert_eq!(backoff(u32::MAX), backoff(16));
+    }
+}
@@ -0,0 +1,238 @@
+use std::io;
+
+use crate::format_in::{Colormap, Lut, PixelSlice};
+
+// How sample values map onto 0..=255
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scaling {
+    // The smallest sample to 0 and the largest to 255
+    Global,
+    // Samples at the low and high percentiles (0 to 100) to 0 and 255,
+    // clipping outliers such as hot pixels
+    Percentile(f64, f64),
+}
+
+// Sample values at the low and high percentiles; NaNs are ignored
+fn window(values: &[f64], scaling: Scaling) -> Option<(f64, f64)> {
+    let mut sorted = values
+        .iter()
+        .copied()
+        .filter(|v| !v.is_nan())
+        .collect::<Vec<_>>();
+    sorted.sort_by(f64::total_cmp);
+
+    let (lo, hi) = match scaling {
+        Scaling::Global => (0.0, 100.0),
+        Scaling::Percentile(lo, hi) => (lo.clamp(0.0, 100.0), hi.clamp(0.0, 100.0)),
+    };
+    let at = |p: f64| {
+        let i = (p / 100.0 * (sorted.len() - 1) as f64).round() as usize;
+        sorted[i]
+    };
+
+    (!sorted.is_empty()).then(|| (at(lo), at(hi)))
+}
+
+// A step applied to display intensities in [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Adjustment {
+    // Intensities raised to 1 / gamma; above 1 brightens the midtones
+    Gamma(f64),
+    Invert,
+}
+
+// Maps samples to display intensities: a window onto [0, 1], then each
+// adjustment in the order added, e.g.
+// RenderPipeline::new().with_level(500.0, 200.0).with_gamma(2.2).with_invert()
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderPipeline {
+    pub(crate) scaling: Scaling,
+    pub(crate) window: Option<(f64, f64)>,
+    pub(crate) adjustments: Vec<Adjustment>,
+}
+
+impl Default for RenderPipeline {
+    fn default() -> Self {
+        RenderPipeline {
+            scaling: Scaling::Global,
+            window: None,
+            adjustments: Vec::new(),
+        }
+    }
+}
+
+impl RenderPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Window taken from the samples themselves when none is set
+    pub fn with_scaling(mut self, scaling: Scaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
+    // Samples from lo to hi span black to white
+    pub fn with_window(mut self, lo: f64, hi: f64) -> Self {
+        self.window = Some((lo, hi));
+        self
+    }
+
+    // The window as level (its centre) and width
+    pub fn with_level(self, level: f64, width: f64) -> Self {
+        self.with_window(level - width / 2.0, level + width / 2.0)
+    }
+
+    pub fn with_gamma(mut self, gamma: f64) -> Self {
+        self.adjustments.push(Adjustment::Gamma(gamma));
+        self
+    }
+
+    pub fn with_invert(mut self) -> Self {
+        self.adjustments.push(Adjustment::Invert);
+        self
+    }
+
+    pub fn window(&self) -> Option<(f64, f64)> {
+        self.window
+    }
+
+    pub fn adjustments(&self) -> &[Adjustment] {
+        &self.adjustments
+    }
+
+    // Intensities in [0, 1]. A flat window maps every sample to 0 before
+    // the adjustments; NaN samples are always 0
+    pub fn intensities(&self, pixels: &PixelSlice) -> Vec<f64> {
+        let values = pixels.to_f64();
+        let (lo, hi) = self
+            .window
+            .or_else(|| window(&values, self.scaling))
+            .unwrap_or((0.0, 0.0));
+        let range = hi - lo;
+
+        values
+            .iter()
+            .map(|v| {
+                if v.is_nan() {
+                    return 0.0;
+                }
+                let mut i = match range > 0.0 {
+                    true => ((v - lo) / range).clamp(0.0, 1.0),
+                    false => 0.0,
+                };
+                for adjustment in &self.adjustments {
+                    i = match adjustment {
+                        Adjustment::Gamma(g) if *g > 0.0 => i.powf(1.0 / g),
+                        Adjustment::Gamma(_) => i,
+                        Adjustment::Invert => 1.0 - i,
+                    };
+                }
+                i
+            })
+            .collect()
+    }
+
+    pub fn to_u8(&self, pixels: &PixelSlice) -> Vec<u8> {
+        self.intensities(pixels)
+            .iter()
+            .map(|i| (i * 255.0).round() as u8)
+            .collect()
+    }
+}
+
+// Convert samples of any type to 8 bits for display or thumbnails. NaN
+// samples, and every sample of a flat image, become 0
+pub fn to_u8(pixels: &PixelSlice, scaling: Scaling) -> Vec<u8> {
+    RenderPipeline::new().with_scaling(scaling).to_u8(pixels)
+}
+
+// A grayscale plane through lut as interleaved 8 bit RGB. Samples index
+// the table directly, so 8 or 16 bit samples are needed; see
+// apply_colormap for other types
+pub fn apply_lut(pixels: &PixelSlice, lut: &Lut) -> io::Result<Vec<u8>> {
+    let rgb = |i: usize| lut.rgb8(i).ok_or(io::Error::other("Empty LUT"));
+
+    match pixels {
+        PixelSlice::U8(v) => v.iter().map(|i| rgb(*i as usize)).collect(),
+        PixelSlice::U16(v) => v.iter().map(|i| rgb(*i as usize)).collect(),
+        _ => Err(io::Error::other("LUTs are indexed by 8 or 16 bit samples")),
+    }
+    .map(|pixels: Vec<[u8; 3]>| pixels.concat())
+}
+
+// Samples of any type scaled to 8 bits and colored by a named colormap,
+// as interleaved RGB
+pub fn apply_colormap(pixels: &PixelSlice, colormap: Colormap, scaling: Scaling) -> Vec<u8> {
+    let lut = colormap.lut();
+    to_u8(pixels, scaling)
+        .into_iter()
+        .flat_map(|i| lut.rgb8(i as usize).unwrap_or_default())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_to_u8() {
+        let ramp = PixelSlice::U16((0..=100).map(|v| v * 40).collect());
+
+        let global = to_u8(&ramp, Scaling::Global);
+        assert_eq!((global[0], global[50], global[100]), (0, 128, 255));
+
+        // The top and bottom 10% saturate
+        let clipped = to_u8(&ramp, Scaling::Percentile(10.0, 90.0));
+        assert_eq!(
+            (clipped[5], clipped[10], clipped[50], clipped[95]),
+            (0, 0, 128, 255)
+        );
+
+        let flat = PixelSlice::F32(vec![2.0, 2.0, f32::NAN]);
+        assert_eq!(to_u8(&flat, Scaling::Global), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn color_gray_planes() {
+        let lut = Lut::U8([vec![0, 10], vec![0, 20], vec![0, 30]]);
+        let plane = PixelSlice::U8(vec![1, 0, 5]);
+
+        assert_eq!(
+            apply_lut(&plane, &lut).unwrap(),
+            vec![10, 20, 30, 0, 0, 0, 10, 20, 30]
+        );
+        assert!(apply_lut(&PixelSlice::F32(vec![1.0]), &lut).is_err());
+
+        let red = apply_colormap(
+            &PixelSlice::I16(vec![-5, 5]),
+            Colormap::Red,
+            Scaling::Global,
+        );
+        assert_eq!(red, vec![0, 0, 0, 255, 0, 0]);
+    }
+
+    #[test]
+    fn chained_adjustments() {
+        let pixels = PixelSlice::U16(vec![400, 500, 600, 700]);
+
+        let windowed = RenderPipeline::new().with_level(500.0, 200.0);
+        assert_eq!(windowed.to_u8(&pixels), vec![0, 128, 255, 255]);
+        assert_eq!(
+            windowed.clone().with_invert().to_u8(&pixels),
+            vec![255, 128, 0, 0]
+        );
+
+        // Gamma then invert differs from invert then gamma
+        let gamma = windowed.clone().with_gamma(2.0).with_invert();
+        assert_eq!(gamma.to_u8(&pixels), vec![255, 75, 0, 0]);
+        let inverted = windowed.with_invert().with_gamma(2.0);
+        assert_eq!(inverted.to_u8(&pixels), vec![255, 180, 0, 0]);
+
+        let ramp = RenderPipeline::new().with_scaling(Scaling::Global);
+        assert_eq!(
+            ramp.intensities(&pixels),
+            vec![0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0]
+        );
+    }
+}
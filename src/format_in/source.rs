@@ -0,0 +1,89 @@
+use std::fs::File;
+#[cfg(not(any(unix, windows)))]
+use std::io::SeekFrom;
+use std::io::{self, Read, Seek};
+use std::sync::Arc;
+
+// Anything readers can pull bytes from: files, in-memory buffers, archive
+// entries or remote objects
+pub trait Source: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> Source for T {}
+
+// Sources read at an offset without a cursor, e.g. with pread, so one
+// handle serves concurrent readers without them racing on its position
+// and each read is one call rather than a seek and a read
+pub trait ReadAt: Send + Sync {
+    // Bytes read into buf from offset, fewer only at the end of the data
+    // or if interrupted, as with Read::read
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
+
+    // Bytes in the whole source
+    fn length(&self) -> io::Result<u64>;
+}
+
+impl ReadAt for File {
+    #[cfg(unix)]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(self, buf, offset)
+    }
+
+    // Moves the cursor, which reads through ReadAt don't rely on
+    #[cfg(windows)]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(self, buf, offset)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let mut file = self;
+        file.seek(SeekFrom::Start(offset))?;
+        file.read(buf)
+    }
+
+    fn length(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+}
+
+impl ReadAt for Arc<[u8]> {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        read_slice_at(self, buf, offset)
+    }
+
+    fn length(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+}
+
+impl ReadAt for Vec<u8> {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        read_slice_at(self, buf, offset)
+    }
+
+    fn length(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+}
+
+fn read_slice_at(bytes: &[u8], buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    let start = (offset as usize).min(bytes.len());
+    let n = buf.len().min(bytes.len() - start);
+    buf[..n].copy_from_slice(&bytes[start..start + n]);
+    Ok(n)
+}
+
+// Read into the whole of buf unless the data ends first, returning the
+// bytes read
+pub(crate) fn read_full_at(source: &dyn ReadAt, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match source.read_at(&mut buf[n..], offset + n as u64) {
+            Ok(0) => break,
+            Ok(r) => n += r,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(n)
+}
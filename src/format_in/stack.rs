@@ -0,0 +1,76 @@
+use std::io;
+
+use crate::OmeError;
+use crate::format_in::{FormatReader, Loc, Region};
+
+// Every plane of the current series, ordered XYZCT, see
+// FormatReader::open_stack
+pub(crate) fn read_stack<R: FormatReader + ?Sized>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let md = reader.metadata()?;
+    let s = md.series(reader.series())?;
+    let (size_z, size_c, size_t) = s.dimensions().zct();
+    let region = Region::full(s.dimensions().size_x(), s.dimensions().size_y());
+
+    // Channels may differ in sample size
+    let plane_bytes = (0..size_c)
+        .map(|c| {
+            s.pixel_type(c)
+                .map(|pt| region.area() as usize * pt.bytes())
+                .ok_or(io::Error::other("Error reading pixel type"))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    let needed = (plane_bytes.iter().sum::<usize>() as u64) * size_z * size_t;
+
+    let options = reader.options();
+    if let Some(limit) = options.memory_limit()
+        && needed > limit
+    {
+        return Err(OmeError::TooLarge { needed, limit }.into());
+    }
+
+    let mut stack = Vec::new();
+    stack
+        .try_reserve_exact(needed as usize)
+        .map_err(io::Error::other)?;
+
+    let total = size_z * size_c * size_t;
+    let mut done = 0;
+    for t in 0..size_t {
+        for (c, bytes) in plane_bytes.iter().enumerate() {
+            for z in 0..size_z {
+                options.progress().step(done, total)?;
+                let start = stack.len();
+                stack.resize(start + bytes, 0);
+                reader.open_bytes_into(Loc::new(z, c as u64, t), region, &mut stack[start..])?;
+                done += 1;
+            }
+        }
+    }
+
+    Ok(stack)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::OmeError;
+    use crate::format_in::tiff_reader::TiffReader;
+    use crate::format_in::{FormatReader, ReaderOptions};
+
+    #[test]
+    fn stack_is_xyzct_ordered() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let stack = tr.open_stack(0).unwrap();
+        let plane = tr.open_plane(0, 2, 0).unwrap();
+
+        assert_eq!(stack.len(), 4 * plane.len());
+        assert_eq!(&stack[2 * plane.len()..3 * plane.len()], &plane[..]);
+
+        tr.set_options(ReaderOptions::new().with_memory_limit(1 << 20));
+        let err = tr.open_stack(0).unwrap_err();
+        assert!(matches!(
+            OmeError::from_io(&err),
+            Some(OmeError::TooLarge { limit: 1048576, .. })
+        ));
+        assert!(tr.open_stack(1).is_err());
+    }
+}
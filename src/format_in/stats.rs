@@ -0,0 +1,107 @@
+use std::io;
+
+use crate::format_in::{FormatReader, Loc, histogram::each_tile};
+
+// Summary of the samples of a plane, e.g. for QC or to pick a display
+// window. NaNs aren't counted
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlaneStats {
+    pub count: u64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    // Population standard deviation
+    pub std_dev: f64,
+}
+
+// Running count, mean and sum of squared deviations (Welford), so a plane is
+// summarised in one pass without holding it
+#[derive(Debug, Clone, Copy)]
+struct Accumulator {
+    count: u64,
+    min: f64,
+    max: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Accumulator {
+    fn new() -> Self {
+        Accumulator {
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    fn add(&mut self, values: &[f64]) {
+        for v in values.iter().filter(|v| !v.is_nan()) {
+            self.count += 1;
+            (self.min, self.max) = (self.min.min(*v), self.max.max(*v));
+            let delta = v - self.mean;
+            self.mean += delta / self.count as f64;
+            self.m2 += delta * (v - self.mean);
+        }
+    }
+
+    fn stats(&self) -> PlaneStats {
+        match self.count {
+            0 => PlaneStats {
+                count: 0,
+                min: f64::NAN,
+                max: f64::NAN,
+                mean: f64::NAN,
+                std_dev: f64::NAN,
+            },
+            n => PlaneStats {
+                count: n,
+                min: self.min,
+                max: self.max,
+                mean: self.mean,
+                std_dev: (self.m2 / n as f64).sqrt(),
+            },
+        }
+    }
+}
+
+// Statistics of plane loc of the current series, read a tile at a time
+pub fn plane_stats<R: FormatReader + ?Sized>(reader: &mut R, loc: Loc) -> io::Result<PlaneStats> {
+    let mut acc = Accumulator::new();
+    each_tile(reader, loc, |values| acc.add(values))?;
+    Ok(acc.stats())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::Region;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[test]
+    fn stats_of_example_plane() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let loc = Loc::new(0, 1, 0);
+        let plane = tr.open_as::<f64>(loc, Region::full(1979, 1979)).unwrap();
+
+        let n = plane.len() as f64;
+        let mean = plane.iter().sum::<f64>() / n;
+        let var = plane.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+
+        let stats = plane_stats(&mut tr, loc).unwrap();
+        assert_eq!(stats.count, 1979 * 1979);
+        assert!((stats.mean * n - 184163095.0).abs() < 1e-3);
+        assert_eq!(
+            stats.min,
+            plane.iter().copied().fold(f64::INFINITY, f64::min)
+        );
+        assert_eq!(stats.max, plane.iter().copied().fold(0.0, f64::max));
+        assert!((stats.std_dev - var.sqrt()).abs() < 1e-6);
+
+        let mut acc = Accumulator::new();
+        acc.add(&[f64::NAN]);
+        assert!(acc.stats().mean.is_nan());
+    }
+}
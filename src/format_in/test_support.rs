@@ -0,0 +1,164 @@
+// Small TIFFs and scratch directories shared by the tests of the readers
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum TagValue {
+    Short(u16),
+    // More than two, stored out of line
+    Shorts(&'static [u16]),
+    Long(u32),
+    Rational(u32, u32),
+}
+
+// One little-endian, uncompressed IFD of one sample per pixel, its pixels
+// in a single strip unless split
+pub(crate) struct TestIfd {
+    pixels: Vec<u8>,
+    tags: Vec<(u16, TagValue)>,
+    strips: u32,
+}
+
+impl TestIfd {
+    pub(crate) fn new(width: u32, height: u32, bits: u16, pixels: Vec<u8>) -> Self {
+        use TagValue::*;
+
+        TestIfd {
+            tags: vec![
+                (256, Long(width)),
+                (257, Long(height)),
+                (258, Short(bits)),
+                (259, Short(1)),
+                (262, Short(1)),
+                (277, Short(1)),
+                (278, Long(height)),
+                (279, Long(pixels.len() as u32)),
+                (284, Short(1)),
+            ],
+            pixels,
+            strips: 1,
+        }
+    }
+
+    // Splits the pixels into n strips of equal length, e.g. one per sample
+    // of a planar image
+    pub(crate) fn strips(mut self, n: u32) -> Self {
+        self.strips = n;
+        self
+    }
+
+    // An 8 bit image with every pixel set to `value`
+    pub(crate) fn filled(width: u32, height: u32, value: u8) -> Self {
+        Self::new(width, height, 8, vec![value; (width * height) as usize])
+    }
+
+    // Sets a tag, replacing any earlier value
+    pub(crate) fn tag(mut self, tag: u16, value: TagValue) -> Self {
+        self.tags.retain(|(t, _)| *t != tag);
+        self.tags.push((tag, value));
+        self
+    }
+
+    pub(crate) fn without(mut self, tag: u16) -> Self {
+        self.tags.retain(|(t, _)| *t != tag);
+        self
+    }
+}
+
+// A TIFF of the given IFDs in order, each IFD's pixels and rationals
+// written just before it
+pub(crate) fn tiff(ifds: Vec<TestIfd>) -> Vec<u8> {
+    let mut out = b"II*\0".to_vec();
+    // Position of the offset pointing at the next IFD
+    let mut link = out.len();
+    out.extend(0u32.to_le_bytes());
+
+    for mut ifd in ifds {
+        let pixels = out.len() as u32;
+        out.extend(&ifd.pixels);
+        ifd.tags.push((273, TagValue::Long(pixels)));
+
+        let mut entries = Vec::new();
+        if ifd.strips > 1 {
+            // Offsets and byte counts of the strips, written out of line
+            let len = ifd.pixels.len() as u32 / ifd.strips;
+            let offsets = (0..ifd.strips)
+                .map(|s| pixels + s * len)
+                .collect::<Vec<_>>();
+            for (tag, values) in [(273, offsets), (279, vec![len; ifd.strips as usize])] {
+                ifd.tags.retain(|(t, _)| *t != tag);
+                entries.push((tag, 4, ifd.strips, out.len() as u32));
+                out.extend(values.iter().flat_map(|v| v.to_le_bytes()));
+            }
+        }
+
+        let values = ifd
+            .tags
+            .iter()
+            .map(|&(tag, value)| match value {
+                TagValue::Short(v) => (tag, 3u16, 1, v as u32),
+                TagValue::Shorts(vs) => {
+                    let at = out.len() as u32;
+                    out.extend(vs.iter().flat_map(|v| v.to_le_bytes()));
+                    (tag, 3, vs.len() as u32, at)
+                }
+                TagValue::Long(v) => (tag, 4, 1, v),
+                TagValue::Rational(n, d) => {
+                    let at = out.len() as u32;
+                    out.extend(n.to_le_bytes());
+                    out.extend(d.to_le_bytes());
+                    (tag, 5, 1, at)
+                }
+            })
+            .collect::<Vec<_>>();
+        entries.extend(values);
+        entries.sort_by_key(|(tag, ..)| *tag);
+
+        let start = out.len() as u32;
+        out[link..link + 4].copy_from_slice(&start.to_le_bytes());
+        out.extend((entries.len() as u16).to_le_bytes());
+        for (tag, kind, count, value) in entries {
+            out.extend(tag.to_le_bytes());
+            out.extend(kind.to_le_bytes());
+            out.extend(count.to_le_bytes());
+            out.extend(value.to_le_bytes());
+        }
+
+        link = out.len();
+        out.extend(0u32.to_le_bytes());
+    }
+
+    out
+}
+
+// A directory of its own under the system temp directory, removed with
+// everything in it on drop
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub(crate) fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let n = NEXT.fetch_add(1, Ordering::Relaxed);
+        let dir =
+            std::env::temp_dir().join(format!("ome-bioformats-rs-{}-{n}", std::process::id()));
+
+        // Left behind by an earlier process of the same id
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    pub(crate) fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -1,7 +1,4 @@
-use std::{
-    io::{self, Read, Seek},
-    usize,
-};
+use std::io::{self, Read, Seek};
 
 use ome_common_rs::ios::RandomAccessInputStream;
 
@@ -22,67 +19,59 @@ impl Compression {
         }
     }
 
+    // Decode PackBits data from the stream's position to its end
     pub fn unpackbits_stream<T: Read + Seek>(
         istream: &mut RandomAccessInputStream<T>,
         buff: &mut [u8],
         expected_byte_count: u64,
     ) -> io::Result<()> {
-        let mut curr_byte_idx: usize = 0;
+        let offset = istream.get_file_pointer()?;
+        let mut input = vec![0; istream.available()? as usize];
+        let n = istream.read(&mut input, offset)?;
 
-        while (curr_byte_idx as u64) < expected_byte_count && istream.available()? != 0 {
-            let byte = istream.read_byte()?;
-            let count = byte as usize;
-
-            if byte == 128 {
-                continue;
-            } else if byte > 128 {
-                let next_byte = istream.read_byte()?;
-
-                buff.get_mut(curr_byte_idx..(curr_byte_idx + 256 - count + 1))
-                    .map(|a| a.fill(next_byte));
-
-                curr_byte_idx += 256 - count + 1;
-            } else {
-                let offset = istream.get_file_pointer()?;
-
-                buff.get_mut(curr_byte_idx..(curr_byte_idx + count + 1))
-                    .map(|a| istream.read(a, offset));
-
-                curr_byte_idx += count + 1;
-            }
-        }
-
-        Ok(())
+        Self::unpackbits(&input[..n], n as u64, buff, expected_byte_count)
     }
 
+    // Decode input_len bytes of PackBits data into the first output_len
+    // bytes of out_buff. Each header byte n is followed by n + 1 literal
+    // bytes when n < 128, or by a byte repeated 257 - n times when n > 128
     pub fn unpackbits(
-        in_buff: &mut [u8],
+        in_buff: &[u8],
         input_len: u64,
         out_buff: &mut [u8],
         output_len: u64,
     ) -> io::Result<()> {
-        let mut in_idx = 0;
-        let mut out_idx = 0;
+        let input = &in_buff[..(input_len as usize).min(in_buff.len())];
+        let out_len = (output_len as usize).min(out_buff.len());
+        let (mut in_idx, mut out_idx) = (0, 0);
+
+        while in_idx < input.len() && out_idx < out_len {
+            let header = input[in_idx];
+            in_idx += 1;
+
+            if header < 128 {
+                let n = header as usize + 1;
+                let (Some(bytes), Some(out)) = (
+                    input.get(in_idx..in_idx + n),
+                    out_buff.get_mut(out_idx..out_idx + n),
+                ) else {
+                    return Err(io::Error::other("Literal overruns the strip"));
+                };
+                out.copy_from_slice(bytes);
+
+                in_idx += n;
+                out_idx += n;
+            } else if header > 128 {
+                let n = 257 - header as usize;
+                let (Some(byte), Some(out)) =
+                    (input.get(in_idx), out_buff.get_mut(out_idx..out_idx + n))
+                else {
+                    return Err(io::Error::other("Run overruns the strip"));
+                };
+                out.fill(*byte);
 
-        while in_idx < input_len as usize && out_idx < output_len as usize {
-            let byte = in_buff[in_idx];
-            let count = byte as usize;
-
-            if byte == 128 {
                 in_idx += 1;
-                continue;
-            } else if byte > 128 {
-                let next_byte = in_buff[in_idx + 1];
-                out_buff[out_idx..(out_idx + 256 - count + 1)].fill(next_byte);
-
-                out_idx += 256 - count + 1;
-                in_idx += 2;
-            } else {
-                let bytes = &in_buff[in_idx + 1..in_idx + count + 2];
-                out_buff[out_idx..(out_idx + count + 1)].copy_from_slice(bytes);
-
-                out_idx += count + 1;
-                in_idx += count + 2;
+                out_idx += n;
             }
         }
 
@@ -90,9 +79,86 @@ impl Compression {
     }
 }
 
+// Decodes PackBits data a row at a time, so rows can be used as they
+// appear rather than once the whole strip is decoded. Runs may carry on
+// from one row into the next
+pub struct PackBitsRows<'a> {
+    input: &'a [u8],
+    pos: usize,
+    run: Run,
+    row: Vec<u8>,
+}
+
+// Bytes left of the run being decoded: a literal continuing at input[pos]
+// or a repeated byte
+enum Run {
+    Literal(usize),
+    Repeat(u8, usize),
+}
+
+impl<'a> PackBitsRows<'a> {
+    pub fn new(input: &'a [u8], row_len: usize) -> Self {
+        PackBitsRows {
+            input,
+            pos: 0,
+            run: Run::Literal(0),
+            row: vec![0; row_len],
+        }
+    }
+
+    pub fn next_row(&mut self) -> io::Result<&[u8]> {
+        let mut fill = 0;
+
+        while fill < self.row.len() {
+            let space = self.row.len() - fill;
+            match self.run {
+                Run::Literal(0) | Run::Repeat(_, 0) => {
+                    let Some(&header) = self.input.get(self.pos) else {
+                        return Err(io::Error::other("Data ends within the strip"));
+                    };
+                    self.pos += 1;
+
+                    self.run = match header {
+                        // No-op
+                        128 => Run::Literal(0),
+                        h if h > 128 => {
+                            let Some(&byte) = self.input.get(self.pos) else {
+                                return Err(io::Error::other("Run overruns the data"));
+                            };
+                            self.pos += 1;
+                            Run::Repeat(byte, 257 - h as usize)
+                        }
+                        h => Run::Literal(h as usize + 1),
+                    };
+                }
+                Run::Literal(left) => {
+                    let n = left.min(space);
+                    let Some(bytes) = self.input.get(self.pos..self.pos + n) else {
+                        return Err(io::Error::other("Literal overruns the data"));
+                    };
+                    self.row[fill..fill + n].copy_from_slice(bytes);
+
+                    self.pos += n;
+                    fill += n;
+                    self.run = Run::Literal(left - n);
+                }
+                Run::Repeat(byte, left) => {
+                    let n = left.min(space);
+                    self.row[fill..fill + n].fill(byte);
+
+                    fill += n;
+                    self.run = Run::Repeat(byte, left - n);
+                }
+            }
+        }
+
+        Ok(&self.row)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::format_in::tiff::compression::Compression;
+    use crate::format_in::tiff::compression::{Compression, PackBitsRows};
     use ome_common_rs::ios::RandomAccessInputStream;
 
     #[test]
@@ -114,5 +180,20 @@ mod tests {
         Compression::unpackbits_stream(&mut istream, &mut output_buff, 24).unwrap();
 
         assert_eq!(output_buff, expected_output);
+
+        // The same rows of 5 bytes, runs crossing between rows
+        let mut rows = PackBitsRows::new(&input, 5);
+        for row in expected_output.chunks_exact(5).take(4) {
+            assert_eq!(rows.next_row().unwrap(), row);
+        }
+        assert!(rows.next_row().is_err());
+
+        // From memory, stopping at the expected length
+        let mut output_buff = vec![0; 24];
+        Compression::unpackbits(&input, input.len() as u64, &mut output_buff, 6).unwrap();
+        assert_eq!(output_buff[..6], expected_output[..6]);
+        assert!(output_buff[6..].iter().all(|b| *b == 0));
+        assert!(Compression::unpackbits(&[4, 1, 2], 3, &mut output_buff, 24).is_err());
+        assert!(Compression::unpackbits(&input, 15, &mut output_buff[..8], 24).is_err());
     }
 }
@@ -1,15 +1,17 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display};
 
 use either::Either;
 
 #[derive(Debug)]
 pub struct IFD {
+    // Where the IFD starts in the file
+    offset: u64,
     next_ifd_offset: u64,
     entries: HashMap<Tag, Entry>,
 }
 
 impl IFD {
-    pub fn new(entry_vec: Vec<Entry>, next_ifd_offset: u64) -> Self {
+    pub fn new(entry_vec: Vec<Entry>, offset: u64, next_ifd_offset: u64) -> Self {
         let mut entries = HashMap::new();
 
         entry_vec.into_iter().for_each(|a| {
@@ -17,10 +19,16 @@ impl IFD {
         });
 
         IFD {
+            offset,
             next_ifd_offset,
             entries,
         }
     }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
     pub fn next_ifd_offset(&self) -> &u64 {
         &self.next_ifd_offset
     }
@@ -37,12 +45,17 @@ impl IFD {
         self.entries.get(&tag)
     }
 
+    pub fn tags(&self) -> impl Iterator<Item = &Tag> {
+        self.entries.keys()
+    }
+
     pub fn size_of(kind: Type, count: u64) -> u64 {
         match kind {
-            Type::ASCII | Type::BYTE | Type::UNDEFINED => 1 * count as u64,
-            Type::SHORT => 2 * count as u64,
-            Type::LONG => 4 * count as u64,
-            Type::RATIONAL | Type::DOUBLE => 8 * count as u64,
+            // Counts come from the file, so sizes saturate
+            Type::ASCII | Type::BYTE | Type::UNDEFINED => count,
+            Type::SHORT => count.saturating_mul(2),
+            Type::LONG => count.saturating_mul(4),
+            Type::RATIONAL | Type::DOUBLE => count.saturating_mul(8),
         }
     }
 }
@@ -68,46 +81,62 @@ impl Entry {
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum Tag {
+    NewSubfileType = 254,
     ImageWidth = 256,
     ImageLength = 257,
     BitsPerSample = 258,
     Compression = 259,
     PhotometricInterpretation = 262,
     FillOrder = 266,
+    ImageDescription = 270,
     StripOffsets = 273,
     Orientation = 274,
     SamplesPerPixel = 277,
     RowsPerStrip = 278,
     StripByteCounts = 279,
+    MinSampleValue = 280,
+    MaxSampleValue = 281,
     XResolution = 282,
     YResolution = 283,
     PlanarConfiguration = 284,
     ResolutionUnit = 296,
+    DateTime = 306,
+    ColorMap = 320,
     ExtraSamples = 338,
     SampleFormat = 339,
+    SMinSampleValue = 340,
+    SMaxSampleValue = 341,
     Other = 0,
 }
 
 impl Tag {
     pub fn from_short(val: u16) -> Option<Self> {
         match val {
+            254 => Some(Self::NewSubfileType),
             256 => Some(Self::ImageWidth),
             257 => Some(Self::ImageLength),
             258 => Some(Self::BitsPerSample),
             259 => Some(Self::Compression),
             262 => Some(Self::PhotometricInterpretation),
             266 => Some(Self::FillOrder),
+            270 => Some(Self::ImageDescription),
             273 => Some(Self::StripOffsets),
             274 => Some(Self::Orientation),
             277 => Some(Self::SamplesPerPixel),
             278 => Some(Self::RowsPerStrip),
             279 => Some(Self::StripByteCounts),
+            280 => Some(Self::MinSampleValue),
+            281 => Some(Self::MaxSampleValue),
             282 => Some(Self::XResolution),
             283 => Some(Self::YResolution),
             284 => Some(Self::PlanarConfiguration),
             296 => Some(Self::ResolutionUnit),
+            306 => Some(Self::DateTime),
+            320 => Some(Self::ColorMap),
             338 => Some(Self::ExtraSamples),
             339 => Some(Self::SampleFormat),
+            340 => Some(Self::SMinSampleValue),
+            341 => Some(Self::SMaxSampleValue),
             _ => Some(Self::Other),
         }
     }
@@ -158,6 +187,23 @@ pub enum Datum {
     RAT(Vec<(u32, u32)>), // Type::RATIONAL
 }
 
+impl Display for Datum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |v: Vec<String>| v.join(" ");
+
+        let s = match self {
+            Self::STR(s) => s.trim_end_matches('\0').to_string(),
+            Self::U8(v) => join(v.iter().map(|a| a.to_string()).collect()),
+            Self::U16(v) => join(v.iter().map(|a| a.to_string()).collect()),
+            Self::U32(v) => join(v.iter().map(|a| a.to_string()).collect()),
+            Self::U64(v) => join(v.iter().map(|a| a.to_string()).collect()),
+            Self::RAT(v) => join(v.iter().map(|(n, d)| format!("{n}/{d}")).collect()),
+        };
+
+        write!(f, "{s}")
+    }
+}
+
 impl Datum {
     pub fn to_vec_u64(&self) -> Option<Vec<u64>> {
         match self {
@@ -3,4 +3,4 @@ pub mod ifd;
 pub mod tiff_parser;
 
 pub use ifd::Datum;
-pub use tiff_parser::TiffParser;
+pub use tiff_parser::{RawStrip, StripTable, TiffParser, decode_strip, decode_strip_rows};
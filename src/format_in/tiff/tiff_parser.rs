@@ -1,195 +1,523 @@
 use std::{
+    collections::{HashMap, HashSet},
     fs::File,
     io::{self, Error},
+    ops::Range,
+    sync::Arc,
+    thread::{self, JoinHandle},
 };
 
 use either::Either::{Left, Right};
 use ome_common_rs::ios::RandomAccessInputStream;
 
+use crate::OmeError;
 use crate::format_in::{
-    ByteOrder,
+    ByteOrder, PixelType, ReadAt, Source, read_plan,
+    source::read_full_at,
     tiff::{
         Datum,
-        compression::Compression,
+        compression::{Compression, PackBitsRows},
         ifd::{Entry, IFD, Tag, Type},
     },
+    translate::OriginalMetadata,
 };
 
+// Largest gap between tag values read_entries reads through rather than
+// seeking past
+const MAX_GAP: u64 = 4096;
+
+// Where a parser reads from: positioned reads of a handle which clones of
+// the parser may share, or seek and read pairs of a stream of its own
+enum Input {
+    At(Arc<dyn ReadAt>),
+    Stream(RandomAccessInputStream<Box<dyn Source>>),
+}
+
+impl Input {
+    // Read into the whole of buf unless the data ends first
+    fn read(&mut self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        match self {
+            Input::At(source) => read_full_at(source.as_ref(), buf, offset),
+            Input::Stream(istream) => istream.read(buf, offset),
+        }
+    }
+
+    fn length(&mut self) -> io::Result<u64> {
+        match self {
+            Input::At(source) => source.length(),
+            Input::Stream(istream) => istream.length(),
+        }
+    }
+}
+
 pub struct TiffParser {
-    istream: RandomAccessInputStream<File>,
+    input: Input,
+    is_le: bool,
     is_big_tiff: bool,
     first_ifd_offset: u64,
+    // Offsets of the IFDs located so far, in file order, and their number
+    // once the whole chain has been followed
+    ifd_offsets: Vec<u64>,
+    ifd_count: Option<u64>,
+    // IFDs parsed so far by index, and the tag values read from beyond
+    // their entries keyed by IFD offset
+    ifds: HashMap<u64, Arc<IFD>>,
+    data: HashMap<(u64, Tag), Datum>,
+    // Strip tables by IFD offset, consulted for every strip read
+    strips: HashMap<u64, Arc<StripTable>>,
+    // Compressed strip data and uncompressed rows, reused from one strip
+    // to the next
+    scratch: Vec<u8>,
+    row: Vec<u8>,
+    // Strips of an IFD being read ahead on another thread, and those read
+    // ahead so far, by IFD offset
+    pending: Option<(u64, JoinHandle<io::Result<Vec<RawStrip>>>)>,
+    prefetched: Option<(u64, Vec<Option<RawStrip>>)>,
+}
+
+// Where each strip of an IFD lies in the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StripTable {
+    pub offsets: Vec<u64>,
+    pub byte_counts: Vec<u64>,
+}
+
+impl StripTable {
+    // (offset, byte count) of strip i
+    pub fn get(&self, i: u64) -> Option<(u64, u64)> {
+        let i = i as usize;
+        Some((*self.offsets.get(i)?, *self.byte_counts.get(i)?))
+    }
 }
 
 impl TiffParser {
     pub fn new(file: String) -> io::Result<Self> {
-        let mut istream = RandomAccessInputStream::from_file(file)?;
-        let (is_big_tiff, first_ifd_offset) = Self::init_stream(&mut istream)?;
-        // let bytes_per_entry = if is_big_tiff { 20 } else { 12 };
+        Self::from_read_at(File::open(file)?)
+    }
+
+    pub fn from_source(source: impl Source + 'static) -> io::Result<Self> {
+        let source: Box<dyn Source> = Box::new(source);
+        Self::from_input(Input::Stream(RandomAccessInputStream::new(source)?))
+    }
+
+    // A parser reading with positioned reads, whose handle clones made with
+    // share use too
+    pub fn from_read_at(source: impl ReadAt + 'static) -> io::Result<Self> {
+        Self::from_input(Input::At(Arc::new(source)))
+    }
+
+    fn from_input(mut input: Input) -> io::Result<Self> {
+        let (is_le, is_big_tiff, first_ifd_offset) = Self::read_header(&mut input)?;
 
         Ok(Self {
-            istream,
+            input,
+            is_le,
             is_big_tiff,
             first_ifd_offset,
+            ifd_offsets: vec![first_ifd_offset],
+            ifd_count: None,
+            ifds: HashMap::new(),
+            data: HashMap::new(),
+            strips: HashMap::new(),
+            scratch: Vec::new(),
+            row: Vec::new(),
+            pending: None,
+            prefetched: None,
         })
     }
 
-    fn init_stream(istream: &mut RandomAccessInputStream<File>) -> io::Result<(bool, u64)> {
-        istream.seek_abs(0)?;
+    // A parser reading the same TIFF through another handle, reusing the
+    // header and the IFDs and tag values parsed so far
+    pub fn try_clone(&self, source: impl Source + 'static) -> io::Result<Self> {
+        let source: Box<dyn Source> = Box::new(source);
+        let istream = RandomAccessInputStream::new(source)?;
+        Ok(self.with_input(Input::Stream(istream)))
+    }
 
-        let first_two_chars = (istream.read_char()?, istream.read_char()?);
-        let is_le = match first_two_chars {
-            ('I', 'I') => Ok(true),
-            ('M', 'M') => Ok(false),
-            _ => Err(Error::other(format!("First two bytes incorrect"))),
-        }?;
+    // As try_clone, through another handle read with positioned reads
+    pub fn try_clone_at(&self, source: impl ReadAt + 'static) -> Self {
+        self.with_input(Input::At(Arc::new(source)))
+    }
 
-        istream.order(is_le);
+    // A parser reading through this one's handle, for another thread,
+    // if it's read with positioned reads
+    pub fn share(&self) -> Option<Self> {
+        match &self.input {
+            Input::At(source) => Some(self.with_input(Input::At(source.clone()))),
+            Input::Stream(_) => None,
+        }
+    }
 
-        let is_bt = match istream.read_u16()? {
+    fn with_input(&self, input: Input) -> Self {
+        Self {
+            input,
+            is_le: self.is_le,
+            is_big_tiff: self.is_big_tiff,
+            first_ifd_offset: self.first_ifd_offset,
+            ifd_offsets: self.ifd_offsets.clone(),
+            ifd_count: self.ifd_count,
+            ifds: self.ifds.clone(),
+            data: self.data.clone(),
+            strips: self.strips.clone(),
+            scratch: Vec::new(),
+            row: Vec::new(),
+            pending: None,
+            prefetched: None,
+        }
+    }
+
+    // (little endian, BigTIFF, first IFD offset) from the header
+    fn read_header(input: &mut Input) -> io::Result<(bool, bool, u64)> {
+        let mut header = [0; 16];
+        let n = input.read(&mut header, 0)?;
+
+        let is_le = match &header[..2] {
+            b"II" => Ok(true),
+            b"MM" => Ok(false),
+            _ => Err(OmeError::NotTiff("byte order mark isn't II or MM")),
+        }?;
+
+        let is_bt = match uint(&header[2..4], is_le) {
             43 => Ok(true),
             42 => Ok(false),
-            _ => Err(Error::other(format!("Invalid magic number"))),
+            _ => Err(OmeError::NotTiff("invalid magic number")),
         }?;
 
-        let first_offset = if is_bt {
-            istream.skip_bytes(4)?;
-            istream.read_u64()?
-        } else {
-            istream.read_u32()? as u64
+        let (first_offset, header_len) = match is_bt {
+            true => (uint(&header[8..16], is_le), 16),
+            false => (uint(&header[4..8], is_le), 8),
         };
+        if n < header_len {
+            return Err(OmeError::Truncated {
+                offset: 0,
+                read: n,
+                expected: header_len,
+            }
+            .into());
+        }
 
-        Ok((is_bt, first_offset))
+        Ok((is_le, is_bt, first_offset))
     }
 
-    fn read_ifd(&mut self) -> io::Result<IFD> {
-        let n_entries = if self.is_big_tiff {
-            self.istream.read_u64()?
-        } else {
-            self.istream.read_u16()? as u64
-        };
+    // An unsigned integer of size bytes at offset
+    fn read_uint(&mut self, offset: u64, size: usize) -> io::Result<u64> {
+        let mut buf = [0; 8];
+        let n = self.input.read(&mut buf[..size], offset)?;
+        if n < size {
+            return Err(OmeError::Truncated {
+                offset,
+                read: n,
+                expected: size,
+            }
+            .into());
+        }
+        Ok(uint(&buf[..size], self.is_le))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
+    fn read_ifd(&mut self, ifd_offset: u64) -> io::Result<IFD> {
+        let (entry_size, offset_size) = if self.is_big_tiff { (20, 8) } else { (12, 4) };
+        let (n_entries, table_offset, table_len) = self.entry_table(ifd_offset)?;
 
+        // The entry table and the next IFD's offset in one read, parsed from
+        // memory
+        let mut table = vec![0; table_len];
+        let n = self.input.read(&mut table, table_offset)?;
+        if n < table_len {
+            return Err(OmeError::Truncated {
+                offset: table_offset,
+                read: n,
+                expected: table_len,
+            }
+            .into());
+        }
+
+        let is_le = self.is_le;
+        let (entries, next) = table.split_at(table_len - offset_size);
         let mut entry_vec = Vec::with_capacity(n_entries as usize);
 
-        for _ in 0..n_entries {
-            let tag_short = self.istream.read_u16()?;
+        for (i, raw) in entries.chunks_exact(entry_size).enumerate() {
+            let tag_short = uint(&raw[..2], is_le) as u16;
             let tag = Tag::from_short(tag_short)
                 .ok_or(Error::other(format!("Failed Parse Tag: {tag_short}")))?;
 
-            let kind_short = self.istream.read_u16()?;
-            let kind = Type::from_short(kind_short)
-                .ok_or(Error::other(format!("Failed Parse Type: {kind_short}")))?;
-
-            let count = self.read_offset()?;
+            let kind_short = uint(&raw[2..4], is_le) as u16;
+            let kind = Type::from_short(kind_short).ok_or(OmeError::UnknownType {
+                ifd_offset,
+                tag: tag_short,
+                kind: kind_short,
+            })?;
 
+            let count = uint(&raw[4..4 + offset_size], is_le);
+            let value = &raw[4 + offset_size..];
             let n_bytes = IFD::size_of(kind, count);
 
-            // println!(
-            //     "TAG: {:<25}  | KIND: {:10}  | COUNT: {:6}  | BYTES: {:4}",
-            //     tag.to_str(),
-            //     kind.to_str(),
-            //     count,
-            //     n_bytes
-            // );
-
-            let offset;
-            let threshold = if self.is_big_tiff { 8 } else { 4 };
-
-            if n_bytes > threshold {
-                offset = Left(self.read_offset()?);
+            let offset = if n_bytes > offset_size as u64 {
+                Left(uint(value, is_le))
             } else {
-                offset = Right(self.read_datum(kind, count)?);
-                self.istream.skip_bytes(threshold - n_bytes)?;
+                let value_offset = table_offset + (i * entry_size + 4 + offset_size) as u64;
+                Right(parse_datum(
+                    kind,
+                    value[..n_bytes as usize].to_vec(),
+                    is_le,
+                    value_offset,
+                )?)
             };
 
             entry_vec.push(Entry::new(tag, kind, count, offset))
         }
 
-        let next_ifd_offset = self.read_offset()?;
-        let new_ifd = IFD::new(entry_vec, next_ifd_offset);
+        let next_ifd_offset = uint(next, is_le);
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(ifd_offset, n_entries, next_ifd_offset, "parsed IFD");
+
+        let new_ifd = IFD::new(entry_vec, ifd_offset, next_ifd_offset);
 
         Ok(new_ifd)
     }
 
+    // (entry count, offset, length) of the entry table of the IFD at
+    // ifd_offset, with the next IFD's offset after it. The count is checked
+    // against the end of the source before anything is sized from it
+    fn entry_table(&mut self, ifd_offset: u64) -> io::Result<(u64, u64, usize)> {
+        let (count_size, entry_size, offset_size) = match self.is_big_tiff {
+            true => (8, 20, 8),
+            false => (2, 12, 4),
+        };
+        let n_entries = self.read_uint(ifd_offset, count_size)?;
+
+        let table_offset = ifd_offset + count_size as u64;
+        let available = self.input.length()?.saturating_sub(table_offset);
+        let table_len = n_entries
+            .checked_mul(entry_size)
+            .and_then(|len| len.checked_add(offset_size));
+        match table_len {
+            Some(len) if len <= available => Ok((n_entries, table_offset, len as usize)),
+            _ => Err(OmeError::Truncated {
+                offset: table_offset,
+                read: available as usize,
+                expected: table_len.map_or(usize::MAX, |len| len.min(usize::MAX as u64) as usize),
+            }
+            .into()),
+        }
+    }
+
     // The number of IFDs
     pub fn n_ifds(&mut self) -> io::Result<i32> {
-        let mut count = 1;
-        self.istream.seek_abs(self.first_ifd_offset)?;
-        let mut curr_ifd = self.read_ifd()?;
+        if let Some(n) = self.ifd_count {
+            return Ok(n as i32);
+        }
 
-        while *curr_ifd.next_ifd_offset() != 0 {
-            count += 1;
-            self.istream.seek_abs(*curr_ifd.next_ifd_offset())?;
-            curr_ifd = self.read_ifd()?;
+        // Follow the chain through each IFD's entry count and next offset,
+        // leaving the entries to be parsed when the IFD is used
+        let offset_size = if self.is_big_tiff { 8 } else { 4 };
+        loop {
+            let j = self.ifd_offsets.len() as u64 - 1;
+            let next_offset = match self.ifds.get(&j) {
+                Some(ifd) => *ifd.next_ifd_offset(),
+                None => {
+                    let (_, table_offset, table_len) =
+                        self.entry_table(self.ifd_offsets[j as usize])?;
+                    let next_at = table_offset + (table_len - offset_size) as u64;
+                    self.read_uint(next_at, offset_size)?
+                }
+            };
+
+            if next_offset == 0 {
+                break;
+            }
+            self.ifd_offsets.push(next_offset);
         }
 
-        Ok(count)
+        self.ifd_count = Some(self.ifd_offsets.len() as u64);
+        Ok(self.ifd_offsets.len() as i32)
     }
 
-    fn read_offset(&mut self) -> io::Result<u64> {
-        if self.is_big_tiff {
-            self.istream.read_u64()
-        } else {
-            self.istream.read_u32().map(|v| v as u64)
+    // The i-th IFD, parsed on first use and shared after
+    pub fn nth_ifd(&mut self, i: u64) -> io::Result<Arc<IFD>> {
+        if let Some(ifd) = self.ifds.get(&i) {
+            return Ok(ifd.clone());
         }
-    }
 
-    pub fn nth_ifd(&mut self, i: u64) -> io::Result<IFD> {
-        self.istream.seek_abs(self.first_ifd_offset)?;
-        let mut curr_ifd = self.read_ifd()?;
+        // Walk on from the furthest IFD located so far
+        let mut j = i.min(self.ifd_offsets.len() as u64 - 1);
+        let mut curr_ifd = self.cached_ifd(j)?;
 
-        for j in 1..i + 1 {
-            let next_offset = curr_ifd.next_ifd_offset();
-            if *next_offset == 0 {
-                return Err(Error::other(format!("IFD idx out of bounds: {i}/{j}")));
+        while j < i {
+            let next_offset = *curr_ifd.next_ifd_offset();
+            if next_offset == 0 {
+                return Err(OmeError::IfdOutOfRange {
+                    index: i,
+                    count: j + 1,
+                }
+                .into());
             }
-            self.istream.seek_abs(*next_offset)?;
-            curr_ifd = self.read_ifd()?;
+            self.ifd_offsets.push(next_offset);
+            j += 1;
+            curr_ifd = self.cached_ifd(j)?;
         }
 
         Ok(curr_ifd)
     }
 
+    // IFD j, whose offset is known, from the cache or the file
+    fn cached_ifd(&mut self, j: u64) -> io::Result<Arc<IFD>> {
+        if let Some(ifd) = self.ifds.get(&j) {
+            return Ok(ifd.clone());
+        }
+
+        let ifd = Arc::new(self.read_ifd(self.ifd_offsets[j as usize])?);
+        self.ifds.insert(j, ifd.clone());
+        Ok(ifd)
+    }
+
     pub fn read_entry(&mut self, ifd: &IFD, tag: Tag) -> io::Result<Datum> {
-        let entry = ifd
-            .get_entry(tag)
-            .ok_or(Error::other(format!("Tag parse error: {:?}", tag)))?;
+        let entry = ifd.get_entry(tag).ok_or(bad_tag(ifd, tag))?;
 
         match &entry.offset_or_datum {
             Left(offset) => {
-                self.istream.seek_abs(*offset)?;
-                self.read_datum(entry.kind, entry.count)
+                let key = (ifd.offset(), tag);
+                if let Some(datum) = self.data.get(&key) {
+                    return Ok(datum.clone());
+                }
+
+                let datum = self.read_datum(entry.kind, entry.count, *offset)?;
+                self.data.insert(key, datum.clone());
+                Ok(datum)
             }
             // Cloning here is inexpensive as datum is limited to 4 bytes
             Right(datum) => Ok(datum.clone()),
         }
     }
 
-    fn read_datum(&mut self, kind: Type, count: u64) -> io::Result<Datum> {
-        let byte_count = IFD::size_of(kind, count) as usize;
-        let offset = self.istream.get_file_pointer()?;
+    // Several tags of an IFD, their values beyond the entries read in file
+    // order with those close together in one read, rather than a seek
+    // each. Tags the IFD lacks are left out
+    pub fn read_entries(&mut self, ifd: &IFD, tags: &[Tag]) -> io::Result<HashMap<Tag, Datum>> {
+        let mut out = HashMap::new();
+        // (offset, byte count, tag, type) of the values still to read
+        let mut wanted = Vec::new();
+        let mut seen = HashSet::new();
 
+        for &tag in tags {
+            let Some(entry) = ifd.get_entry(tag).filter(|_| seen.insert(tag)) else {
+                continue;
+            };
+            match (&entry.offset_or_datum, self.data.get(&(ifd.offset(), tag))) {
+                (Right(datum), _) | (Left(_), Some(datum)) => {
+                    out.insert(tag, datum.clone());
+                }
+                (Left(offset), None) => {
+                    wanted.push((
+                        *offset,
+                        IFD::size_of(entry.kind, entry.count),
+                        tag,
+                        entry.kind,
+                    ));
+                }
+            }
+        }
+        wanted.sort_by_key(|(offset, ..)| *offset);
+        if wanted.is_empty() {
+            return Ok(out);
+        }
+
+        let is_le = self.is_le;
+        let mut rest = &wanted[..];
+        while let Some(&(start, len, ..)) = rest.first() {
+            // Values separated by less than MAX_GAP bytes share a read. The
+            // offsets and counts come from the file, so sums saturate
+            let mut end = start.saturating_add(len);
+            let n_values = rest
+                .iter()
+                .take_while(|(offset, len, ..)| {
+                    let near = *offset <= end.saturating_add(MAX_GAP);
+                    if near {
+                        end = end.max(offset.saturating_add(*len));
+                    }
+                    near
+                })
+                .count();
+
+            // Spans past the end of the file are never allocated
+            self.check_span(start, end - start)?;
+
+            let mut span = vec![0; (end - start) as usize];
+            let n = self.input.read(&mut span, start)?;
+            if n < span.len() {
+                return Err(OmeError::Truncated {
+                    offset: start,
+                    read: n,
+                    expected: span.len(),
+                }
+                .into());
+            }
+
+            let (values, tail) = rest.split_at(n_values);
+            for &(offset, len, tag, kind) in values {
+                let at = (offset - start) as usize;
+                let datum = parse_datum(kind, span[at..at + len as usize].to_vec(), is_le, offset)?;
+                self.data.insert((ifd.offset(), tag), datum.clone());
+                out.insert(tag, datum);
+            }
+            rest = tail;
+        }
+
+        Ok(out)
+    }
+
+    fn read_datum(&mut self, kind: Type, count: u64, offset: u64) -> io::Result<Datum> {
+        // The count comes from the file, so is checked before allocating
+        let byte_count = IFD::size_of(kind, count);
+        self.check_span(offset, byte_count)?;
+        let byte_count = byte_count as usize;
         let mut buff = vec![0; byte_count];
 
-        let is_le = self.istream.is_little_endian();
-        let n = self.istream.read(&mut buff, offset)?;
+        let is_le = self.is_le;
+        let n = self.input.read(&mut buff, offset)?;
 
         if n < byte_count {
-            return Err(Error::other("Insufficient byes read"));
+            return Err(OmeError::Truncated {
+                offset,
+                read: n,
+                expected: byte_count,
+            }
+            .into());
         }
 
-        Ok(match kind {
-            Type::BYTE | Type::UNDEFINED => Datum::U8(buff),
-            Type::SHORT => Datum::from_bytes_u16(&buff, is_le),
-            Type::LONG => Datum::from_bytes_u32(&buff, is_le),
-            Type::DOUBLE => Datum::from_bytes_u64(&buff, is_le),
-            Type::ASCII => Datum::STR(String::from_utf8(buff).map_err(|_| Error::other("ASCII"))?),
-            Type::RATIONAL => Datum::from_bytes_rational(&buff, is_le),
-        })
+        parse_datum(kind, buff, is_le, offset)
+    }
+
+    // Every recognised tag of an IFD keyed by tag name, excluding the
+    // strip tables which describe layout rather than the image
+    pub fn original_metadata(&mut self, ifd: &IFD) -> io::Result<OriginalMetadata> {
+        let tags = ifd
+            .tags()
+            .filter(|t| !matches!(t, Tag::Other | Tag::StripOffsets | Tag::StripByteCounts))
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut out = OriginalMetadata::new();
+        if let Ok(data) = self.read_entries(ifd, &tags) {
+            for (tag, datum) in data {
+                out.insert(tag.to_str(), datum.to_string());
+            }
+            return Ok(out);
+        }
+
+        for tag in tags {
+            // Malformed descriptive tags shouldn't prevent opening the file
+            if let Ok(datum) = self.read_entry(ifd, tag) {
+                out.insert(tag.to_str(), datum.to_string());
+            }
+        }
+
+        Ok(out)
     }
 
     pub fn byte_order(&mut self) -> ByteOrder {
-        if self.istream.is_little_endian() {
+        if self.is_le {
             ByteOrder::LE
         } else {
             ByteOrder::BE
@@ -200,73 +528,176 @@ impl TiffParser {
         // Array of SHORT OR LONG in tiff spec, use most permissive
         self.read_entry(ifd, Tag::StripByteCounts)?
             .to_vec_u64()
-            .ok_or(Error::other("Failed parse strip byte counts"))
+            .ok_or(bad_tag(ifd, Tag::StripByteCounts))
+    }
+
+    // StripOffsets and StripByteCounts of an IFD, read once
+    pub fn strip_table(&mut self, ifd: &IFD) -> io::Result<Arc<StripTable>> {
+        if let Some(table) = self.strips.get(&ifd.offset()) {
+            return Ok(table.clone());
+        }
+
+        // Both arrays are usually stored side by side
+        self.read_entries(ifd, &[Tag::StripOffsets, Tag::StripByteCounts])?;
+        let table = Arc::new(StripTable {
+            offsets: self.strip_offsets(ifd)?,
+            byte_counts: self.strip_byte_counts(ifd)?,
+        });
+        self.strips.insert(ifd.offset(), table.clone());
+        Ok(table)
+    }
+
+    // 0 for a full resolution image, bit 0 marks a reduced resolution copy
+    pub fn new_subfile_type(&mut self, ifd: &IFD) -> io::Result<u64> {
+        if ifd.get_entry(Tag::NewSubfileType).is_none() {
+            return Ok(0);
+        }
+
+        self.read_entry(ifd, Tag::NewSubfileType)?
+            .to_u64()
+            .ok_or(bad_tag(ifd, Tag::NewSubfileType))
     }
 
     pub fn image_length(&mut self, ifd: &IFD) -> io::Result<u64> {
         self.read_entry(ifd, Tag::ImageLength)?
             .to_u64()
-            .ok_or(Error::other("Failed parse ImageLength"))
+            .ok_or(bad_tag(ifd, Tag::ImageLength))
     }
 
     pub fn image_width(&mut self, ifd: &IFD) -> io::Result<u64> {
         self.read_entry(ifd, Tag::ImageWidth)?
             .to_u64()
-            .ok_or(Error::other("Failed parse ImageWidth"))
+            .ok_or(bad_tag(ifd, Tag::ImageWidth))
     }
 
     pub fn rows_per_strip(&mut self, ifd: &IFD) -> io::Result<u64> {
         self.read_entry(ifd, Tag::RowsPerStrip)?
             .to_u64()
-            .ok_or(Error::other("Failed parse RowsPerStrip"))
+            .ok_or(bad_tag(ifd, Tag::RowsPerStrip))
     }
 
     pub fn strip_offsets(&mut self, ifd: &IFD) -> io::Result<Vec<u64>> {
         // Array of SHORT OR LONG in tiff spec, use most permissive
         self.read_entry(ifd, Tag::StripOffsets)?
             .to_vec_u64()
-            .ok_or(Error::other("Failed parse strip offsets"))
+            .ok_or(bad_tag(ifd, Tag::StripOffsets))
     }
 
     pub fn bits_per_sample(&mut self, ifd: &IFD) -> io::Result<Vec<u16>> {
         // Array of SHORT OR LONG in tiff spec, use most permissive
         self.read_entry(ifd, Tag::BitsPerSample)?
             .to_vec_u16()
-            .ok_or(Error::other("Failed parse bits per sample"))
+            .ok_or(bad_tag(ifd, Tag::BitsPerSample))
+    }
+
+    // 1 unsigned integer, 2 signed, 3 floating point; unsigned if missing
+    pub fn sample_format(&mut self, ifd: &IFD) -> io::Result<Vec<u16>> {
+        if ifd.get_entry(Tag::SampleFormat).is_none() {
+            return Ok(vec![1]);
+        }
+
+        self.read_entry(ifd, Tag::SampleFormat)?
+            .to_vec_u16()
+            .ok_or(bad_tag(ifd, Tag::SampleFormat))
+    }
+
+    // Sample type of each channel, a single BitsPerSample or SampleFormat
+    // value applying to all of them
+    pub fn pixel_types(&mut self, ifd: &IFD) -> io::Result<Vec<PixelType>> {
+        let bits = self.bits_per_sample(ifd)?;
+        let formats = self.sample_format(ifd)?;
+        let n = self.samples_per_pixel(ifd)? as usize;
+        let pick = |v: &[u16], i: usize| v.get(i).or(v.first()).copied().unwrap_or(1);
+
+        (0..n)
+            .map(|i| {
+                let (bits, format) = (pick(&bits, i), pick(&formats, i));
+                PixelType::from_tiff(bits, format).ok_or(
+                    OmeError::UnsupportedSampleType {
+                        ifd_offset: ifd.offset(),
+                        bits,
+                        format,
+                    }
+                    .into(),
+                )
+            })
+            .collect()
     }
 
     pub fn samples_per_pixel(&mut self, ifd: &IFD) -> io::Result<u16> {
         self.read_entry(ifd, Tag::SamplesPerPixel)?
             .to_u16()
-            .ok_or(Error::other("Failed parse samples per pixel"))
+            .ok_or(bad_tag(ifd, Tag::SamplesPerPixel))
     }
 
+    // 1 chunky, 2 planar; chunky if missing
     pub fn planar_configuration(&mut self, ifd: &IFD) -> io::Result<u16> {
+        if ifd.get_entry(Tag::PlanarConfiguration).is_none() {
+            return Ok(1);
+        }
+
         self.read_entry(ifd, Tag::PlanarConfiguration)?
             .to_u16()
-            .ok_or(Error::other("Failed parse planar configuratoin"))
+            .ok_or(bad_tag(ifd, Tag::PlanarConfiguration))
     }
 
     pub fn compression(&mut self, ifd: &IFD) -> io::Result<Compression> {
-        self.read_entry(ifd, Tag::Compression)?
+        let codec = self
+            .read_entry(ifd, Tag::Compression)?
             .to_u16()
-            .ok_or(Error::other("Failed parse compression"))
-            .map(|a| Compression::from_short(a).ok_or(Error::other("Failed parse compression")))
-            .flatten()
+            .ok_or(bad_tag(ifd, Tag::Compression))?;
+
+        Ok(
+            Compression::from_short(codec).ok_or(OmeError::UnsupportedCodec {
+                ifd_offset: ifd.offset(),
+                codec,
+            })?,
+        )
     }
 
     pub fn fill_order(&mut self, ifd: &IFD) -> io::Result<u16> {
         self.read_entry(ifd, Tag::FillOrder)?
             .to_u16()
-            .ok_or(Error::other("Failed parse fill order"))
+            .ok_or(bad_tag(ifd, Tag::FillOrder))
+    }
+
+    pub fn photometric_interpretation(&mut self, ifd: &IFD) -> io::Result<u16> {
+        self.read_entry(ifd, Tag::PhotometricInterpretation)?
+            .to_u16()
+            .ok_or(bad_tag(ifd, Tag::PhotometricInterpretation))
+    }
+
+    // Palette of a palette color image, 3 * 2^BitsPerSample values
+    pub fn color_map(&mut self, ifd: &IFD) -> io::Result<Vec<u16>> {
+        self.read_entry(ifd, Tag::ColorMap)?
+            .to_vec_u16()
+            .ok_or(bad_tag(ifd, Tag::ColorMap))
+    }
+
+    pub fn image_description(&mut self, ifd: &IFD) -> io::Result<String> {
+        match self.read_entry(ifd, Tag::ImageDescription)? {
+            Datum::STR(s) => Ok(s.trim_end_matches('\0').to_string()),
+            _ => Err(bad_tag(ifd, Tag::ImageDescription)),
+        }
+    }
+
+    pub fn date_time(&mut self, ifd: &IFD) -> io::Result<String> {
+        match self.read_entry(ifd, Tag::DateTime)? {
+            Datum::STR(s) => Ok(s.trim_end_matches('\0').to_string()),
+            _ => Err(bad_tag(ifd, Tag::DateTime)),
+        }
     }
 
     pub fn orientation(&mut self, ifd: &IFD) -> io::Result<u16> {
         self.read_entry(ifd, Tag::FillOrder)?
             .to_u16()
-            .ok_or(Error::other("Failed parse orientation"))
+            .ok_or(bad_tag(ifd, Tag::FillOrder))
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(self, ifd, out_buff), fields(ifd_offset = ifd.offset()))
+    )]
     pub fn read_strip(
         &mut self,
         ifd: &IFD,
@@ -274,46 +705,652 @@ impl TiffParser {
         out_buff: &mut [u8],
         expected_bytes: u64,
     ) -> io::Result<()> {
-        let strip_offsets = self.strip_offsets(ifd)?;
-        let offset = strip_offsets
-            .get(strip_idx as usize)
-            .ok_or(Error::other("Strip offset index out of range"))?;
-
-        let strip_byte_counts = self.strip_byte_counts(ifd)?;
-        let strip_byte_count = strip_byte_counts
-            .get(strip_idx as usize)
-            .ok_or(Error::other("Strip byte_count index out of range"))?;
-
-        let mut in_buff = vec![0; *strip_byte_count as usize];
-        self.istream.read(&mut in_buff, *offset)?;
-
-        match self.compression(&ifd)? {
-            Compression::PackBits => {
-                Compression::unpackbits(&mut in_buff, *strip_byte_count, out_buff, expected_bytes)?;
-            }
-            Compression::CCITT => todo!(),
+        let (offset, strip_byte_count) = self
+            .strip_table(ifd)?
+            .get(strip_idx)
+            .ok_or(strip_out_of_range(ifd, strip_idx))?;
+
+        let compression = self.compression(ifd)?;
+        if let Some(mut raw) = self.take_prefetched(ifd, strip_idx) {
+            return decode_strip(&compression, ifd, &mut raw, out_buff, expected_bytes);
+        }
+
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
+
+        match compression {
             Compression::None => {
-                self.istream.read(out_buff, *offset as u64)?;
+                self.read_fully(out_buff, offset)?;
+            }
+            _ => {
+                self.check_span(offset, strip_byte_count)?;
+                let mut data = std::mem::take(&mut self.scratch);
+                data.resize(strip_byte_count as usize, 0);
+                self.read_fully(&mut data, offset)?;
+
+                let mut raw = RawStrip {
+                    index: strip_idx,
+                    offset,
+                    data,
+                };
+                let decoded = decode_strip(&compression, ifd, &mut raw, out_buff, expected_bytes);
+                self.scratch = raw.data;
+                decoded?;
             }
         };
 
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            codec = ?compression,
+            compressed = strip_byte_count,
+            elapsed = ?start.elapsed(),
+            "decoded strip"
+        );
+
+        Ok(())
+    }
+
+    // Pass columns cols of each of rows of a strip of row_len byte rows to
+    // f in turn. Uncompressed rows are read one at a time and compressed
+    // ones decoded one at a time up to the last wanted, so no more than a
+    // row is held decoded
+    pub fn read_strip_rows(
+        &mut self,
+        ifd: &IFD,
+        strip_idx: u64,
+        row_len: usize,
+        rows: Range<u64>,
+        cols: Range<usize>,
+        mut f: impl FnMut(&[u8]) -> io::Result<()>,
+    ) -> io::Result<()> {
+        if rows.is_empty() || cols.is_empty() {
+            return Ok(());
+        }
+
+        let compression = self.compression(ifd)?;
+        if let Some(raw) = self.take_prefetched(ifd, strip_idx) {
+            return decode_strip_rows(&compression, ifd, &raw, row_len, rows, cols, f);
+        }
+
+        let (offset, strip_byte_count) = self
+            .strip_table(ifd)?
+            .get(strip_idx)
+            .ok_or(strip_out_of_range(ifd, strip_idx))?;
+
+        if let Compression::None = compression {
+            // Whole rows follow one another, parts of rows are read apart
+            let (reads, len) = match cols.len() == row_len {
+                true => (
+                    rows.start..rows.start + 1,
+                    (rows.end - rows.start) as usize * row_len,
+                ),
+                false => (rows.clone(), cols.len()),
+            };
+
+            let first = offset.saturating_add(rows.start.saturating_mul(row_len as u64));
+            self.check_span(
+                first,
+                (rows.end - rows.start).saturating_mul(row_len as u64),
+            )?;
+            let mut buf = std::mem::take(&mut self.row);
+            buf.resize(len, 0);
+            let read = reads.map(|r| {
+                let at = offset + r * row_len as u64 + cols.start as u64;
+                self.read_fully(&mut buf, at)?;
+                buf.chunks_exact(cols.len()).try_for_each(&mut f)
+            });
+            let read = read.collect::<io::Result<()>>();
+            self.row = buf;
+            return read;
+        }
+
+        self.check_span(offset, strip_byte_count)?;
+        let mut data = std::mem::take(&mut self.scratch);
+        data.resize(strip_byte_count as usize, 0);
+        self.read_fully(&mut data, offset)?;
+
+        let raw = RawStrip {
+            index: strip_idx,
+            offset,
+            data,
+        };
+        let decoded = decode_strip_rows(&compression, ifd, &raw, row_len, rows, cols, f);
+        self.scratch = raw.data;
+        decoded
+    }
+
+    // Fail with Truncated, before anything is allocated for them, when len
+    // bytes at offset run past the end of the file. Both come from the
+    // file, so the end saturates
+    fn check_span(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        let file_len = self.input.length()?;
+        if offset.saturating_add(len) > file_len {
+            return Err(OmeError::Truncated {
+                offset,
+                read: file_len.saturating_sub(offset).min(len) as usize,
+                expected: len.min(usize::MAX as u64) as usize,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    // Fill buf with the bytes at offset, failing if the file ends first
+    // rather than leaving the rest zero
+    fn read_fully(&mut self, buf: &mut [u8], offset: u64) -> io::Result<()> {
+        let n = self.input.read(buf, offset)?;
+        if n < buf.len() {
+            return Err(OmeError::Truncated {
+                offset,
+                read: n,
+                expected: buf.len(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    // The stored bytes of a strip, to be decoded with decode_strip, e.g. on
+    // another thread
+    pub fn read_raw_strip(&mut self, ifd: &IFD, strip_idx: u64) -> io::Result<RawStrip> {
+        if let Some(raw) = self.take_prefetched(ifd, strip_idx) {
+            return Ok(raw);
+        }
+
+        let (offset, strip_byte_count) = self
+            .strip_table(ifd)?
+            .get(strip_idx)
+            .ok_or(strip_out_of_range(ifd, strip_idx))?;
+
+        self.check_span(offset, strip_byte_count)?;
+        let mut data = vec![0; strip_byte_count as usize];
+        self.read_fully(&mut data, offset)?;
+        Ok(RawStrip {
+            index: strip_idx,
+            offset,
+            data,
+        })
+    }
+
+    // Strips run of an IFD in a single read spanning them, for strips lying
+    // close together in the file
+    pub fn read_raw_strips(&mut self, ifd: &IFD, run: Range<u64>) -> io::Result<Vec<RawStrip>> {
+        let table = self.strip_table(ifd)?;
+        let last = run.end.saturating_sub(1);
+        let span = read_plan::span(&table, &run).ok_or(strip_out_of_range(ifd, last))?;
+
+        let len = span.end.saturating_sub(span.start);
+        self.check_span(span.start, len)?;
+        let mut data = vec![0; len as usize];
+        self.read_fully(&mut data, span.start)?;
+        run.map(|i| {
+            let (offset, byte_count) = table.get(i).ok_or(strip_out_of_range(ifd, i))?;
+            let start = offset
+                .checked_sub(span.start)
+                .ok_or(strip_out_of_range(ifd, i))?;
+            let end = start
+                .checked_add(byte_count)
+                .ok_or(strip_out_of_range(ifd, i))?;
+            let bytes = data
+                .get(start as usize..end as usize)
+                .ok_or(strip_out_of_range(ifd, i))?;
+            Ok(RawStrip {
+                index: i,
+                offset,
+                data: bytes.to_vec(),
+            })
+        })
+        .collect()
+    }
+
+    // Start reading every strip of the i-th IFD on another thread sharing
+    // this parser's handle, so IO of the next plane overlaps decoding of
+    // this one. Later strip reads of that IFD wait for and use the result.
+    // IFDs storing more than budget bytes aren't read ahead
+    pub fn prefetch(&mut self, i: u64, budget: u64) -> io::Result<()> {
+        let ifd = self.nth_ifd(i)?;
+        if self.is_prefetched(&ifd) {
+            return Ok(());
+        }
+
+        let table = self.strip_table(&ifd)?;
+        let stored = table
+            .byte_counts
+            .iter()
+            .fold(0u64, |sum, n| sum.saturating_add(*n));
+        if stored > budget {
+            return Ok(());
+        }
+
+        let (offset, n_strips) = (ifd.offset(), table.offsets.len() as u64);
+        let mut parser = self.share().ok_or(Error::other(
+            "Only positioned reads can be shared to read ahead",
+        ))?;
+        // The read ahead being replaced finishes first, its strips kept
+        // for the read which may be under way
+        self.join_pending();
+        let handle = thread::spawn(move || {
+            (0..n_strips)
+                .map(|strip_idx| parser.read_raw_strip(&ifd, strip_idx))
+                .collect()
+        });
+
+        self.pending = Some((offset, handle));
         Ok(())
     }
 
+    // Whether strips of ifd are being or have been read ahead
+    pub fn is_prefetched(&self, ifd: &IFD) -> bool {
+        [
+            self.pending.as_ref().map(|(offset, _)| *offset),
+            self.prefetched.as_ref().map(|(offset, _)| *offset),
+        ]
+        .contains(&Some(ifd.offset()))
+    }
+
+    // Wait for any read ahead still running, so no thread is left doing IO
+    // for a parser, and drop the strips read ahead
+    pub fn cancel_prefetch(&mut self) {
+        if let Some((_, handle)) = self.pending.take() {
+            let _ = handle.join();
+        }
+        self.prefetched = None;
+    }
+
+    fn join_pending(&mut self) {
+        if let Some((offset, handle)) = self.pending.take() {
+            // A failed read ahead leaves the strips to be read as usual
+            self.prefetched = match handle.join() {
+                Ok(Ok(strips)) => Some((offset, strips.into_iter().map(Some).collect())),
+                _ => None,
+            };
+        }
+    }
+
+    fn take_prefetched(&mut self, ifd: &IFD, strip_idx: u64) -> Option<RawStrip> {
+        // Strips left over from an IFD since moved away from won't be read
+        if self
+            .prefetched
+            .as_ref()
+            .is_some_and(|(offset, _)| *offset != ifd.offset())
+        {
+            self.prefetched = None;
+        }
+
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|(offset, _)| *offset == ifd.offset())
+        {
+            self.join_pending();
+        }
+
+        match &mut self.prefetched {
+            Some((offset, strips)) if *offset == ifd.offset() => {
+                strips.get_mut(strip_idx as usize)?.take()
+            }
+            _ => None,
+        }
+    }
+
     pub fn is_big_tiff(&self) -> &bool {
         &self.is_big_tiff
     }
 }
 
+fn bad_tag(ifd: &IFD, tag: Tag) -> io::Error {
+    OmeError::BadTag {
+        ifd_offset: ifd.offset(),
+        tag,
+    }
+    .into()
+}
+
+// A strip as stored in the file
+#[derive(Debug, Clone)]
+pub struct RawStrip {
+    pub index: u64,
+    // Where the strip starts in the file
+    pub offset: u64,
+    pub data: Vec<u8>,
+}
+
+// Decode a strip of an IFD compressed with compression into out_buff,
+// expecting expected_bytes
+pub fn decode_strip(
+    compression: &Compression,
+    ifd: &IFD,
+    raw: &mut RawStrip,
+    out_buff: &mut [u8],
+    expected_bytes: u64,
+) -> io::Result<()> {
+    match compression {
+        Compression::None => {
+            let n = raw.data.len().min(out_buff.len());
+            out_buff[..n].copy_from_slice(&raw.data[..n]);
+        }
+        Compression::PackBits => {
+            let len = raw.data.len() as u64;
+            Compression::unpackbits(&raw.data, len, out_buff, expected_bytes).map_err(|e| {
+                OmeError::Codec {
+                    codec: "PackBits",
+                    strip: raw.index,
+                    offset: raw.offset,
+                    reason: e.to_string(),
+                }
+            })?;
+        }
+        Compression::CCITT => {
+            return Err(OmeError::UnsupportedCodec {
+                ifd_offset: ifd.offset(),
+                codec: Compression::CCITT as u16,
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+// A value of kind from its bytes, found at offset
+fn parse_datum(kind: Type, buff: Vec<u8>, is_le: bool, offset: u64) -> io::Result<Datum> {
+    Ok(match kind {
+        Type::BYTE | Type::UNDEFINED => Datum::U8(buff),
+        Type::SHORT => Datum::from_bytes_u16(&buff, is_le),
+        Type::LONG => Datum::from_bytes_u32(&buff, is_le),
+        Type::DOUBLE => Datum::from_bytes_u64(&buff, is_le),
+        Type::ASCII => Datum::STR(
+            String::from_utf8(buff)
+                .map_err(|_| Error::other(format!("Invalid ASCII at offset {offset}")))?,
+        ),
+        Type::RATIONAL => Datum::from_bytes_rational(&buff, is_le),
+    })
+}
+
+// An unsigned integer of 2, 4 or 8 bytes
+fn uint(bytes: &[u8], is_le: bool) -> u64 {
+    let mut out = [0; 8];
+    if is_le {
+        out[..bytes.len()].copy_from_slice(bytes);
+        u64::from_le_bytes(out)
+    } else {
+        out[8 - bytes.len()..].copy_from_slice(bytes);
+        u64::from_be_bytes(out)
+    }
+}
+
+// As decode_strip, but passing columns cols of each of rows of row_len
+// bytes to f in turn rather than decoding the whole strip
+pub fn decode_strip_rows(
+    compression: &Compression,
+    ifd: &IFD,
+    raw: &RawStrip,
+    row_len: usize,
+    rows: Range<u64>,
+    cols: Range<usize>,
+    mut f: impl FnMut(&[u8]) -> io::Result<()>,
+) -> io::Result<()> {
+    match compression {
+        Compression::None => {
+            for r in rows {
+                let start = r as usize * row_len;
+                let Some(row) = raw.data.get(start..start + row_len) else {
+                    return Err(OmeError::Truncated {
+                        offset: raw.offset + start as u64,
+                        read: raw.data.len().saturating_sub(start),
+                        expected: row_len,
+                    }
+                    .into());
+                };
+                f(&row[cols.clone()])?;
+            }
+        }
+        Compression::PackBits => {
+            // Rows before the first wanted are decoded but not used
+            let mut decoder = PackBitsRows::new(&raw.data, row_len);
+            for r in 0..rows.end {
+                let row = decoder.next_row().map_err(|e| OmeError::Codec {
+                    codec: "PackBits",
+                    strip: raw.index,
+                    offset: raw.offset,
+                    reason: e.to_string(),
+                })?;
+                if r >= rows.start {
+                    f(&row[cols.clone()])?;
+                }
+            }
+        }
+        Compression::CCITT => {
+            return Err(OmeError::UnsupportedCodec {
+                ifd_offset: ifd.offset(),
+                codec: Compression::CCITT as u16,
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+fn strip_out_of_range(ifd: &IFD, strip: u64) -> io::Error {
+    OmeError::StripOutOfRange {
+        ifd_offset: ifd.offset(),
+        strip,
+    }
+    .into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::format_in::test_support::{TagValue, TestIfd, tiff};
 
     #[test]
     fn intialise_parser() {
         let tp = TiffParser::new("assets/example_valid.tiff".into()).unwrap();
 
         assert!(!tp.is_big_tiff);
-        assert!(!tp.istream.is_little_endian());
+        assert!(!tp.is_le);
+    }
+
+    #[test]
+    fn ifds_and_values_are_cached() {
+        let mut tp = TiffParser::new("assets/example_valid.tiff".into()).unwrap();
+
+        let ifd = tp.nth_ifd(0).unwrap();
+        assert!(Arc::ptr_eq(&ifd, &tp.nth_ifd(0).unwrap()));
+
+        let offsets = tp.strip_offsets(&ifd).unwrap();
+        assert!(tp.data.contains_key(&(ifd.offset(), Tag::StripOffsets)));
+        assert_eq!(tp.strip_offsets(&ifd).unwrap(), offsets);
+
+        let table = tp.strip_table(&ifd).unwrap();
+        assert_eq!(table.offsets, offsets);
+        assert!(Arc::ptr_eq(&table, &tp.strip_table(&ifd).unwrap()));
+        assert_eq!(table.get(0), Some((offsets[0], table.byte_counts[0])));
+
+        let clone = tp
+            .try_clone(File::open("assets/example_valid.tiff").unwrap())
+            .unwrap();
+        assert!(Arc::ptr_eq(&ifd, &clone.ifds[&0]));
+        let mut shared = tp.share().unwrap();
+        assert_eq!(shared.strip_table(&ifd).unwrap(), table);
+        let stream = TiffParser::from_source(File::open("assets/example_valid.tiff").unwrap());
+        assert!(stream.unwrap().share().is_none());
+        assert!(tp.nth_ifd(1).is_err());
+    }
+
+    #[test]
+    fn count_ifds_without_parsing() {
+        let mut tp = TiffParser::new("assets/example_valid.tiff".into()).unwrap();
+
+        assert_eq!(tp.n_ifds().unwrap(), 1);
+        assert!(tp.ifds.is_empty());
+        assert_eq!(tp.ifd_count, Some(1));
+        assert_eq!(*tp.nth_ifd(0).unwrap().next_ifd_offset(), 0);
+    }
+
+    #[test]
+    fn batch_tag_reads() {
+        let mut tp = TiffParser::new("assets/example_valid.tiff".into()).unwrap();
+        let ifd = tp.nth_ifd(0).unwrap();
+        let tags = [
+            Tag::StripOffsets,
+            Tag::ImageWidth,
+            Tag::BitsPerSample,
+            Tag::StripByteCounts,
+            Tag::ColorMap,
+        ];
+
+        let data = tp.read_entries(&ifd, &tags).unwrap();
+        assert_eq!(data.len(), 4);
+        assert!(!data.contains_key(&Tag::ColorMap));
+        assert!(tp.data.contains_key(&(ifd.offset(), Tag::StripByteCounts)));
+
+        let mut fresh = TiffParser::new("assets/example_valid.tiff".into()).unwrap();
+        for tag in &tags[..4] {
+            assert_eq!(
+                data[tag].to_string(),
+                fresh.read_entry(&ifd, *tag).unwrap().to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn batch_reads_of_malformed_offsets() {
+        let mut tp = TiffParser::new("assets/example_valid.tiff".into()).unwrap();
+        let ifd = tp.nth_ifd(0).unwrap();
+        let repeated = [Tag::StripOffsets, Tag::BitsPerSample, Tag::StripOffsets];
+        assert_eq!(tp.read_entries(&ifd, &repeated).unwrap().len(), 2);
+
+        // XResolution's value placed near the end of the 32 bit offsets
+        let ifd = TestIfd::filled(2, 1, 1).tag(282, TagValue::Rational(1, 1));
+        let mut bytes = tiff(vec![ifd]);
+        let entry = bytes
+            .windows(4)
+            .rposition(|w| w == [0x1a, 0x01, 5, 0])
+            .unwrap();
+        bytes[entry + 8..entry + 12].copy_from_slice(&(u32::MAX - 4).to_le_bytes());
+        let mut tp = TiffParser::from_source(std::io::Cursor::new(bytes)).unwrap();
+        let ifd = tp.nth_ifd(0).unwrap();
+
+        let tags = [Tag::XResolution, Tag::ImageWidth, Tag::XResolution];
+        let err = tp.read_entries(&ifd, &tags).unwrap_err();
+        assert!(matches!(
+            OmeError::from_io(&err),
+            Some(OmeError::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn strips_past_the_end_are_truncated() {
+        // A 2x2 strip moved to start 2 bytes before the end of the file
+        let mut bytes = tiff(vec![TestIfd::filled(2, 2, 1)]);
+        let entry = bytes
+            .windows(4)
+            .rposition(|w| w == [0x11, 0x01, 4, 0])
+            .unwrap();
+        let near_end = bytes.len() as u32 - 2;
+        bytes[entry + 8..entry + 12].copy_from_slice(&near_end.to_le_bytes());
+        let mut tp = TiffParser::from_read_at(bytes).unwrap();
+        let ifd = tp.nth_ifd(0).unwrap();
+
+        let truncated = |err: io::Error| {
+            matches!(
+                OmeError::from_io(&err),
+                Some(OmeError::Truncated { read: 2, .. })
+            )
+        };
+        assert!(truncated(
+            tp.read_strip(&ifd, 0, &mut [0; 4], 4).unwrap_err()
+        ));
+        assert!(truncated(tp.read_raw_strip(&ifd, 0).unwrap_err()));
+        assert!(truncated(tp.read_raw_strips(&ifd, 0..1).unwrap_err()));
+    }
+
+    #[test]
+    fn corrupt_strip_byte_counts_are_not_allocated() {
+        let ifd = TestIfd::filled(2, 2, 1)
+            .tag(259, TagValue::Short(32773))
+            .tag(279, TagValue::Long(u32::MAX));
+        let mut tp = TiffParser::from_read_at(tiff(vec![ifd])).unwrap();
+        let ifd = tp.nth_ifd(0).unwrap();
+
+        let truncated = |err: io::Error| {
+            matches!(
+                OmeError::from_io(&err),
+                Some(OmeError::Truncated { expected, .. }) if *expected == u32::MAX as usize
+            )
+        };
+        assert!(truncated(tp.read_raw_strip(&ifd, 0).unwrap_err()));
+        assert!(truncated(tp.read_raw_strips(&ifd, 0..1).unwrap_err()));
+        assert!(truncated(
+            tp.read_strip(&ifd, 0, &mut [0; 4], 4).unwrap_err()
+        ));
+    }
+
+    #[test]
+    fn corrupt_value_counts_are_not_allocated() {
+        // XResolution claiming u32::MAX rationals
+        let ifd = TestIfd::filled(2, 1, 1).tag(282, TagValue::Rational(1, 1));
+        let mut bytes = tiff(vec![ifd]);
+        let entry = bytes
+            .windows(4)
+            .rposition(|w| w == [0x1a, 0x01, 5, 0])
+            .unwrap();
+        bytes[entry + 4..entry + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut tp = TiffParser::from_read_at(bytes).unwrap();
+        let ifd = tp.nth_ifd(0).unwrap();
+
+        let err = tp.read_entry(&ifd, Tag::XResolution).unwrap_err();
+        assert!(matches!(
+            OmeError::from_io(&err),
+            Some(OmeError::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn entry_table_read_at_once() {
+        let mut tp = TiffParser::new("assets/example_valid.tiff".into()).unwrap();
+        let ifd = tp.nth_ifd(0).unwrap();
+        assert_eq!(tp.image_width(&ifd).unwrap(), 1979);
+        assert_eq!(tp.bits_per_sample(&ifd).unwrap(), vec![16; 4]);
+        assert_eq!(*ifd.next_ifd_offset(), 0);
+
+        // Cut short within the entry table
+        let mut bytes = std::fs::read("assets/example_valid.tiff").unwrap();
+        bytes.truncate(ifd.offset() as usize + 2 + 12 * 3);
+        let mut tp = TiffParser::from_source(std::io::Cursor::new(bytes)).unwrap();
+        let err = tp.nth_ifd(0).unwrap_err();
+        assert!(matches!(
+            OmeError::from_io(&err),
+            Some(OmeError::Truncated { read: 36, .. })
+        ));
+    }
+
+    #[test]
+    fn huge_entry_count_rejected_before_allocating() {
+        // A BigTIFF whose only IFD claims 2^60 entries
+        let mut bytes = b"II+\0".to_vec();
+        bytes.extend(8u16.to_le_bytes());
+        bytes.extend(0u16.to_le_bytes());
+        bytes.extend(16u64.to_le_bytes());
+        bytes.extend((1u64 << 60).to_le_bytes());
+        bytes.extend([0; 28]);
+
+        let mut tp = TiffParser::from_source(std::io::Cursor::new(bytes.clone())).unwrap();
+        for err in [tp.nth_ifd(0).unwrap_err(), tp.n_ifds().unwrap_err()] {
+            assert!(matches!(
+                OmeError::from_io(&err),
+                Some(OmeError::Truncated {
+                    offset: 24,
+                    read: 28,
+                    ..
+                })
+            ));
+        }
+
+        let mut tp = TiffParser::from_read_at(bytes).unwrap();
+        assert!(tp.nth_ifd(0).is_err());
     }
 }
@@ -1,134 +1,994 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fs::File;
 use std::io::{self, Error};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::slice::ChunksExactMut;
+use std::sync::Arc;
 
-use crate::format_in::{Dim, Loc, Metadata};
+use crate::format_in::detect::FormatDetector;
+
+use crate::format_in::translate::{OmeXmlTranslator, TiffTranslator, translate};
+use crate::format_in::{
+    ByteOrder, Dim, GlobalMetadata, Loc, Lut, Metadata, MetadataLevel, OutOfBounds, PixelType,
+    ReadPlan, ReaderOptions, Region, SeriesMetadata, Source,
+};
+use crate::format_in::{layout, pixel, read_plan, region};
 
 use super::FormatReader;
 use super::tiff::TiffParser;
+use super::tiff::compression::Compression;
+use super::tiff::decode_strip_rows;
+use super::tile_cache::TileKey;
 
+#[derive(Default)]
 pub struct TiffReader {
-    parser: TiffParser,
+    file: Option<PathBuf>,
+    parser: Option<TiffParser>,
+    options: ReaderOptions,
+    series: u64,
+    resolution: u64,
+    // Set by fast init until the first pixel read
+    deferred: bool,
+    // Parsed once per file and shared with clones
+    metadata: Option<Arc<Metadata>>,
+    // Sizes and sample types of the series asked about so far, read from
+    // their own IFDs rather than by parsing every series
+    core: HashMap<u64, Arc<SeriesMetadata>>,
+    // IFD indices of each series, walked once per file and options
+    pyramids: Option<Arc<Vec<Vec<u64>>>>,
+    // The whole file, when read from memory with from_bytes
+    bytes: Option<Arc<[u8]>>,
 }
 
 impl TiffReader {
     pub fn new(file: String) -> io::Result<Self> {
-        Ok(Self {
-            parser: TiffParser::new(file)?,
+        let mut reader = Self::default();
+        reader.set_id(Path::new(&file))?;
+        Ok(reader)
+    }
+
+    // A reader for set_id to open files with the given options
+    pub fn with_options(options: ReaderOptions) -> Self {
+        TiffReader {
+            options,
+            ..Default::default()
+        }
+    }
+
+    // Read a TIFF from any seekable source, e.g. bytes already in memory
+    pub fn from_source(source: impl Source + 'static) -> io::Result<Self> {
+        Ok(TiffReader {
+            parser: Some(TiffParser::from_source(source)?),
+            ..Default::default()
         })
     }
-}
 
-impl FormatReader for TiffReader {
-    fn metadata(&mut self) -> io::Result<Metadata> {
-        let mut bpp = HashMap::new();
-        let mut dim = HashMap::new();
+    // Read a TIFF held in memory. Unlike from_source, uncompressed reads can
+    // then borrow the bytes rather than copy them, see open_bytes_cow
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>) -> io::Result<Self> {
+        let bytes = bytes.into();
+        Ok(TiffReader {
+            parser: Some(TiffParser::from_read_at(bytes.clone())?),
+            bytes: Some(bytes),
+            ..Default::default()
+        })
+    }
 
-        let be = self.parser.byte_order();
-        let ifd_count = self.parser.n_ifds()? as u64;
+    // An independent reader of the same file for another worker, sharing
+    // the parsed header and IFD index and starting at the same series.
+    // Reads are positioned, so the file handle is shared too
+    pub fn try_clone(&self) -> io::Result<Self> {
+        let (Some(file), Some(parser)) = (&self.file, &self.parser) else {
+            return Err(Error::other(
+                "Only readers opened with set_id can be cloned",
+            ));
+        };
+        let parser = match parser.share() {
+            Some(parser) => parser,
+            None => parser.try_clone(File::open(file)?)?,
+        };
 
-        for i in 0..ifd_count {
-            let ifd = self.parser.nth_ifd(i)?;
-            let w = self.parser.image_width(&ifd)?;
-            let h = self.parser.image_length(&ifd)?;
-            let c = self.parser.samples_per_pixel(&ifd)? as u64;
+        Ok(TiffReader {
+            file: Some(file.clone()),
+            parser: Some(parser),
+            options: self.options.clone(),
+            series: self.series,
+            resolution: self.resolution,
+            deferred: self.deferred,
+            metadata: self.metadata.clone(),
+            core: self.core.clone(),
+            pyramids: self.pyramids.clone(),
+            bytes: None,
+        })
+    }
+
+    // Replace the file handle, e.g. after the file was moved back into
+    // place, keeping everything parsed so far
+    pub fn reopen(&mut self) -> io::Result<()> {
+        let (Some(file), Some(parser)) = (&self.file, &self.parser) else {
+            return Err(Error::other(
+                "Only readers opened with set_id can be reopened",
+            ));
+        };
 
-            dim.insert(i, Dim::from_whc(w, h, c));
+        self.parser = Some(parser.try_clone_at(File::open(file)?));
+        Ok(())
+    }
 
-            let bpps = self.parser.bits_per_sample(&ifd)?;
+    fn parser(&mut self) -> io::Result<&mut TiffParser> {
+        self.parser
+            .as_mut()
+            .ok_or(Error::other("Reader not initialized, call set_id"))
+    }
+
+    pub fn set_metadata_level(&mut self, level: MetadataLevel) {
+        self.options.metadata_level = level;
+        self.metadata = None;
+        self.core.clear();
+    }
+
+    fn bytes_per_sample(&mut self, c: u64) -> io::Result<usize> {
+        let i = self.ifd_index()?;
+        let parser = self.parser()?;
+        let ifd = parser.nth_ifd(i)?;
+        let bits = parser.bits_per_sample(&ifd)?;
+
+        Ok(*bits.get(c as usize).ok_or(Error::other("Invalid c"))? as usize / 8)
+    }
+
+    // A region overlapping the edges of the plane, padded with value
+    fn open_padded(
+        &mut self,
+        loc: Loc,
+        region: Region,
+        value: f64,
+        buf: &mut [u8],
+    ) -> io::Result<()> {
+        let pixel_type = self
+            .series_core()?
+            .pixel_type(loc.c)
+            .ok_or(Error::other("Error reading pixel type"))?;
+        let bytes_per_sample = pixel_type.bytes();
+
+        if buf.len() != region.area() as usize * bytes_per_sample {
+            return Err(Error::other(format!(
+                "Buffer holds {} bytes, region needs {}",
+                buf.len(),
+                region.area() as usize * bytes_per_sample
+            )));
+        }
 
-            for (j, v) in bpps.iter().enumerate() {
-                bpp.insert((j as u64, i), *v);
+        let pad = pixel::encode(value, pixel_type, self.byte_order()?);
+        for dst in buf.chunks_exact_mut(bytes_per_sample) {
+            dst.copy_from_slice(&pad);
+        }
+
+        let (iw, ih) = self.resolution_size()?;
+        if let Some(inner) = region.intersection(&Region::full(iw, ih)) {
+            let bytes = self.open_bytes(loc, inner)?;
+            region::paste(&bytes, inner, buf, region, bytes_per_sample);
+        }
+
+        Ok(())
+    }
+
+    // Where region lies in the bytes given to from_bytes, if stored there
+    // as is: whole rows of a single sample image, uncompressed, in strips
+    // following one another
+    fn stored_range(&mut self, loc: Loc, region: Region) -> io::Result<Option<Range<usize>>> {
+        let Some(len) = self.bytes.as_ref().map(|b| b.len()) else {
+            return Ok(None);
+        };
+        let bytes_per_sample = self.bytes_per_sample(loc.c)?;
+        let i = self.ifd_index()?;
+        let parser = self.parser()?;
+        let ifd = parser.nth_ifd(i)?;
+
+        let (iw, ih) = (parser.image_width(&ifd)?, parser.image_length(&ifd)?);
+        let Region { x, y, w, h } = region;
+        if region.is_empty()
+            || x != 0
+            || w != iw
+            || y + h > ih
+            || parser.bits_per_sample(&ifd)?.len() != 1
+            || !matches!(parser.compression(&ifd)?, Compression::None)
+        {
+            return Ok(None);
+        }
+
+        let rows_per_strip = parser.rows_per_strip(&ifd)?.clamp(1, ih);
+        let row_len = iw * bytes_per_sample as u64;
+        let table = parser.strip_table(&ifd)?;
+        let (offsets, counts) = (&table.offsets, &table.byte_counts);
+        let strips = (y / rows_per_strip) as usize..=((y + h - 1) / rows_per_strip) as usize;
+
+        // Each strip must hold exactly its rows and end where the next begins
+        for s in strips.clone() {
+            let rows = rows_per_strip.min(ih - s as u64 * rows_per_strip);
+            let next = offsets.get(s + 1).copied();
+            match (offsets.get(s), counts.get(s)) {
+                (Some(o), Some(n)) if *n == rows * row_len => {
+                    if s < *strips.end() && next != Some(o + n) {
+                        return Ok(None);
+                    }
+                }
+                _ => return Ok(None),
             }
         }
 
-        Ok(Metadata {
-            dimensions: dim,
-            bits_per_pixel: bpp,
-            byte_order: be,
-        })
+        let start = offsets[*strips.start()] + (y % rows_per_strip) * row_len;
+        let end = start + h * row_len;
+        Ok((end <= len as u64).then_some(start as usize..end as usize))
+    }
+
+    // IFD indices of each series, walking the IFD chain on first use only
+    fn pyramids(&mut self) -> io::Result<Arc<Vec<Vec<u64>>>> {
+        if let Some(pyramids) = &self.pyramids {
+            return Ok(pyramids.clone());
+        }
+
+        let pyramids = Arc::new(self.read_pyramids()?);
+        self.pyramids = Some(pyramids.clone());
+        Ok(pyramids)
+    }
+
+    // IFD indices of each series, full resolution first. Reduced resolution
+    // IFDs follow the image they shrink and, unless flattened, join its
+    // series rather than forming their own
+    fn read_pyramids(&mut self) -> io::Result<Vec<Vec<u64>>> {
+        let flatten = self.options.flatten_resolutions;
+        let parser = self.parser()?;
+
+        // Every IFD is a series of its own, none need be parsed
+        if flatten {
+            return Ok((0..parser.n_ifds()? as u64).map(|i| vec![i]).collect());
+        }
+
+        let mut pyramids: Vec<Vec<u64>> = Vec::new();
+        for i in 0..parser.n_ifds()? as u64 {
+            let ifd = parser.nth_ifd(i)?;
+            let reduced = parser.new_subfile_type(&ifd)? & 1 == 1;
+
+            match pyramids.last_mut() {
+                Some(pyramid) if reduced => pyramid.push(i),
+                _ => pyramids.push(vec![i]),
+            }
+        }
+
+        Ok(pyramids)
+    }
+
+    // IFD holding the current series and resolution
+    fn ifd_index(&mut self) -> io::Result<u64> {
+        // Every IFD is a series of its own, no need to walk the chain
+        if self.options.flatten_resolutions && self.resolution == 0 {
+            return Ok(self.series);
+        }
+
+        let (series, resolution) = (self.series, self.resolution);
+
+        self.pyramids()?
+            .get(series as usize)
+            .and_then(|p| p.get(resolution as usize))
+            .copied()
+            .ok_or(Error::other(format!(
+                "Invalid series {series} / resolution {resolution}"
+            )))
+    }
+
+    // Full resolution IFD of the series after the current one, the IFD a
+    // caller reading planes in order reads next. None when reading a
+    // reduced resolution, whose successor isn't known
+    fn next_series_ifd(&mut self) -> io::Result<Option<u64>> {
+        if self.resolution > 0 {
+            return Ok(None);
+        }
+        if self.options.flatten_resolutions {
+            return Ok(Some(self.series + 1));
+        }
+
+        let next = self.series as usize + 1;
+        Ok(self.pyramids()?.get(next).map(|p| p[0]))
     }
 
-    fn open_bytes(&mut self, origin: Loc, h: u64, w: u64) -> io::Result<Vec<u8>> {
-        let Loc { x, y, z, c, t, s } = origin;
+    // Sizes, sample types and layout of a series from the IFDs of its
+    // pyramid, without the tags metadata() translates
+    fn read_series(&mut self, pyramid: &[u64]) -> io::Result<SeriesMetadata> {
+        let parser = self.parser()?;
+        let ifd = parser.nth_ifd(pyramid[0])?;
+        let w = parser.image_width(&ifd)?;
+        let h = parser.image_length(&ifd)?;
+        let c = parser.samples_per_pixel(&ifd)? as u64;
+
+        let mut s = SeriesMetadata::new(
+            Dim::from_whc(w, h, c),
+            parser.pixel_types(&ifd)?,
+            parser.planar_configuration(&ifd)? == 1,
+        );
+        s.resolution_count = pyramid.len() as u64;
+        Ok(s)
+    }
 
-        let ifd = self.parser.nth_ifd(s)?;
-        let iw = self.parser.image_width(&ifd)?;
-        let bits_per_sample = self.parser.bits_per_sample(&ifd)?;
-        let samples_per_pixel = bits_per_sample.len();
-        let bytes_per_sample = (bits_per_sample[c as usize] / 8) as usize;
-        let is_chunky = self.parser.planar_configuration(&ifd)? == 1;
-        let rows_per_strip = self.parser.rows_per_strip(&ifd)? as u64;
-        let n_strips = self.parser.strip_offsets(&ifd)?.len() as u64;
+    // The current series as read_series gives it, parsed on first use so
+    // that sizes of one series of a file with many IFDs are quick to get.
+    // Metadata translation doesn't change any of these
+    fn series_core(&mut self) -> io::Result<Arc<SeriesMetadata>> {
+        self.core_of(self.series)
+    }
+
+    fn core_of(&mut self, series: u64) -> io::Result<Arc<SeriesMetadata>> {
+        if let Some(s) = self.core.get(&series) {
+            return Ok(s.clone());
+        }
 
-        let bytes_per_pixel = if is_chunky {
-            // Chunky configuration, 'c' samples per pixel
-            bits_per_sample.into_iter().map(|a| a as u64).sum::<u64>() / 8
+        let pyramid = if self.options.flatten_resolutions {
+            vec![series]
+        } else if self.deferred && series == 0 {
+            // Its reduced resolutions aren't known until the chain is
+            // walked, so this isn't kept
+            return Ok(Arc::new(self.read_series(&[0])?));
         } else {
-            // Planar configuration, one sample per pixel
-            *bits_per_sample
-                .get(c as usize)
-                .ok_or(Error::other("Invalid c"))? as u64
-                / 8
+            self.pyramids()?
+                .get(series as usize)
+                .cloned()
+                .ok_or(Error::other(format!("Invalid series {series}")))?
         };
 
-        let start_idx = y / rows_per_strip;
-        let end_idx = (y + h) / rows_per_strip;
+        let s = Arc::new(self.read_series(&pyramid)?);
+        self.core.insert(series, s.clone());
+        Ok(s)
+    }
 
-        let mut buff = vec![0; (bytes_per_pixel * iw * rows_per_strip) as usize];
-        let mut out = Vec::with_capacity((h * w * bytes_per_pixel) as usize);
+    // Walk every series' IFDs and translate their tags; metadata() keeps
+    // the result until the file or options change
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    fn parse_metadata(&mut self) -> io::Result<Metadata> {
+        let mut series = Vec::new();
+        let lenient = self.options.lenient;
+        // Deferred, only the first IFD is read: the IFD chain is left for
+        // the first pixel read or a move to another series
+        let (level, pyramids) = if self.deferred {
+            (MetadataLevel::Minimum, Arc::new(vec![vec![0]]))
+        } else {
+            (self.options.metadata_level, self.pyramids()?)
+        };
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            deferred = self.deferred,
+            series = pyramids.len(),
+            "parsing metadata"
+        );
+        let progress = self.options.progress.clone();
+        let be = self.parser()?.byte_order();
 
-        for strip_idx in start_idx..end_idx + 1 {
-            // Calculate start/end indexes into image rows
-            let s_idx = (strip_idx * rows_per_strip) as usize;
-            let e_idx = ((strip_idx + 1) * rows_per_strip) as usize;
+        let total = pyramids.len() as u64;
+        for (i, pyramid) in pyramids.iter().enumerate() {
+            progress.step(i as u64, total)?;
+            let mut s = SeriesMetadata::clone(&*self.core_of(i as u64)?);
+            if level != MetadataLevel::Minimum {
+                let parser = self.parser()?;
+                let ifd = parser.nth_ifd(pyramid[0])?;
+                s.original_metadata = parser.original_metadata(&ifd)?;
+            }
 
-            // Calculate start/end indices into a vector of strip rows
-            let lower_idx = std::cmp::max(s_idx, y as usize) - s_idx;
-            let upper_idx = std::cmp::min(e_idx, (y + h) as usize) - s_idx;
+            series.push(s);
+        }
+        progress.step(total, total)?;
 
-            // Chunk and change
-            let bytes_per_row = bytes_per_pixel * iw;
-            let lower_col = (bytes_per_pixel * x) as usize;
-            let upper_col = lower_col + (bytes_per_pixel * w) as usize;
+        // As in Bio-Formats, the first IFD doubles as file-level metadata
+        let mut global = GlobalMetadata::new(be);
+        if let Some(first) = series.first() {
+            global.original_metadata = first.original_metadata.clone();
+        }
+
+        let mut md = Metadata { global, series };
+        if level == MetadataLevel::Minimum {
+            return Ok(md);
+        }
+
+        let translated = translate(&[&TiffTranslator, &OmeXmlTranslator::default()], &mut md);
+        if !lenient {
+            translated?;
+        }
+
+        if level == MetadataLevel::NoOriginal {
+            md.global.original_metadata.clear();
+            md.series
+                .iter_mut()
+                .for_each(|s| s.original_metadata.clear());
+        }
 
-            let expected_bytes = if strip_idx + 1 == n_strips {
-                bytes_per_pixel * iw * ((y + h) % rows_per_strip)
+        Ok(md)
+    }
+
+    // Copy channel c of region into each (c, buf) of outs, in one pass over
+    // the strips holding the region, swapping the bytes of 16 bit samples
+    // if swap16 is set
+    fn read_samples(
+        &mut self,
+        region: Region,
+        outs: &mut [(u64, &mut [u8])],
+        swap16: bool,
+    ) -> io::Result<()> {
+        let Region { x, y, w, h } = region;
+        let (iw, ih) = self.resolution_size()?;
+        let i = self.ifd_index()?;
+        let parser = self.parser()?;
+
+        let ifd = parser.nth_ifd(i)?;
+        region.validate(iw, ih)?;
+        let bits_per_sample = parser.bits_per_sample(&ifd)?;
+        let is_chunky = parser.planar_configuration(&ifd)? == 1;
+        let rows_per_strip = parser.rows_per_strip(&ifd)?.min(ih);
+        let compression = parser.compression(&ifd)?;
+
+        // Planar samples lie in strips of their own, one plane of strips
+        // per sample, so a planar read takes one output
+        debug_assert!(is_chunky || outs.len() <= 1);
+        let first_strip = match is_chunky {
+            true => 0,
+            false => outs.first().map_or(0, |(c, _)| *c) * ih.div_ceil(rows_per_strip),
+        };
+
+        // Chunky configuration, all samples of a pixel together; planar
+        // configuration, one sample per pixel
+        let bits = |c: u64| -> io::Result<usize> {
+            let bits = bits_per_sample
+                .get(c as usize)
+                .ok_or(Error::other("Invalid c"))?;
+            Ok(*bits as usize / 8)
+        };
+        let bytes_per_pixel = match is_chunky {
+            true => bits_per_sample.iter().map(|a| *a as usize).sum::<usize>() / 8,
+            false => bits(0)?,
+        };
+
+        // (sample offset, bytes per sample, region rows) of each output
+        let mut samples = Vec::with_capacity(outs.len());
+        for (c, buf) in outs.iter_mut() {
+            let bytes_per_sample = bits(*c)?;
+            if buf.len() != (w * h) as usize * bytes_per_sample {
+                return Err(Error::other(format!(
+                    "Buffer holds {} bytes, region needs {}",
+                    buf.len(),
+                    (w * h) as usize * bytes_per_sample
+                )));
+            }
+
+            let offset = if is_chunky {
+                *c as usize * bytes_per_sample
             } else {
-                bytes_per_pixel * iw * rows_per_strip
+                0
             };
+            samples.push((offset, bytes_per_sample, &mut **buf));
+        }
+        if region.is_empty() {
+            return Ok(());
+        }
 
-            self.parser
-                .read_strip(&ifd, strip_idx, &mut buff, expected_bytes)?;
-
-            let rows = buff
-                .chunks_exact(bytes_per_row as usize)
-                .skip(lower_idx)
-                .take(upper_idx - lower_idx)
-                .map(|row| &row[lower_col..upper_col])
-                .flatten()
-                .map(|a| a.to_owned())
-                .collect::<Vec<u8>>();
-
-            let bytes: Vec<u8> = if is_chunky {
-                rows.chunks_exact(bytes_per_sample)
-                    .skip(c as usize)
-                    .step_by(samples_per_pixel)
-                    .flatten()
-                    .map(|a| a.to_owned())
-                    .collect()
-            } else {
-                rows.chunks_exact(bytes_per_sample)
-                    .skip((c * h * w) as usize)
-                    .take((h * w) as usize)
-                    .flatten()
-                    .map(|a| a.to_owned())
-                    .collect()
+        let bytes_per_row = bytes_per_pixel * iw as usize;
+        let lower_col = bytes_per_pixel * x as usize;
+        let cols = lower_col..lower_col + bytes_per_pixel * w as usize;
+        let strips = first_strip + y / rows_per_strip..=first_strip + (y + h - 1) / rows_per_strip;
+
+        // Rows of a strip falling inside the region
+        let rows_of = |strip_idx: u64| {
+            let first_row = (strip_idx - first_strip) * rows_per_strip;
+            let lower_idx = y.max(first_row) - first_row;
+            let upper_idx = (y + h).min(first_row + rows_per_strip) - first_row;
+            lower_idx..upper_idx
+        };
+
+        // A read reaching the bottom of the plane is likely followed by one
+        // of the next series, whose strips are read ahead on another thread
+        if self.options.prefetch && y + h == ih && self.file.is_some() {
+            // Nothing follows the last series
+            if let Some(next) = self.next_series_ifd()? {
+                let budget = self.options.prefetch_budget;
+                let _ = self.parser()?.prefetch(next, budget);
+            }
+        }
+
+        // Rows, strips or runs of strips each in one read, whichever costs
+        // least on the device for the region's shape. Strips read ahead are
+        // taken as they are
+        let profile = self.options.io_profile;
+        let parser = self.parser()?;
+        let plan = match parser.is_prefetched(&ifd) {
+            true => ReadPlan::Strips,
+            false => read_plan::plan_strip_reads(
+                &profile,
+                &*parser.strip_table(&ifd)?,
+                strips.clone(),
+                !matches!(compression, Compression::None),
+                h,
+                bytes_per_row,
+                cols.len(),
+            ),
+        };
+
+        // Compressed strips are read in file order on this thread and
+        // decoded on the rayon pool, each into its own rows of the outputs
+        #[cfg(feature = "rayon")]
+        if strips.end() > strips.start()
+            && !matches!(compression, Compression::None)
+            && self.options.tile_cache.is_none()
+        {
+            use rayon::prelude::*;
+
+            let parser = self.parser()?;
+            let raws = match &plan {
+                ReadPlan::Coalesced(runs) => {
+                    let mut raws = Vec::new();
+                    for run in runs {
+                        raws.extend(parser.read_raw_strips(&ifd, run.clone())?);
+                    }
+                    raws
+                }
+                _ => strips
+                    .map(|strip_idx| parser.read_raw_strip(&ifd, strip_idx))
+                    .collect::<io::Result<Vec<_>>>()?,
             };
 
-            out.extend_from_slice(&bytes);
+            let mut jobs = Vec::new();
+            for raw in raws {
+                let rows = rows_of(raw.index);
+                let n_rows = (rows.end - rows.start) as usize;
+                let dsts = samples
+                    .iter_mut()
+                    .map(|(offset, bytes_per_sample, rest)| {
+                        let row_len = *bytes_per_sample * w as usize;
+                        let (out, tail) = std::mem::take(rest).split_at_mut(n_rows * row_len);
+                        *rest = tail;
+                        (*offset, *bytes_per_sample, out.chunks_exact_mut(row_len))
+                    })
+                    .collect::<Vec<_>>();
+                jobs.push((raw, rows, dsts));
+            }
+
+            return jobs.into_par_iter().try_for_each(|(raw, rows, mut dsts)| {
+                decode_strip_rows(
+                    &compression,
+                    &ifd,
+                    &raw,
+                    bytes_per_row,
+                    rows,
+                    cols.clone(),
+                    |row| copy_row(row, bytes_per_pixel, &mut dsts, swap16),
+                )
+            });
+        }
+
+        // Rows are copied out as they're read or decoded, only the region's
+        // columns of them
+        let mut dsts = samples
+            .into_iter()
+            .map(|(offset, bytes_per_sample, out)| {
+                (
+                    offset,
+                    bytes_per_sample,
+                    out.chunks_exact_mut(bytes_per_sample * w as usize),
+                )
+            })
+            .collect::<Vec<_>>();
+        // Cached strips are decoded whole, for later reads of other regions
+        // of them
+        if let (Some(cache), Some(file)) = (&self.options.tile_cache, &self.file) {
+            let parser = self
+                .parser
+                .as_mut()
+                .ok_or(Error::other("Reader not initialized, call set_id"))?;
+            for strip_idx in strips {
+                let key = TileKey::new(file, ifd.offset(), strip_idx);
+                let strip = match cache.get(&key) {
+                    Some(strip) => strip,
+                    None => {
+                        let first_row = (strip_idx - first_strip) * rows_per_strip;
+                        let n_rows = rows_per_strip.min(ih - first_row);
+                        let len = bytes_per_row * n_rows as usize;
+                        let mut strip = vec![0; len];
+                        parser.read_strip(&ifd, strip_idx, &mut strip, len as u64)?;
+                        let strip = Arc::<[u8]>::from(strip);
+                        cache.insert(key, strip.clone());
+                        strip
+                    }
+                };
+                for r in rows_of(strip_idx) {
+                    let row = &strip[r as usize * bytes_per_row..][..bytes_per_row];
+                    copy_row(&row[cols.clone()], bytes_per_pixel, &mut dsts, swap16)?;
+                }
+            }
+            return Ok(());
+        }
+
+        let parser = self.parser()?;
+        if let ReadPlan::Coalesced(runs) = plan {
+            for run in runs {
+                for raw in parser.read_raw_strips(&ifd, run)? {
+                    decode_strip_rows(
+                        &compression,
+                        &ifd,
+                        &raw,
+                        bytes_per_row,
+                        rows_of(raw.index),
+                        cols.clone(),
+                        |row| copy_row(row, bytes_per_pixel, &mut dsts, swap16),
+                    )?;
+                }
+            }
+            return Ok(());
+        }
+
+        // Uncompressed strips are read a row's columns at a time, or whole
+        // rows at once
+        let read_cols = match plan {
+            ReadPlan::Rows => cols.clone(),
+            _ => 0..bytes_per_row,
+        };
+        for strip_idx in strips {
+            parser.read_strip_rows(
+                &ifd,
+                strip_idx,
+                bytes_per_row,
+                rows_of(strip_idx),
+                read_cols.clone(),
+                |row| {
+                    let row = &row[cols.start - read_cols.start..][..cols.len()];
+                    copy_row(row, bytes_per_pixel, &mut dsts, swap16)
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn byte_order(&mut self) -> io::Result<ByteOrder> {
+        Ok(self.parser()?.byte_order())
+    }
+}
+
+// Copy the samples of a row of bytes_per_pixel byte pixels into the next
+// row of each output, given as (sample offset, bytes per sample, rows).
+// Swapping 16 bit samples here, while the row is in cache, saves another
+// pass over the whole output
+fn copy_row(
+    row: &[u8],
+    bytes_per_pixel: usize,
+    dsts: &mut [(usize, usize, ChunksExactMut<'_, u8>)],
+    swap16: bool,
+) -> io::Result<()> {
+    for (offset, bytes_per_sample, rows) in dsts {
+        let dst = rows.next().unwrap_or_default();
+        layout::copy_sample(row, bytes_per_pixel, *offset, *bytes_per_sample, dst)?;
+        if swap16 && *bytes_per_sample == 2 {
+            pixel::swap16_in_place(dst);
+        }
+    }
+    Ok(())
+}
+
+// Classic ("II*\0", "MM\0*") and BigTIFF ("II+\0", "MM\0+") headers
+pub struct TiffFormat;
+
+impl FormatDetector for TiffFormat {
+    fn format_name(&self) -> &'static str {
+        "TIFF"
+    }
+
+    fn suffixes(&self) -> &'static [&'static str] {
+        &["tif", "tiff", "tf2", "tf8", "btf"]
+    }
+
+    fn is_this_type(&self, header: &[u8], _path: &Path) -> bool {
+        matches!(
+            header.get(..4),
+            Some(b"II*\0" | b"MM\0*" | b"II+\0" | b"MM\0+")
+        )
+    }
+
+    fn new_reader(&self) -> Box<dyn FormatReader> {
+        Box::new(TiffReader::default())
+    }
+}
+
+impl FormatReader for TiffReader {
+    fn set_id(&mut self, id: &Path) -> io::Result<()> {
+        self.close()?;
+        self.parser = Some(TiffParser::new(id.to_string_lossy().into())?);
+        self.file = Some(id.to_path_buf());
+        self.deferred = self.options.fast_init;
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        if let Some(parser) = &mut self.parser {
+            parser.cancel_prefetch();
+        }
+        self.parser = None;
+        self.file = None;
+        self.series = 0;
+        self.resolution = 0;
+        self.deferred = false;
+        self.metadata = None;
+        self.core.clear();
+        self.pyramids = None;
+        Ok(())
+    }
+
+    fn used_files(&self) -> io::Result<Vec<PathBuf>> {
+        Ok(self.file.iter().cloned().collect())
+    }
+
+    fn options(&self) -> ReaderOptions {
+        self.options.clone()
+    }
+
+    fn set_options(&mut self, options: ReaderOptions) {
+        self.options = options;
+        self.metadata = None;
+        self.core.clear();
+        self.pyramids = None;
+    }
+
+    fn series(&self) -> u64 {
+        self.series
+    }
+
+    // Each IFD is presented as a series of its own
+    fn set_series(&mut self, series: u64) -> io::Result<()> {
+        // Every TIFF has a first IFD; later ones need the chain walked
+        if series > 0 && series >= self.series_count()? {
+            return Err(Error::other(format!("Invalid series {series}")));
+        }
+        self.core_of(series)?;
+        self.series = series;
+        self.resolution = 0;
+        Ok(())
+    }
+
+    fn series_count(&mut self) -> io::Result<u64> {
+        Ok(self.pyramids()?.len() as u64)
+    }
+
+    fn resolution(&self) -> u64 {
+        self.resolution
+    }
+
+    fn set_resolution(&mut self, resolution: u64) -> io::Result<()> {
+        if resolution >= self.resolution_count()? {
+            return Err(Error::other(format!("Invalid resolution {resolution}")));
+        }
+        self.resolution = resolution;
+        Ok(())
+    }
+
+    fn resolution_size(&mut self) -> io::Result<(u64, u64)> {
+        let i = self.ifd_index()?;
+        let parser = self.parser()?;
+        let ifd = parser.nth_ifd(i)?;
+        Ok((parser.image_width(&ifd)?, parser.image_length(&ifd)?))
+    }
+
+    fn resolution_count(&mut self) -> io::Result<u64> {
+        Ok(self.series_core()?.resolution_count())
+    }
+
+    fn size_x(&mut self) -> io::Result<u64> {
+        Ok(self.series_core()?.dimensions().size_x())
+    }
+
+    fn size_y(&mut self) -> io::Result<u64> {
+        Ok(self.series_core()?.dimensions().size_y())
+    }
+
+    fn size_z(&mut self) -> io::Result<u64> {
+        Ok(self.series_core()?.dimensions().size_z())
+    }
+
+    fn size_c(&mut self) -> io::Result<u64> {
+        Ok(self.series_core()?.dimensions().size_c())
+    }
+
+    fn size_t(&mut self) -> io::Result<u64> {
+        Ok(self.series_core()?.dimensions().size_t())
+    }
+
+    fn image_count(&mut self) -> io::Result<u64> {
+        Ok(self.series_core()?.core().image_count())
+    }
+
+    fn pixel_type(&mut self) -> io::Result<PixelType> {
+        self.series_core()?
+            .pixel_type(0)
+            .ok_or(Error::other("Error reading pixel type"))
+    }
+
+    fn is_interleaved(&mut self) -> io::Result<bool> {
+        Ok(self.series_core()?.is_interleaved())
+    }
+
+    fn samples_per_pixel(&mut self) -> io::Result<u64> {
+        Ok(self.series_core()?.samples_per_pixel())
+    }
+
+    fn metadata(&mut self) -> io::Result<Metadata> {
+        Ok(Metadata::clone(&*self.metadata_arc()?))
+    }
+
+    fn is_deferred(&self) -> bool {
+        self.deferred
+    }
+
+    fn metadata_arc(&mut self) -> io::Result<Arc<Metadata>> {
+        if let Some(md) = &self.metadata {
+            return Ok(md.clone());
+        }
+
+        let md = Arc::new(self.parse_metadata()?);
+        // Fast init metadata is partial, parse again once pixels are read
+        if !self.deferred {
+            self.metadata = Some(md.clone());
+        }
+
+        Ok(md)
+    }
+
+    fn open_bytes(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u8>> {
+        let region = self.bounded_region(region)?;
+        let mut buf = vec![0; region.area() as usize * self.bytes_per_sample(loc.c)?];
+        self.open_bytes_into(loc, region, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn open_bytes_cow(&mut self, loc: Loc, region: Region) -> io::Result<Cow<'_, [u8]>> {
+        if let Some(range) = self.stored_range(loc, region)? {
+            self.deferred = false;
+            let bytes = self.bytes.as_deref().unwrap_or_default();
+            return bytes
+                .get(range)
+                .map(Cow::Borrowed)
+                .ok_or(Error::other("Region lies past the end of the file"));
+        }
+
+        Ok(Cow::Owned(self.open_bytes(loc, region)?))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, loc, buf), fields(c = loc.c))
+    )]
+    fn open_bytes_into(&mut self, loc: Loc, region: Region, buf: &mut [u8]) -> io::Result<()> {
+        self.deferred = false;
+        let asked = region;
+        let region = self.bounded_region(region)?;
+        // A buffer sized for the region asked takes a clamped read at its
+        // front
+        let bytes_per_sample = self.bytes_per_sample(loc.c)?;
+        let buf = match buf.len() == asked.area() as usize * bytes_per_sample {
+            true => &mut buf[..region.area() as usize * bytes_per_sample],
+            false => buf,
+        };
+        let (iw, ih) = self.resolution_size()?;
+        if let OutOfBounds::Pad(value) = self.options.out_of_bounds
+            && !region.is_empty()
+            && !Region::full(iw, ih).contains(&region)
+        {
+            return self.open_padded(loc, region, value, buf);
+        }
+
+        self.read_samples(region, &mut [(loc.c, buf)], false)
+    }
+
+    fn open_u16(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u16>> {
+        let region = self.bounded_region(region)?;
+        let mut buf = vec![0; region.area() as usize];
+        self.open_u16_into(loc, region, &mut buf)?;
+        Ok(buf)
+    }
+
+    // Samples are swapped into native order row by row as they're copied
+    // out of the strips
+    fn open_u16_into(&mut self, loc: Loc, region: Region, buf: &mut [u16]) -> io::Result<()> {
+        let pixel_type = self
+            .series_core()?
+            .pixel_type(loc.c)
+            .ok_or(Error::other("Error reading pixel type"))?;
+        if pixel_type != PixelType::U16 {
+            return Err(Error::other(format!(
+                "{} samples aren't 16 bit unsigned",
+                pixel_type.ome_name()
+            )));
+        }
+
+        self.deferred = false;
+        let order = self.byte_order()?;
+        let asked = region;
+        let region = self.bounded_region(region)?;
+        let buf = match buf.len() == asked.area() as usize {
+            true => &mut buf[..region.area() as usize],
+            false => buf,
+        };
+        let (iw, ih) = self.resolution_size()?;
+        if let OutOfBounds::Pad(value) = self.options.out_of_bounds
+            && !region.is_empty()
+            && !Region::full(iw, ih).contains(&region)
+        {
+            self.open_padded(loc, region, value, pixel::u16_bytes_mut(buf))?;
+            pixel::u16_to_native(buf, order);
+            return Ok(());
+        }
+
+        let swap16 = !pixel::is_native(order);
+        self.read_samples(region, &mut [(loc.c, pixel::u16_bytes_mut(buf))], swap16)
+    }
+
+    fn plane_offset(&mut self, _loc: Loc) -> io::Result<Option<u64>> {
+        let i = self.ifd_index()?;
+        let parser = self.parser()?;
+        let ifd = parser.nth_ifd(i)?;
+        Ok(parser.strip_table(&ifd)?.offsets.first().copied())
+    }
+
+    // Channels held together in chunky pixels are all copied out of one
+    // pass over the strips
+    fn open_planes_bytes(&mut self, locs: &[Loc]) -> io::Result<Vec<Vec<u8>>> {
+        self.deferred = false;
+        let (w, h) = self.resolution_size()?;
+        let i = self.ifd_index()?;
+        let parser = self.parser()?;
+        let ifd = parser.nth_ifd(i)?;
+        if parser.planar_configuration(&ifd)? != 1 {
+            return locs
+                .iter()
+                .map(|loc| self.open_bytes(*loc, Region::full(w, h)))
+                .collect();
+        }
+
+        let mut planes = Vec::with_capacity(locs.len());
+        for loc in locs {
+            planes.push(vec![0; (w * h) as usize * self.bytes_per_sample(loc.c)?]);
+        }
+        let mut outs = locs
+            .iter()
+            .zip(planes.iter_mut())
+            .map(|(loc, plane)| (loc.c, &mut plane[..]))
+            .collect::<Vec<_>>();
+        self.read_samples(Region::full(w, h), &mut outs, false)?;
+
+        Ok(planes)
+    }
+
+    fn get_lut(&mut self, c: u64) -> io::Result<Option<Lut>> {
+        let i = self.ifd_index()?;
+        let parser = self.parser()?;
+        let ifd = parser.nth_ifd(i)?;
+
+        // Palette color images map their one sample through the ColorMap
+        if c == 0 && parser.photometric_interpretation(&ifd)? == 3 {
+            return Ok(Lut::from_color_map(&parser.color_map(&ifd)?));
         }
 
-        Ok(out)
+        let md = self.metadata()?;
+        Ok(Lut::for_channel(md.series(self.series)?, c))
+    }
+
+    // One strip: the full width and RowsPerStrip rows
+    fn optimal_tile_size(&mut self) -> io::Result<(u64, u64)> {
+        if let Some(size) = self.options.tile_size {
+            return Ok(size);
+        }
+
+        let i = self.ifd_index()?;
+        let parser = self.parser()?;
+        let ifd = parser.nth_ifd(i)?;
+        let length = parser.image_length(&ifd)?;
+
+        Ok((
+            parser.image_width(&ifd)?,
+            parser.rows_per_strip(&ifd)?.min(length),
+        ))
     }
 }
 
@@ -136,10 +996,13 @@ impl FormatReader for TiffReader {
 mod tests {
     use std::{
         fmt::Display,
+        sync::atomic::{AtomicUsize, Ordering},
         time::{SystemTime, UNIX_EPOCH},
     };
 
-    use crate::format_in::PixelSlice;
+    use crate::format_in::test_support::{TagValue, TempDir, TestIfd, tiff};
+    use crate::format_in::{ImageReader, IoProfile, Normalization, PixelSlice, PixelType, ReadAt};
+    use crate::units::LengthUnit;
 
     use super::*;
 
@@ -158,11 +1021,11 @@ mod tests {
         let f_name = "assets/example_valid.tiff".into();
         let mut tr = TiffReader::new(f_name).unwrap();
 
-        let (x, y, z, c, t, s, h, w) = (0, 0, 0, 1, 0, 0, 1979, 1979);
-        let origin = Loc::new(x, y, z, c, t, s);
+        let (x, y, z, c, t, h, w) = (0, 0, 0, 1, 0, 1979, 1979);
+        let loc = Loc::new(z, c, t);
 
         let start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        let pxs = tr.open_pixels(origin, h, w).unwrap();
+        let pxs = tr.open_pixels(loc, Region::new(x, y, w, h)).unwrap();
         let end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
         println!("Duration {:?}", end - start);
 
@@ -177,15 +1040,749 @@ mod tests {
         // assert_eq!(1, 2)
     }
 
+    #[test]
+    fn normalized_pixels() {
+        let options = ReaderOptions::new().with_normalization(Normalization::MinMax);
+        let mut tr = TiffReader::with_options(options);
+        tr.set_id(Path::new("assets/example_valid.tiff")).unwrap();
+
+        let pxs = tr.open_pixels(Loc::new(0, 1, 0), Region::new(0, 0, 64, 64));
+        let PixelSlice::F32(data) = pxs.unwrap() else {
+            panic!("Expected f32 pixels");
+        };
+
+        let min = data.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max = data.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        assert_eq!((data.len(), min, max), (64 * 64, 0.0, 1.0));
+    }
+
+    #[test]
+    fn metadata_is_cached_and_shared() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        assert!(tr.metadata.is_none());
+
+        let md = tr.metadata().unwrap();
+        let clone = tr.try_clone().unwrap();
+        assert!(Arc::ptr_eq(
+            tr.metadata.as_ref().unwrap(),
+            clone.metadata.as_ref().unwrap()
+        ));
+        assert_eq!(tr.metadata().unwrap().series.len(), md.series.len());
+
+        tr.set_metadata_level(MetadataLevel::Minimum);
+        assert!(tr.metadata.is_none());
+        assert!(
+            tr.metadata()
+                .unwrap()
+                .global()
+                .original_metadata()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn core_metadata_of_interleaved_tiff() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let core = tr.core_metadata().unwrap();
+
+        assert_eq!((core.size_x, core.size_y, core.size_c), (1979, 1979, 4));
+        assert_eq!((core.size_z, core.size_t, core.image_count()), (1, 1, 4));
+        assert_eq!(core.pixel_type, PixelType::U16);
+        assert!(core.rgb && core.interleaved);
+        assert_eq!((core.thumb_size_x, core.thumb_size_y), (128, 128));
+        assert_eq!(core.resolution_count, 1);
+    }
+
+    #[test]
+    fn open_plane_matches_open_bytes() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let region = Region::new(10, 20, 5, 3);
+
+        let plane = tr.open_plane(0, 1, 0).unwrap();
+        assert_eq!(plane.len(), 1979 * 1979 * 2);
+        assert_eq!(
+            tr.open_plane_region(0, 1, 0, region).unwrap(),
+            tr.open_bytes(Loc::new(0, 1, 0), region).unwrap()
+        );
+    }
+
+    #[test]
+    fn borrow_uncompressed_rows() {
+        let mut tr = TiffReader::from_bytes(tiff_bytes(&[(8, 8, 0), (4, 4, 0)])).unwrap();
+        let loc = Loc::new(0, 0, 0);
+
+        let rows = Region::new(0, 2, 8, 3);
+        let expected = tr.open_bytes(loc, rows).unwrap();
+        let cow = tr.open_bytes_cow(loc, rows).unwrap();
+        assert!(matches!(cow, Cow::Borrowed(_)));
+        assert_eq!(cow.into_owned(), expected);
+
+        // Part rows have to be gathered
+        let part = Region::new(1, 2, 4, 3);
+        let expected = tr.open_bytes(loc, part).unwrap();
+        let cow = tr.open_bytes_cow(loc, part).unwrap();
+        assert!(matches!(cow, Cow::Owned(_)));
+        assert_eq!(cow.into_owned(), expected);
+
+        tr.set_series(1).unwrap();
+        assert_eq!(
+            tr.open_bytes_cow(loc, Region::full(4, 4)).unwrap().as_ref(),
+            &[2; 16]
+        );
+    }
+
+    #[test]
+    fn out_of_bounds_policies() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let loc = Loc::new(0, 1, 0);
+        let (edge, inside) = (
+            Region::new(1970, 1975, 20, 10),
+            Region::new(1970, 1975, 9, 4),
+        );
+        let expected = tr.open_as::<u16>(loc, inside).unwrap();
+        assert!(tr.open_bytes(loc, edge).is_err());
+
+        tr.set_options(ReaderOptions::new().with_out_of_bounds(OutOfBounds::Clamp));
+        assert_eq!(tr.open_as::<u16>(loc, edge).unwrap(), expected);
+        assert!(tr.open_bytes(loc, Region::new(2000, 0, 5, 5)).is_err());
+        // Buffers sized for the region asked take the clamped read
+        let mut buf = vec![0; 200 * 2];
+        tr.open_bytes_into(loc, edge, &mut buf).unwrap();
+        assert_eq!(buf[..72], tr.open_bytes(loc, inside).unwrap());
+        let mut wide = vec![0; 200];
+        tr.open_u16_into(loc, edge, &mut wide).unwrap();
+        assert_eq!(wide[..36], expected);
+
+        tr.set_options(ReaderOptions::new().with_out_of_bounds(OutOfBounds::Pad(7.0)));
+        let padded = tr.open_as::<u16>(loc, edge).unwrap();
+        assert_eq!(padded.len(), 200);
+        for (i, v) in padded.iter().enumerate() {
+            let (x, y) = (i % 20, i / 20);
+            match x < 9 && y < 4 {
+                true => assert_eq!(*v, expected[y * 9 + x]),
+                false => assert_eq!(*v, 7),
+            }
+        }
+        let outside = tr.open_as::<u16>(loc, Region::new(3000, 0, 2, 2)).unwrap();
+        assert_eq!(outside, vec![7; 4]);
+    }
+
+    #[test]
+    fn planned_reads_match_across_devices() {
+        let (loc, region) = (Loc::new(0, 3, 0), Region::new(1000, 0, 2, 1979));
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let expected = tr.open_bytes(loc, region).unwrap();
+
+        for profile in [IoProfile::HDD, IoProfile::NETWORK] {
+            tr.set_options(ReaderOptions::new().with_io_profile(profile));
+            assert_eq!(tr.open_bytes(loc, region).unwrap(), expected);
+        }
+
+        // Part of an uncompressed row
+        let mut tr =
+            TiffReader::from_source(std::io::Cursor::new(sample_tiff(8, 1, &[3, 4]))).unwrap();
+        let column = tr.open_bytes(Loc::new(0, 0, 0), Region::new(1, 0, 1, 1));
+        assert_eq!(column.unwrap(), vec![4]);
+    }
+
+    // Counts deep copies of the metadata made through metadata()
+    struct CountingReader {
+        reader: TiffReader,
+        copies: usize,
+    }
+
+    impl FormatReader for CountingReader {
+        fn set_id(&mut self, id: &Path) -> io::Result<()> {
+            self.reader.set_id(id)
+        }
+
+        fn close(&mut self) -> io::Result<()> {
+            self.reader.close()
+        }
+
+        fn used_files(&self) -> io::Result<Vec<PathBuf>> {
+            self.reader.used_files()
+        }
+
+        fn series(&self) -> u64 {
+            self.reader.series()
+        }
+
+        fn set_series(&mut self, series: u64) -> io::Result<()> {
+            self.reader.set_series(series)
+        }
+
+        fn metadata(&mut self) -> io::Result<Metadata> {
+            self.copies += 1;
+            self.reader.metadata()
+        }
+
+        fn metadata_arc(&mut self) -> io::Result<Arc<Metadata>> {
+            self.reader.metadata_arc()
+        }
+
+        fn open_bytes(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u8>> {
+            self.reader.open_bytes(loc, region)
+        }
+    }
+
+    #[test]
+    fn reads_share_cached_metadata() {
+        let reader = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let mut counting = CountingReader { reader, copies: 0 };
+        let (loc, region) = (Loc::new(0, 1, 0), Region::new(0, 0, 4, 4));
+
+        counting.open_pixels(loc, region).unwrap();
+        counting.open_as::<f32>(loc, region).unwrap();
+        counting.open_u16(loc, region).unwrap();
+        counting.pixel_type().unwrap();
+        assert_eq!(counting.copies, 0);
+
+        let reader = &mut counting.reader;
+        assert!(Arc::ptr_eq(
+            &reader.metadata_arc().unwrap(),
+            &reader.metadata_arc().unwrap()
+        ));
+    }
+
+    #[test]
+    fn open_u16_in_native_order() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let (loc, region) = (Loc::new(0, 1, 0), Region::new(1900, 1970, 79, 9));
+        let expected = tr.open_as::<u16>(loc, region).unwrap();
+
+        let mut buf = vec![0; 79 * 9];
+        tr.open_u16_into(loc, region, &mut buf).unwrap();
+        assert_eq!(buf, expected);
+        assert_eq!(tr.open_u16(loc, region).unwrap(), expected);
+        assert!(
+            matches!(tr.open_pixels(loc, region).unwrap(), PixelSlice::U16(p) if p == expected)
+        );
+
+        let mut tr =
+            TiffReader::from_source(std::io::Cursor::new(sample_tiff(8, 1, &[1, 2]))).unwrap();
+        assert!(tr.open_u16(Loc::new(0, 0, 0), Region::full(2, 1)).is_err());
+    }
+
+    #[test]
+    fn open_bytes_into_reuses_buffer() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let (loc, region) = (Loc::new(0, 2, 0), Region::new(1900, 1970, 79, 9));
+        let mut buf = vec![0; 79 * 9 * 2];
+
+        tr.open_bytes_into(loc, region, &mut buf).unwrap();
+        assert_eq!(buf, tr.open_bytes(loc, region).unwrap());
+        assert!(tr.open_bytes_into(loc, region, &mut buf[1..]).is_err());
+
+        // A PackBits run of two 5s, through the parser's reused input buffer
+        let packed = TestIfd::new(2, 1, 8, vec![255, 5]).tag(259, TagValue::Short(32773));
+        let mut tr = TiffReader::from_source(std::io::Cursor::new(tiff(vec![packed]))).unwrap();
+        for _ in 0..2 {
+            assert_eq!(
+                tr.open_bytes(Loc::new(0, 0, 0), Region::full(2, 1))
+                    .unwrap(),
+                vec![5, 5]
+            );
+        }
+    }
+
+    #[test]
+    fn open_planes_in_one_pass() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let locs = [Loc::new(0, 3, 0), Loc::new(0, 1, 0), Loc::new(0, 3, 0)];
+        let planes = tr.open_planes(&locs).unwrap();
+
+        assert_eq!(planes.len(), 3);
+        for (loc, plane) in locs.iter().zip(&planes) {
+            assert_eq!(
+                *plane,
+                tr.open_pixels(*loc, Region::full(1979, 1979)).unwrap()
+            );
+        }
+        let PixelSlice::U16(channel) = &planes[1] else {
+            panic!("Expected u16 pixels");
+        };
+        assert_eq!(channel.iter().map(|a| *a as u64).sum::<u64>(), 184163095);
+
+        assert!(tr.open_planes(&[Loc::new(0, 4, 0)]).is_err());
+        assert!(tr.open_planes(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn narrow_column_reads() {
+        let packbits = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let stored = TiffReader::from_bytes(tiff_bytes(&[(6, 5, 0)])).unwrap();
+
+        for (mut tr, (w, h)) in [(packbits, (1979, 1979)), (stored, (6, 5))] {
+            let loc = Loc::new(0, 0, 0);
+            let full = tr.open_bytes(loc, Region::full(w, h)).unwrap();
+            let bytes = full.len() / (w * h) as usize;
+
+            let column = tr.open_bytes(loc, Region::new(w - 2, 1, 1, h - 1)).unwrap();
+            let expected = full
+                .chunks_exact(w as usize * bytes)
+                .skip(1)
+                .flat_map(|row| row[(w as usize - 2) * bytes..(w as usize - 1) * bytes].to_vec())
+                .collect::<Vec<_>>();
+            assert_eq!(column, expected);
+        }
+    }
+
+    #[test]
+    fn multi_strip_reads_match_rows() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let (loc, region) = (Loc::new(0, 3, 0), Region::new(10, 5, 40, 30));
+        let whole = tr.open_bytes(loc, region).unwrap();
+
+        // Each row lies within a single strip
+        let rows = (0..region.h)
+            .flat_map(|r| tr.open_bytes(loc, Region::new(10, 5 + r, 40, 1)).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(whole, rows);
+    }
+
+    #[test]
+    fn sizes_of_one_series_read_lazily() {
+        let ifds = (0..50).map(|i| (4 + i % 3, 2, 0)).collect::<Vec<_>>();
+        let mut tr = TiffReader::from_bytes(tiff_bytes(&ifds)).unwrap();
+
+        tr.set_series(31).unwrap();
+        assert_eq!(tr.size_x().unwrap(), 5);
+        assert_eq!(tr.image_count().unwrap(), 1);
+        assert_eq!(tr.pixel_type().unwrap(), PixelType::U8);
+        assert!(tr.metadata.is_none());
+        assert_eq!(tr.core.len(), 1);
+
+        let md = tr.metadata().unwrap();
+        assert_eq!(md.series_count(), 50);
+        assert_eq!(md.series(31).unwrap().core(), tr.core_metadata().unwrap());
+    }
+
+    #[test]
+    fn prefetch_next_plane() {
+        let dir = TempDir::new();
+        let path = dir.join("planes.tif");
+        std::fs::write(&path, tiff_bytes(&[(4, 3, 0), (4, 3, 0), (4, 3, 0)])).unwrap();
+
+        let mut tr = TiffReader::with_options(ReaderOptions::new().with_prefetch(true));
+        tr.set_id(&path).unwrap();
+        let loc = Loc::new(0, 0, 0);
+        let next = |tr: &mut TiffReader, i| {
+            let parser = tr.parser.as_mut().unwrap();
+            let ifd = parser.nth_ifd(i).unwrap();
+            parser.is_prefetched(&ifd)
+        };
+
+        // Only reads reaching the last row look ahead
+        tr.open_bytes(loc, Region::new(0, 0, 4, 2)).unwrap();
+        assert!(!next(&mut tr, 1));
+        tr.open_bytes(loc, Region::full(4, 3)).unwrap();
+        assert!(next(&mut tr, 1));
+
+        for series in 1..3 {
+            tr.set_series(series).unwrap();
+            let plane = tr.open_bytes(loc, Region::full(4, 3)).unwrap();
+            assert_eq!(plane, vec![series as u8 + 1; 12]);
+        }
+        assert!(!next(&mut tr, 1));
+
+        // Planes storing more than the budget are left to be read when asked
+        let options = ReaderOptions::new()
+            .with_prefetch(true)
+            .with_prefetch_budget(11);
+        let mut tr = TiffReader::with_options(options);
+        tr.set_id(&path).unwrap();
+        tr.open_bytes(loc, Region::full(4, 3)).unwrap();
+        assert!(!next(&mut tr, 1));
+    }
+
+    #[test]
+    fn prefetch_follows_series_not_ifds() {
+        let dir = TempDir::new();
+        let path = dir.join("pyramids.tif");
+        // Two series, each a 4x4 image and its 2x2 reduced resolution
+        let ifds = [(4, 4, 0), (2, 2, 1), (4, 4, 0), (2, 2, 1)];
+        std::fs::write(&path, tiff_bytes(&ifds)).unwrap();
+
+        let options = ReaderOptions::new()
+            .with_prefetch(true)
+            .with_flatten_resolutions(false);
+        let mut tr = TiffReader::with_options(options);
+        tr.set_id(&path).unwrap();
+        tr.open_bytes(Loc::new(0, 0, 0), Region::full(4, 4))
+            .unwrap();
+
+        let parser = tr.parser.as_mut().unwrap();
+        let (reduced, next) = (parser.nth_ifd(1).unwrap(), parser.nth_ifd(2).unwrap());
+        assert!(!parser.is_prefetched(&reduced));
+        assert!(parser.is_prefetched(&next));
+    }
+
+    #[test]
+    fn open_as_widens_samples() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let (loc, region) = (Loc::new(0, 1, 0), Region::new(0, 0, 1979, 1979));
+
+        let wide = tr.open_as::<u32>(loc, region).unwrap();
+        let check_sum = wide.into_iter().map(|a| a as u64).sum::<u64>();
+
+        assert_eq!(check_sum, 184163095);
+        assert!(tr.open_as::<u8>(loc, region).is_err());
+    }
+
+    #[test]
+    fn cloned_readers_read_in_parallel() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let region = Region::new(100, 100, 16, 16);
+        let expected = (0..4)
+            .map(|c| tr.open_bytes(Loc::new(0, c, 0), region).unwrap())
+            .collect::<Vec<_>>();
+
+        let handles = (0..4)
+            .map(|c| {
+                let mut clone = tr.try_clone().unwrap();
+                std::thread::spawn(move || clone.open_bytes(Loc::new(0, c, 0), region).unwrap())
+            })
+            .collect::<Vec<_>>();
+        let read = handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(read, expected);
+
+        tr.reopen().unwrap();
+        assert_eq!(
+            tr.open_bytes(Loc::new(0, 0, 0), region).unwrap(),
+            expected[0]
+        );
+
+        let bytes = std::fs::read("assets/example_valid.tiff").unwrap();
+        let memory = TiffReader::from_source(std::io::Cursor::new(bytes)).unwrap();
+        assert!(memory.try_clone().is_err());
+    }
+
+    #[test]
+    fn read_from_memory() {
+        let bytes = std::fs::read("assets/example_valid.tiff").unwrap();
+        let mut memory = TiffReader::from_source(std::io::Cursor::new(bytes)).unwrap();
+        let mut file = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let (loc, region) = (Loc::new(0, 3, 0), Region::new(50, 60, 8, 8));
+
+        assert!(memory.used_files().unwrap().is_empty());
+        assert_eq!(
+            memory.open_bytes(loc, region).unwrap(),
+            file.open_bytes(loc, region).unwrap()
+        );
+    }
+
+    // One 8 bit IFD per (w, h, NewSubfileType), each filled with its IFD
+    // index + 1. Full resolution pixels are 1 µm wide
+    fn tiff_bytes(ifds: &[(u32, u32, u32)]) -> Vec<u8> {
+        let full = ifds[0].0;
+        let ifds = ifds.iter().enumerate().map(|(i, &(w, h, subfile))| {
+            // 10^4 / (w / full width) pixels per cm
+            let resolution = TagValue::Rational(10000 * w, full);
+            TestIfd::filled(w, h, i as u8 + 1)
+                .tag(254, TagValue::Long(subfile))
+                .tag(282, resolution)
+                .tag(283, resolution)
+                .tag(296, TagValue::Short(3))
+        });
+
+        tiff(ifds.collect())
+    }
+
+    // A 2x1 image of one sample per pixel with the given SampleFormat
+    fn sample_tiff(bits: u16, format: u16, pixels: &[u8]) -> Vec<u8> {
+        let ifd = TestIfd::new(2, 1, bits, pixels.to_vec()).tag(339, TagValue::Short(format));
+        tiff(vec![ifd])
+    }
+
+    #[test]
+    fn signed_and_float_pixels() {
+        let open = |bits, format, pixels: Vec<u8>| {
+            let tiff = sample_tiff(bits, format, &pixels);
+            let mut tr = TiffReader::from_source(std::io::Cursor::new(tiff)).unwrap();
+            tr.open_pixels(Loc::new(0, 0, 0), Region::full(2, 1))
+                .unwrap()
+        };
+
+        let floats = [1.5f32, -2.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        assert!(matches!(open(32, 3, floats), PixelSlice::F32(v) if v == [1.5, -2.0]));
+
+        let doubles = [0.25f64, 1e10]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        assert!(matches!(open(64, 3, doubles), PixelSlice::F64(v) if v == [0.25, 1e10]));
+
+        // Half precision 1.5 and -2
+        let halves = [0x3e00u16, 0xc000]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        assert!(matches!(open(16, 3, halves), PixelSlice::F32(v) if v == [1.5, -2.0]));
+
+        let shorts = [-300i16, 7].iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert!(matches!(open(16, 2, shorts), PixelSlice::I16(v) if v == [-300, 7]));
+
+        let ints = [u32::MAX, 1].iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert!(matches!(open(32, 1, ints), PixelSlice::U32(v) if v == [u32::MAX, 1]));
+
+        let complex = [1.0f32, -1.0, 0.5, 2.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        assert!(
+            matches!(open(64, 6, complex), PixelSlice::C64(v) if v == [[1.0, -1.0], [0.5, 2.0]])
+        );
+    }
+
+    #[test]
+    fn planar_configuration_defaults_to_chunky() {
+        let ifd = TestIfd::new(2, 1, 8, vec![3, 4]).without(284);
+        let mut tr = TiffReader::from_source(std::io::Cursor::new(tiff(vec![ifd]))).unwrap();
+
+        assert!(tr.metadata().is_ok());
+        assert_eq!(
+            tr.open_bytes(Loc::new(0, 0, 0), Region::full(2, 1))
+                .unwrap(),
+            vec![3, 4]
+        );
+    }
+
+    #[test]
+    fn planar_samples_read_from_their_own_strips() {
+        // 2x2 RGB, each sample's plane in a strip of its own
+        let planes = [[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12]];
+        let ifd = TestIfd::new(2, 2, 8, planes.concat())
+            .tag(258, TagValue::Shorts(&[8, 8, 8]))
+            .tag(262, TagValue::Short(2))
+            .tag(277, TagValue::Short(3))
+            .tag(284, TagValue::Short(2))
+            .strips(3);
+        let mut tr = TiffReader::from_bytes(tiff(vec![ifd])).unwrap();
+
+        for (c, plane) in planes.iter().enumerate() {
+            let loc = Loc::new(0, c as u64, 0);
+            assert_eq!(tr.open_bytes(loc, Region::full(2, 2)).unwrap(), plane);
+            assert_eq!(
+                tr.open_bytes(loc, Region::new(1, 1, 1, 1)).unwrap(),
+                [plane[3]]
+            );
+        }
+        let locs = [Loc::new(0, 2, 0), Loc::new(0, 1, 0)];
+        assert_eq!(
+            tr.open_planes(&locs).unwrap(),
+            [
+                PixelSlice::U8(planes[2].to_vec()),
+                PixelSlice::U8(planes[1].to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn flattened_and_hierarchical_resolutions() {
+        let bytes = tiff_bytes(&[(8, 8, 0), (4, 4, 1), (2, 2, 1), (6, 6, 0)]);
+
+        let mut flat = TiffReader::from_source(std::io::Cursor::new(bytes.clone())).unwrap();
+        assert_eq!(flat.series_count().unwrap(), 4);
+        assert_eq!(flat.resolution_count().unwrap(), 1);
+
+        let mut nested = TiffReader::from_source(std::io::Cursor::new(bytes)).unwrap();
+        nested.set_options(ReaderOptions::new().with_flatten_resolutions(false));
+        assert_eq!(nested.series_count().unwrap(), 2);
+        assert_eq!(nested.resolution_count().unwrap(), 3);
+
+        nested.set_resolution(2).unwrap();
+        let level = nested.open_bytes(Loc::new(0, 0, 0), Region::full(2, 2));
+        assert_eq!(level.unwrap(), vec![3; 4]);
+
+        nested.set_series(1).unwrap();
+        assert_eq!(nested.resolution(), 0);
+        assert_eq!(nested.size_x().unwrap(), 6);
+        assert!(nested.set_resolution(1).is_err());
+    }
+
+    #[test]
+    fn scaled_reads_pick_pyramid_level() {
+        let bytes = tiff_bytes(&[(8, 8, 0), (4, 4, 1), (2, 2, 1)]);
+        let mut tr = TiffReader::from_source(std::io::Cursor::new(bytes)).unwrap();
+        tr.set_options(ReaderOptions::new().with_flatten_resolutions(false));
+        let loc = Loc::new(0, 0, 0);
+
+        let half = tr.open_region_scaled(loc, Region::full(8, 8), 0.5);
+        assert_eq!(half.unwrap(), vec![2; 16]);
+        // No level matches exactly, level 1 is decimated to 3x3
+        let decimated = tr.open_region_scaled(loc, Region::full(8, 8), 0.4);
+        assert_eq!(decimated.unwrap(), vec![2; 9]);
+        let quarter = tr.open_region_scaled(loc, Region::new(4, 4, 4, 4), 0.25);
+        assert_eq!(quarter.unwrap(), vec![3]);
+        assert_eq!(tr.resolution(), 0);
+
+        // Single level files are decimated from full resolution
+        let mut flat = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let full = flat.open_bytes(loc, Region::new(0, 0, 4, 4)).unwrap();
+        let scaled = flat
+            .open_region_scaled(loc, Region::new(0, 0, 4, 4), 0.5)
+            .unwrap();
+        let pixel = |x: usize, y: usize| &full[(y * 4 + x) * 2..(y * 4 + x) * 2 + 2];
+        assert_eq!(
+            scaled,
+            [pixel(1, 1), pixel(3, 1), pixel(1, 3), pixel(3, 3)].concat()
+        );
+    }
+
+    #[test]
+    fn pyramid_layout_walked_once() {
+        let bytes = tiff_bytes(&[(8, 8, 0), (4, 4, 1), (8, 8, 0), (4, 4, 1)]);
+        let mut tr = TiffReader::from_source(std::io::Cursor::new(bytes)).unwrap();
+        tr.set_options(ReaderOptions::new().with_flatten_resolutions(false));
+
+        tr.set_series(1).unwrap();
+        tr.set_resolution(1).unwrap();
+        let pyramids = tr.pyramids.clone().unwrap();
+        assert_eq!(*pyramids, vec![vec![0, 1], vec![2, 3]]);
+
+        let tile = tr
+            .open_bytes(Loc::new(0, 0, 0), Region::full(4, 4))
+            .unwrap();
+        assert_eq!(tile, vec![4; 16]);
+        assert!(Arc::ptr_eq(tr.pyramids.as_ref().unwrap(), &pyramids));
+
+        // Flattening makes every IFD a series of its own
+        tr.set_options(ReaderOptions::new());
+        assert!(tr.pyramids.is_none());
+        assert_eq!(tr.series_count().unwrap(), 4);
+    }
+
+    #[test]
+    fn select_resolution_by_pixel_size() {
+        let bytes = tiff_bytes(&[(8, 8, 0), (4, 4, 1), (2, 2, 1)]);
+        let mut tr = TiffReader::from_source(std::io::Cursor::new(bytes)).unwrap();
+        tr.set_options(ReaderOptions::new().with_flatten_resolutions(false));
+
+        let md = tr.metadata().unwrap();
+        let size = md.series(0).unwrap().physical_size_x().unwrap();
+        assert!((size.value_in(LengthUnit::Micrometer).unwrap() - 1.0).abs() < 1e-9);
+
+        assert!((tr.select_resolution(2.5).unwrap() - 2.0).abs() < 1e-9);
+        assert_eq!(tr.resolution(), 1);
+        assert!((tr.select_resolution(0.5).unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(tr.resolution(), 0);
+    }
+
+    #[test]
+    fn fast_init_defers_full_metadata() {
+        let mut tr = TiffReader::with_options(ReaderOptions::new().with_fast_init(true));
+        tr.set_id(Path::new("assets/example_valid.tiff")).unwrap();
+
+        let fast = tr.metadata().unwrap();
+        assert_eq!(fast.series(0).unwrap().core().size_x, 1979);
+        assert_eq!(tr.pixel_type().unwrap(), PixelType::U16);
+        assert!(fast.global().original_metadata().is_empty());
+
+        tr.open_bytes(Loc::new(0, 0, 0), Region::new(0, 0, 1, 1))
+            .unwrap();
+        assert!(
+            !tr.metadata()
+                .unwrap()
+                .global()
+                .original_metadata()
+                .is_empty()
+        );
+    }
+
+    // Bytes read through a count of the reads made
+    struct Counted(Vec<u8>, Arc<AtomicUsize>);
+
+    impl ReadAt for Counted {
+        fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+            self.1.fetch_add(1, Ordering::Relaxed);
+            self.0.read_at(buf, offset)
+        }
+
+        fn length(&self) -> io::Result<u64> {
+            self.0.length()
+        }
+    }
+
+    #[test]
+    fn fast_init_reads_only_the_first_ifd() {
+        // Reads made by a deferred open, metadata() and the first series'
+        // sizes of a file of n series
+        let reads = |n: u32| {
+            let ifds = (0..n).map(|i| (4 + i % 3, 2, 0)).collect::<Vec<_>>();
+            let count = Arc::new(AtomicUsize::new(0));
+            let counted = Counted(tiff_bytes(&ifds), count.clone());
+            let mut tr = TiffReader {
+                parser: Some(TiffParser::from_read_at(counted).unwrap()),
+                options: ReaderOptions::new()
+                    .with_fast_init(true)
+                    .with_flatten_resolutions(false),
+                deferred: true,
+                ..Default::default()
+            };
+
+            let md = tr.metadata().unwrap();
+            assert_eq!(md.series_count(), 1);
+            assert_eq!(tr.size_x().unwrap(), 4);
+            tr.set_series(0).unwrap();
+            assert!(tr.pyramids.is_none());
+            (tr, count.load(Ordering::Relaxed))
+        };
+
+        let (mut tr, few) = reads(3);
+        assert_eq!(reads(50).1, few);
+
+        // Another series walks the chain, still without parsing any tags
+        tr.set_series(2).unwrap();
+        assert_eq!(tr.size_x().unwrap(), 6);
+        assert!(tr.deferred);
+    }
+
+    #[test]
+    fn minimum_metadata_level() {
+        let mut tr = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let all = tr.metadata().unwrap();
+
+        tr.set_metadata_level(MetadataLevel::Minimum);
+        let min = tr.metadata().unwrap();
+
+        let options = ReaderOptions::new().with_metadata_level(MetadataLevel::Minimum);
+        let mut opened = ImageReader::with_options(options);
+        opened
+            .set_id(Path::new("assets/example_valid.tiff"))
+            .unwrap();
+        assert!(
+            opened
+                .metadata()
+                .unwrap()
+                .global
+                .original_metadata()
+                .is_empty()
+        );
+
+        assert!(!all.global.original_metadata().is_empty());
+        assert!(min.global.original_metadata().is_empty());
+        assert_eq!(
+            min.series(0).unwrap().core().size_x,
+            all.series(0).unwrap().core().size_x
+        );
+    }
+
     #[test]
     fn open_pixels_big_tiff() {
         let f_name = "/Users/albert/Downloads/example_ws/ws_converted/24_3_21_7.1_conv.tiff".into();
         let mut tr = TiffReader::new(f_name).unwrap();
 
-        let (x, y, z, c, t, s, h, w) = (0, 0, 0, 0, 0, 0, 1000, 1000);
-        let origin = Loc::new(x, y, z, c, t, s);
+        let (x, y, z, c, t, h, w) = (0, 0, 0, 0, 0, 1000, 1000);
+        let loc = Loc::new(z, c, t);
 
-        let pxs = tr.open_pixels(origin, h, w).unwrap();
+        let pxs = tr.open_pixels(loc, Region::new(x, y, w, h)).unwrap();
 
         let data = match pxs {
             PixelSlice::U16(v) => v,
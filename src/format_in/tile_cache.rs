@@ -0,0 +1,164 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+// A decoded tile or strip: the file, the offset of its IFD and its index
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct TileKey {
+    pub(crate) file: PathBuf,
+    pub(crate) ifd: u64,
+    pub(crate) tile: u64,
+}
+
+impl TileKey {
+    pub(crate) fn new(file: &Path, ifd: u64, tile: u64) -> Self {
+        TileKey {
+            file: file.to_path_buf(),
+            ifd,
+            tile,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Tiles {
+    tiles: HashMap<TileKey, Arc<[u8]>>,
+    // Least recently used first
+    order: VecDeque<TileKey>,
+    bytes: usize,
+}
+
+// Decoded tiles and strips kept in memory up to a budget of bytes, evicting
+// the least recently used beyond it, so panning and zooming viewers don't
+// decode the same compressed data again. Clones share the cache, so one
+// budget may serve several readers
+#[derive(Clone)]
+pub struct TileCache {
+    budget: usize,
+    inner: Arc<Mutex<Tiles>>,
+}
+
+impl TileCache {
+    pub fn new(budget_bytes: usize) -> Self {
+        TileCache {
+            budget: budget_bytes,
+            inner: Arc::default(),
+        }
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    // Tiles currently cached
+    pub fn len(&self) -> usize {
+        self.inner.lock().map_or(0, |t| t.tiles.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Decoded bytes currently cached
+    pub fn bytes(&self) -> usize {
+        self.inner.lock().map_or(0, |t| t.bytes)
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut t) = self.inner.lock() {
+            *t = Tiles::default();
+        }
+    }
+
+    pub(crate) fn get(&self, key: &TileKey) -> Option<Arc<[u8]>> {
+        let mut t = self.inner.lock().ok()?;
+        let tile = t.tiles.get(key).cloned()?;
+        if let Some(i) = t.order.iter().position(|k| k == key) {
+            let k = t.order.remove(i).unwrap();
+            t.order.push_back(k);
+        }
+        Some(tile)
+    }
+
+    // Tiles larger than the whole budget aren't kept
+    pub(crate) fn insert(&self, key: TileKey, tile: Arc<[u8]>) {
+        let Ok(mut t) = self.inner.lock() else {
+            return;
+        };
+        if tile.len() > self.budget || t.tiles.contains_key(&key) {
+            return;
+        }
+        while t.bytes + tile.len() > self.budget {
+            let Some(oldest) = t.order.pop_front() else {
+                break;
+            };
+            if let Some(evicted) = t.tiles.remove(&oldest) {
+                t.bytes -= evicted.len();
+            }
+        }
+        t.bytes += tile.len();
+        t.order.push_back(key.clone());
+        t.tiles.insert(key, tile);
+    }
+}
+
+impl fmt::Debug for TileCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TileCache")
+            .field("budget", &self.budget)
+            .field("len", &self.len())
+            .field("bytes", &self.bytes())
+            .finish()
+    }
+}
+
+// Equal when sharing tiles, i.e. clones of one another
+impl PartialEq for TileCache {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+    use crate::format_in::{FormatReader, Loc, ReaderOptions, Region};
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = TileCache::new(10);
+        let key = |i| TileKey::new(Path::new("a.tif"), 8, i);
+        cache.insert(key(0), vec![0; 4].into());
+        cache.insert(key(1), vec![1; 4].into());
+        assert!(cache.get(&key(0)).is_some());
+
+        cache.insert(key(2), vec![2; 4].into());
+        assert!(cache.get(&key(1)).is_none());
+        assert_eq!(cache.bytes(), 8);
+
+        cache.insert(key(3), vec![3; 11].into());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn repeat_reads_hit_the_cache() {
+        let cache = TileCache::new(4 << 20);
+        let mut reader = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let region = Region::new(100, 100, 64, 64);
+        let uncached = reader.open_bytes(Loc::new(0, 1, 0), region).unwrap();
+
+        reader.set_options(ReaderOptions::new().with_tile_cache(cache.clone()));
+        let first = reader.open_bytes(Loc::new(0, 1, 0), region).unwrap();
+        let strips = cache.len();
+        assert_eq!(strips, 9);
+
+        let second = reader.open_bytes(Loc::new(0, 2, 0), region).unwrap();
+        assert_eq!(cache.len(), strips);
+        assert_eq!(first, uncached);
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,156 @@
+use std::io;
+
+use crate::format_in::{DimensionOrder, FormatReader, Loc, PixelSlice, Progress, Region};
+
+// Iterates every plane of a series tile by tile, so planes too large for
+// memory can be processed piecewise. Yields the plane index, the region
+// read and its pixels; tiles within a plane run left to right, top to bottom
+pub struct Tiles<'a, R: FormatReader + ?Sized> {
+    reader: &'a mut R,
+    order: DimensionOrder,
+    sizes: (u64, u64, u64),
+    plane_size: (u64, u64),
+    tile_size: (u64, u64),
+    plane: u64,
+    x: u64,
+    y: u64,
+    progress: Progress,
+    done: u64,
+    total: u64,
+}
+
+impl<'a, R: FormatReader + ?Sized> Tiles<'a, R> {
+    pub(crate) fn new(
+        reader: &'a mut R,
+        series: u64,
+        tile_w: u64,
+        tile_h: u64,
+    ) -> io::Result<Self> {
+        reader.set_series(series)?;
+        let (native_w, native_h) = reader.optimal_tile_size()?;
+
+        let md = reader.metadata()?;
+        let s = md.series(series)?;
+        let dim = s.dimensions();
+        let (size_x, size_y) = (dim.size_x(), dim.size_y());
+        let (size_z, size_c, size_t) = dim.zct();
+
+        if tile_w == 0 || tile_h == 0 {
+            return Err(io::Error::other("Tile size must be non-zero"));
+        }
+
+        let tile_size = (
+            align(tile_w, native_w, size_x),
+            align(tile_h, native_h, size_y),
+        );
+        let per_plane = size_x.div_ceil(tile_size.0.max(1)) * size_y.div_ceil(tile_size.1.max(1));
+
+        Ok(Tiles {
+            order: s.dimension_order(),
+            sizes: dim.zct(),
+            plane_size: (size_x, size_y),
+            tile_size,
+            progress: reader.options().progress,
+            reader,
+            plane: 0,
+            x: 0,
+            y: 0,
+            done: 0,
+            total: per_plane * size_z * size_c * size_t,
+        })
+    }
+
+    // Tile size actually used, after alignment to the native layout
+    pub fn tile_size(&self) -> (u64, u64) {
+        self.tile_size
+    }
+}
+
+// Round a requested tile length down to a whole number of native tiles
+// where it spans at least one, so no native tile is decoded twice
+fn align(requested: u64, native: u64, size: u64) -> u64 {
+    let len = if native > 0 && requested >= native {
+        requested / native * native
+    } else {
+        requested
+    };
+
+    len.min(size)
+}
+
+impl<R: FormatReader + ?Sized> Iterator for Tiles<'_, R> {
+    type Item = io::Result<(u64, Region, PixelSlice)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (size_z, size_c, size_t) = self.sizes;
+        let (size_x, size_y) = self.plane_size;
+        let (tile_w, tile_h) = self.tile_size;
+
+        if self.plane >= size_z * size_c * size_t || size_x == 0 || size_y == 0 {
+            return None;
+        }
+
+        if let Err(e) = self.progress.step(self.done, self.total) {
+            // Nothing further once cancelled
+            self.plane = size_z * size_c * size_t;
+            return Some(Err(e));
+        }
+        self.done += 1;
+
+        let plane = self.plane;
+        let region = Region::new(
+            self.x,
+            self.y,
+            tile_w.min(size_x - self.x),
+            tile_h.min(size_y - self.y),
+        );
+
+        self.x += tile_w;
+        if self.x >= size_x {
+            self.x = 0;
+            self.y += tile_h;
+        }
+        if self.y >= size_y {
+            self.y = 0;
+            self.plane += 1;
+        }
+
+        let pixels = self
+            .order
+            .index_to_zct(self.sizes, plane)
+            .and_then(|(z, c, t)| self.reader.open_pixels(Loc::new(z, c, t), region));
+
+        Some(pixels.map(|px| (plane, region, px)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[test]
+    fn tiles_cover_every_plane() {
+        let mut reader = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+        let planes = reader.image_count().unwrap();
+
+        let tiles = reader
+            .tiles(0, 1000, 1000)
+            .unwrap()
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        let area = tiles.iter().map(|(_, r, _)| r.area()).sum::<u64>();
+
+        assert_eq!(area, 1979 * 1979 * planes);
+        assert_eq!(tiles.last().unwrap().0, planes - 1);
+
+        let (_, region, first) = tiles.into_iter().next().unwrap();
+        match (
+            first,
+            reader.open_pixels(Loc::new(0, 0, 0), region).unwrap(),
+        ) {
+            (PixelSlice::U16(a), PixelSlice::U16(b)) => assert_eq!(a, b),
+            _ => panic!("Expected 16 bit pixels"),
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use std::{collections::BTreeMap, io};
+
+use crate::format_in::Metadata;
+
+pub mod ome_xml;
+pub mod tiff;
+
+pub use ome_xml::OmeXmlTranslator;
+pub use tiff::TiffTranslator;
+
+// Raw key/value metadata exactly as found in the file, before any
+// interpretation (tag names, vendor header keys, ...)
+pub type OriginalMetadata = BTreeMap<String, String>;
+
+// Populates the metadata model from the original metadata collected by a
+// reader. Translators only fill in what they recognise, so several may be
+// chained, later ones taking precedence.
+pub trait MetadataTranslator {
+    fn translate(&self, md: &mut Metadata) -> io::Result<()>;
+}
+
+pub fn translate(translators: &[&dyn MetadataTranslator], md: &mut Metadata) -> io::Result<()> {
+    for translator in translators {
+        translator.translate(md)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use std::io;
+
+use crate::format_in::{
+    ByteOrder, Dim, GlobalMetadata, Metadata, PixelType, SeriesMetadata,
+    translate::MetadataTranslator,
+};
+use crate::ome::{self, Element};
+use crate::units::{Length, LengthUnit};
+
+// An OME-XML document stored under `key` in the global original metadata,
+// e.g. the ImageDescription of the first IFD of an OME-TIFF
+#[derive(Debug)]
+pub struct OmeXmlTranslator {
+    pub key: &'static str,
+}
+
+impl Default for OmeXmlTranslator {
+    fn default() -> Self {
+        OmeXmlTranslator {
+            key: "ImageDescription",
+        }
+    }
+}
+
+impl OmeXmlTranslator {
+    pub fn apply(xml: &Element, md: &mut Metadata) {
+        let mut planes = ome::planes_from_xml(xml);
+        let mut dimension_order = ome::dimension_orders_from_xml(xml);
+        let mut modulo = ome::modulos_from_xml(xml);
+        let mut acquisition_date = ome::acquisition_dates_from_xml(xml);
+        let mut channels = ome::channels_from_xml(xml);
+        let mut tiff_data = ome::tiff_data_from_xml(xml);
+
+        // Only what the document gives replaces what earlier translators set
+        for (s, image) in md.series.iter_mut().zip(xml.children_named("Image")) {
+            if let Some(id) = image.attr("ID") {
+                s.image_id = Some(id.into());
+            }
+            if let Some(name) = image.attr("Name") {
+                s.name = Some(name.into());
+            }
+            if let Some(id) = image.child("ExperimenterRef").and_then(|r| r.attr("ID")) {
+                s.experimenter = Some(id.into());
+            }
+            if let Some(label) = image.child("StageLabel") {
+                s.stage_label = Some(ome::StageLabel::from_xml(label));
+            }
+            if let Some(px) = image.child("Pixels") {
+                if let Some(bits) = px.attr_as("SignificantBits") {
+                    s.significant_bits = Some(bits);
+                }
+                let size = |key| px.attr_quantity(key, LengthUnit::Micrometer, Length::new);
+                s.physical_size_x = size("PhysicalSizeX").or(s.physical_size_x);
+                s.physical_size_y = size("PhysicalSizeY").or(s.physical_size_y);
+            }
+        }
+
+        for (i, s) in md.series.iter_mut().enumerate() {
+            let i = i as u64;
+            if let Some(planes) = planes.remove(&i).filter(|p| !p.is_empty()) {
+                s.planes = planes;
+            }
+            if let Some(modulo) = modulo.remove(&i).filter(|m| !m.is_empty()) {
+                s.modulo = modulo;
+            }
+            if let Some(order) = dimension_order.remove(&i) {
+                s.dimension_order = order;
+            }
+            if let Some(tiff_data) = tiff_data.remove(&i).filter(|t| !t.is_empty()) {
+                s.tiff_data = tiff_data;
+            }
+            if let Some(date) = acquisition_date.remove(&i) {
+                s.acquisition_date = Some(date);
+            }
+            if let Some(chs) = channels.remove(&i).filter(|c| !c.is_empty()) {
+                s.channels = chs;
+            }
+        }
+
+        md.global.rois = ome::rois_from_xml(xml);
+        md.global.plates = ome::plates_from_xml(xml);
+        md.global.annotations = ome::annotations_from_xml(xml);
+        md.global.instruments = ome::instruments_from_xml(xml);
+        md.global.experimenters = ome::experimenters_from_xml(xml);
+        md.global.projects = ome::projects_from_xml(xml);
+        md.global.datasets = ome::datasets_from_xml(xml);
+    }
+}
+
+// Core metadata described entirely by an OME-XML document, for files (such
+// as companion files) which have no pixel headers of their own
+pub fn metadata_from_ome(root: &Element) -> io::Result<Metadata> {
+    let mut series = Vec::new();
+    let mut byte_order = ByteOrder::BE;
+
+    for image in root.children_named("Image") {
+        let px = image
+            .child("Pixels")
+            .ok_or(io::Error::other("Image without Pixels"))?;
+        let size = |key: &str| px.attr_as::<u64>(key).unwrap_or(1);
+        let pixel_type = px
+            .attr("Type")
+            .and_then(PixelType::from_ome)
+            .ok_or(io::Error::other("Invalid Pixels Type"))?;
+
+        if px.attr("BigEndian") == Some("false") {
+            byte_order = ByteOrder::LE;
+        }
+
+        let c = size("SizeC");
+        let dim = Dim::new(
+            size("SizeX"),
+            size("SizeY"),
+            size("SizeZ"),
+            c,
+            size("SizeT"),
+        );
+        let interleaved = px.attr("Interleaved") == Some("true");
+
+        series.push(SeriesMetadata::new(
+            dim,
+            vec![pixel_type; c as usize],
+            interleaved,
+        ));
+    }
+
+    let mut md = Metadata {
+        global: GlobalMetadata::new(byte_order),
+        series,
+    };
+    OmeXmlTranslator::apply(root, &mut md);
+
+    Ok(md)
+}
+
+impl MetadataTranslator for OmeXmlTranslator {
+    fn translate(&self, md: &mut Metadata) -> io::Result<()> {
+        let xml = match md.global.original_metadata.get(self.key) {
+            Some(desc) if desc.contains("<OME") => Element::parse(desc).ok(),
+            _ => None,
+        };
+
+        if let Some(xml) = xml {
+            Self::apply(&xml, md);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::{
+        ByteOrder, DimensionOrder, GlobalMetadata, SeriesMetadata, metadata::Dim,
+    };
+    use crate::units::{Time, TimeUnit};
+
+    #[test]
+    fn translate_ome_xml_description() {
+        let mut md = Metadata {
+            global: GlobalMetadata::new(ByteOrder::LE),
+            series: vec![SeriesMetadata::new(
+                Dim::from_whc(4, 4, 1),
+                vec![PixelType::U8],
+                true,
+            )],
+        };
+
+        md.global.original_metadata.insert(
+            "ImageDescription".into(),
+            r#"<OME><Image ID="Image:0"><AcquisitionDate>2020-01-02T03:04:05</AcquisitionDate>
+                <Pixels DimensionOrder="XYZTC" SignificantBits="4"><Plane TheZ="0" ExposureTime="0.1" PositionX="1" PositionZ="3"/></Pixels>
+            <StageLabel Name="A1" X="10" Y="20"/></Image><ROI ID="ROI:0"/></OME>"#
+                .into(),
+        );
+
+        OmeXmlTranslator::default().translate(&mut md).unwrap();
+
+        let series = md.series(0).unwrap();
+        assert_eq!(series.dimension_order(), DimensionOrder::XYZTC);
+        assert_eq!(
+            series.planes()[0].exposure_time,
+            Some(Time::new(0.1, TimeUnit::Second))
+        );
+        assert!(series.acquisition_date().is_some());
+        assert_eq!(series.significant_bits(0), Some(4));
+        assert_eq!(series.stage_label().unwrap().name, "A1");
+        // The label has no Z, so the plane's fills it in
+        let at = |v| Some(Length::new(v, LengthUnit::ReferenceFrame));
+        assert_eq!(series.position(), (at(10.0), at(20.0), at(3.0)));
+        assert_eq!(series.display_window(0), (0.0, 15.0));
+        assert_eq!(md.global().rois().len(), 1);
+    }
+
+    #[test]
+    fn keep_what_the_xml_leaves_out() {
+        let mut series = SeriesMetadata::new(Dim::from_whc(4, 4, 1), vec![PixelType::U16], true);
+        series.dimension_order = DimensionOrder::XYCTZ;
+        series.significant_bits = Some(12);
+        series.name = Some("from tiff".into());
+        let mut md = Metadata {
+            global: GlobalMetadata::new(ByteOrder::LE),
+            series: vec![series],
+        };
+
+        OmeXmlTranslator::apply(
+            &Element::parse(r#"<OME><Image ID="Image:0"><Pixels/></Image></OME>"#).unwrap(),
+            &mut md,
+        );
+
+        let series = md.series(0).unwrap();
+        assert_eq!(series.dimension_order(), DimensionOrder::XYCTZ);
+        assert_eq!(series.significant_bits(0), Some(12));
+        assert_eq!(series.name.as_deref(), Some("from tiff"));
+        assert_eq!(series.image_id.as_deref(), Some("Image:0"));
+    }
+}
@@ -0,0 +1,93 @@
+use std::io;
+
+use crate::format_in::{Metadata, translate::MetadataTranslator};
+use crate::ome::parse_timestamp;
+use crate::units::{Length, LengthUnit};
+
+// Baseline TIFF tags, keyed by tag name, of each IFD
+#[derive(Debug, Default)]
+pub struct TiffTranslator;
+
+impl MetadataTranslator for TiffTranslator {
+    fn translate(&self, md: &mut Metadata) -> io::Result<()> {
+        for series in md.series.iter_mut() {
+            // Display windows from the sample value range tags, one value
+            // per sample or a single value for all
+            let range = |keys: [&str; 2]| {
+                keys.into_iter()
+                    .find_map(|k| series.original_metadata.get(k))
+                    .map(|v| {
+                        v.split_whitespace()
+                            .filter_map(|a| a.parse::<f64>().ok())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default()
+            };
+
+            let min = range(["SMinSampleValue", "MinSampleValue"]);
+            let max = range(["SMaxSampleValue", "MaxSampleValue"]);
+
+            for (c, channel) in series.channels.iter_mut().enumerate() {
+                let lo = min.get(c).or(min.first());
+                let hi = max.get(c).or(max.first());
+                if let (Some(lo), Some(hi)) = (lo, hi) {
+                    channel.window = Some((*lo, *hi));
+                }
+            }
+
+            // XResolution and YResolution are pixels per ResolutionUnit,
+            // 1 meaning no absolute unit, 2 (the default) inch, 3 cm
+            let unit = match series.original_metadata.get("ResolutionUnit") {
+                Some(u) if u == "1" => None,
+                Some(u) if u == "3" => Some(LengthUnit::Centimeter),
+                _ => Some(LengthUnit::Inch),
+            };
+            let size = |key: &str| {
+                let (n, d) = series.original_metadata.get(key)?.split_once('/')?;
+                let (n, d) = (n.parse::<f64>().ok()?, d.parse::<f64>().ok()?);
+                let unit = unit?;
+                (n > 0.0).then(|| Length::new(d / n, unit))
+            };
+            series.physical_size_x = size("XResolution");
+            series.physical_size_y = size("YResolution");
+
+            if let Some(date) = series
+                .original_metadata
+                .get("DateTime")
+                .and_then(|d| parse_timestamp(d))
+            {
+                series.acquisition_date = Some(date);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::{ByteOrder, Dim, GlobalMetadata, PixelType, SeriesMetadata};
+    use crate::ome::Color;
+
+    #[test]
+    fn display_window_from_sample_range() {
+        let mut series = SeriesMetadata::new(Dim::from_whc(2, 2, 3), vec![PixelType::U16; 3], true);
+        series
+            .original_metadata
+            .insert("MaxSampleValue".into(), "4095".into());
+        series
+            .original_metadata
+            .insert("MinSampleValue".into(), "10 20 30".into());
+
+        let mut md = Metadata {
+            global: GlobalMetadata::new(ByteOrder::LE),
+            series: vec![series],
+        };
+        TiffTranslator.translate(&mut md).unwrap();
+
+        let series = md.series(0).unwrap();
+        assert_eq!(series.display_window(1), (20.0, 4095.0));
+        assert_eq!(series.display_color(2), Color::BLUE);
+    }
+}
@@ -0,0 +1,248 @@
+use std::{
+    borrow::Cow,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::format_in::{
+    Dim, FormatReader, Loc, Lut, Metadata, ReaderOptions, Region, SeriesMetadata,
+};
+use crate::ome::Channel;
+
+// Presents interleaved (e.g. RGB) data as separate single-sample channels,
+// so every (z, c, t) plane holds exactly one sample per pixel. Each source
+// plane of n samples becomes n planes along C, output channel c reading
+// sample c % n of source plane c / n.
+pub struct ChannelSeparator<R: FormatReader> {
+    reader: R,
+}
+
+impl<R: FormatReader> ChannelSeparator<R> {
+    pub fn new(reader: R) -> Self {
+        ChannelSeparator { reader }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // Source plane and sample output channel c is read from
+    fn to_source(&mut self, c: u64) -> io::Result<(u64, u64, u64)> {
+        let md = self.reader.metadata_arc()?;
+        let series = md.series(self.reader.series())?;
+        let (planes, n) = source_planes(series);
+
+        if c >= planes * n {
+            return Err(io::Error::other(format!("Invalid c {c}")));
+        }
+        Ok((c / n, c % n, n))
+    }
+
+    // The wrapped reader addresses sample s of source plane p of n samples
+    // as channel p * n + s
+    fn to_inner(&mut self, c: u64) -> io::Result<u64> {
+        let (plane, sample, n) = self.to_source(c)?;
+        Ok(plane * n + sample)
+    }
+
+    fn inner_loc(&mut self, loc: Loc) -> io::Result<Loc> {
+        Ok(Loc {
+            c: self.to_inner(loc.c)?,
+            ..loc
+        })
+    }
+}
+
+// Number of source planes along C and the samples each holds. Channels
+// saying how many samples they have are each a source plane; otherwise
+// the samples of an interleaved series share one
+fn source_planes(series: &SeriesMetadata) -> (u64, u64) {
+    let n = series.samples_per_pixel();
+    let size_c = series.dimensions.size_c();
+
+    if series
+        .channels
+        .iter()
+        .any(|ch| ch.samples_per_pixel.is_some())
+    {
+        (series.channels.len() as u64, n)
+    } else {
+        (size_c.div_ceil(n), n)
+    }
+}
+
+impl<R: FormatReader> FormatReader for ChannelSeparator<R> {
+    fn set_id(&mut self, id: &Path) -> io::Result<()> {
+        self.reader.set_id(id)
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.reader.close()
+    }
+
+    fn used_files(&self) -> io::Result<Vec<PathBuf>> {
+        self.reader.used_files()
+    }
+
+    fn options(&self) -> ReaderOptions {
+        self.reader.options()
+    }
+
+    fn set_options(&mut self, options: ReaderOptions) {
+        self.reader.set_options(options)
+    }
+
+    fn resolution(&self) -> u64 {
+        self.reader.resolution()
+    }
+
+    fn set_resolution(&mut self, resolution: u64) -> io::Result<()> {
+        self.reader.set_resolution(resolution)
+    }
+
+    fn resolution_size(&mut self) -> io::Result<(u64, u64)> {
+        self.reader.resolution_size()
+    }
+
+    fn series(&self) -> u64 {
+        self.reader.series()
+    }
+
+    fn set_series(&mut self, series: u64) -> io::Result<()> {
+        self.reader.set_series(series)
+    }
+
+    fn is_deferred(&self) -> bool {
+        self.reader.is_deferred()
+    }
+
+    fn metadata(&mut self) -> io::Result<Metadata> {
+        let mut md = self.reader.metadata()?;
+
+        for series in md.series.iter_mut() {
+            let (planes, n) = source_planes(series);
+            let d = &series.dimensions;
+            series.dimensions =
+                Dim::new(d.size_x(), d.size_y(), d.size_z(), planes * n, d.size_t());
+            if let Some(&last) = series.pixel_types.last() {
+                series.pixel_types.resize((planes * n) as usize, last);
+            }
+            series.interleaved = false;
+
+            // A channel of n samples becomes n channels of one sample
+            series.channels = series
+                .channels
+                .drain(..)
+                .flat_map(|ch| {
+                    let n = ch.samples_per_pixel.unwrap_or(1).max(1);
+                    (0..n).map(move |s| Channel {
+                        id: ch.id.clone().filter(|_| s == 0),
+                        samples_per_pixel: Some(1),
+                        ..ch.clone()
+                    })
+                })
+                .collect();
+        }
+
+        Ok(md)
+    }
+
+    fn open_bytes(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u8>> {
+        let loc = self.inner_loc(loc)?;
+        self.reader.open_bytes(loc, region)
+    }
+
+    fn open_bytes_into(&mut self, loc: Loc, region: Region, buf: &mut [u8]) -> io::Result<()> {
+        let loc = self.inner_loc(loc)?;
+        self.reader.open_bytes_into(loc, region, buf)
+    }
+
+    fn open_u16(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u16>> {
+        let loc = self.inner_loc(loc)?;
+        self.reader.open_u16(loc, region)
+    }
+
+    fn open_u16_into(&mut self, loc: Loc, region: Region, buf: &mut [u16]) -> io::Result<()> {
+        let loc = self.inner_loc(loc)?;
+        self.reader.open_u16_into(loc, region, buf)
+    }
+
+    fn open_bytes_cow(&mut self, loc: Loc, region: Region) -> io::Result<Cow<'_, [u8]>> {
+        let loc = self.inner_loc(loc)?;
+        self.reader.open_bytes_cow(loc, region)
+    }
+
+    fn get_lut(&mut self, c: u64) -> io::Result<Option<Lut>> {
+        let c = self.to_inner(c)?;
+        self.reader.get_lut(c)
+    }
+
+    fn plane_present(&mut self, loc: Loc) -> io::Result<bool> {
+        let loc = self.inner_loc(loc)?;
+        self.reader.plane_present(loc)
+    }
+
+    fn optimal_tile_size(&mut self) -> io::Result<(u64, u64)> {
+        self.reader.optimal_tile_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::test_support::{TagValue, TestIfd, tiff};
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[test]
+    fn separated_channels_are_single_sample() {
+        let f_name = "assets/example_valid.tiff";
+        let mut separated = ChannelSeparator::new(TiffReader::new(f_name.into()).unwrap());
+        let mut direct = TiffReader::new(f_name.into()).unwrap();
+
+        let md = separated.metadata().unwrap();
+        let series = md.series(0).unwrap();
+        let (loc, region) = (Loc::new(0, 2, 0), Region::new(10, 10, 4, 4));
+
+        assert!(!series.is_interleaved());
+        assert!(!separated.is_rgb().unwrap());
+        assert!(direct.is_rgb().unwrap() && direct.is_interleaved().unwrap());
+        assert_eq!(direct.samples_per_pixel().unwrap(), 4);
+        assert_eq!(series.channels().len(), 4);
+        assert!(
+            series
+                .channels()
+                .iter()
+                .all(|ch| ch.samples_per_pixel == Some(1))
+        );
+        assert_eq!(
+            separated.open_bytes(loc, region).unwrap(),
+            direct.open_bytes(loc, region).unwrap()
+        );
+    }
+
+    #[test]
+    fn separated_channel_reads_its_sample() {
+        // 2x2 chunky RGB, pixel i holding (i, 10 + i, 20 + i)
+        let pixels = (0..4u8).flat_map(|i| [i, 10 + i, 20 + i]).collect();
+        let ifd = TestIfd::new(2, 2, 8, pixels)
+            .tag(258, TagValue::Shorts(&[8, 8, 8]))
+            .tag(262, TagValue::Short(2))
+            .tag(277, TagValue::Short(3));
+        let mut separated = ChannelSeparator::new(TiffReader::from_bytes(tiff(vec![ifd])).unwrap());
+
+        assert_eq!(separated.size_c().unwrap(), 3);
+        assert_eq!(separated.image_count().unwrap(), 3);
+        assert_eq!(separated.samples_per_pixel().unwrap(), 1);
+        assert_eq!(
+            separated
+                .open_bytes(Loc::new(0, 1, 0), Region::new(0, 0, 2, 2))
+                .unwrap(),
+            vec![10, 11, 12, 13]
+        );
+        assert!(
+            separated
+                .open_bytes(Loc::new(0, 3, 0), Region::new(0, 0, 2, 2))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    borrow::Cow,
+    io::{self, Error},
+    path::{Path, PathBuf},
+};
+
+use crate::format_in::{Axis, Dim, FormatReader, Loc, Metadata, PixelType, ReaderOptions, Region};
+use crate::ome::Channel;
+
+// Reinterprets the Z, C and T axes of another reader, e.g. for files which
+// label a timelapse as a Z stack. Each output axis is backed by an axis of
+// the wrapped reader.
+pub struct DimensionSwapper<R: FormatReader> {
+    reader: R,
+    // Wrapped axis presented as output (Z, C, T)
+    axes: [Axis; 3],
+}
+
+impl<R: FormatReader> DimensionSwapper<R> {
+    // `z`, `c` and `t` name the wrapped axis to present as each output axis
+    pub fn new(reader: R, z: Axis, c: Axis, t: Axis) -> io::Result<Self> {
+        if z == c || c == t || z == t {
+            return Err(Error::other(format!(
+                "Axes must be a permutation of Z, C and T: {z:?} {c:?} {t:?}"
+            )));
+        }
+
+        Ok(DimensionSwapper {
+            reader,
+            axes: [z, c, t],
+        })
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn to_outer(&self, (z, c, t): (u64, u64, u64)) -> (u64, u64, u64) {
+        let inner = [z, c, t];
+        let [oz, oc, ot] = self.axes.map(|a| inner[a.index()]);
+        (oz, oc, ot)
+    }
+
+    fn to_inner(&self, (z, c, t): (u64, u64, u64)) -> (u64, u64, u64) {
+        let mut inner = [0; 3];
+        for (axis, v) in self.axes.iter().zip([z, c, t]) {
+            inner[axis.index()] = v;
+        }
+        (inner[0], inner[1], inner[2])
+    }
+}
+
+impl<R: FormatReader> FormatReader for DimensionSwapper<R> {
+    fn set_id(&mut self, id: &Path) -> io::Result<()> {
+        self.reader.set_id(id)
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.reader.close()
+    }
+
+    fn used_files(&self) -> io::Result<Vec<PathBuf>> {
+        self.reader.used_files()
+    }
+
+    fn options(&self) -> ReaderOptions {
+        self.reader.options()
+    }
+
+    fn set_options(&mut self, options: ReaderOptions) {
+        self.reader.set_options(options)
+    }
+
+    fn resolution(&self) -> u64 {
+        self.reader.resolution()
+    }
+
+    fn set_resolution(&mut self, resolution: u64) -> io::Result<()> {
+        self.reader.set_resolution(resolution)
+    }
+
+    fn resolution_size(&mut self) -> io::Result<(u64, u64)> {
+        self.reader.resolution_size()
+    }
+
+    fn series(&self) -> u64 {
+        self.reader.series()
+    }
+
+    fn set_series(&mut self, series: u64) -> io::Result<()> {
+        self.reader.set_series(series)
+    }
+
+    fn is_deferred(&self) -> bool {
+        self.reader.is_deferred()
+    }
+
+    fn metadata(&mut self) -> io::Result<Metadata> {
+        let mut md = self.reader.metadata()?;
+
+        for series in md.series.iter_mut() {
+            let dim = &series.dimensions;
+            let (z, c, t) = self.to_outer(dim.zct());
+            series.dimensions = Dim::new(dim.size_x(), dim.size_y(), z, c, t);
+
+            for plane in series.planes.iter_mut() {
+                (plane.the_z, plane.the_c, plane.the_t) =
+                    self.to_outer((plane.the_z, plane.the_c, plane.the_t));
+            }
+
+            // Channel metadata no longer describes the C axis
+            if self.axes[1] != Axis::C {
+                let pixel_type = series.pixel_types.first().copied().unwrap_or(PixelType::U8);
+                series.pixel_types = vec![pixel_type; c as usize];
+                series.channels = vec![Channel::default(); c as usize];
+            }
+        }
+
+        Ok(md)
+    }
+
+    fn open_bytes(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u8>> {
+        let (z, c, t) = self.to_inner((loc.z, loc.c, loc.t));
+        self.reader.open_bytes(Loc::new(z, c, t), region)
+    }
+
+    fn open_bytes_cow(&mut self, loc: Loc, region: Region) -> io::Result<Cow<'_, [u8]>> {
+        let (z, c, t) = self.to_inner((loc.z, loc.c, loc.t));
+        self.reader.open_bytes_cow(Loc::new(z, c, t), region)
+    }
+
+    fn plane_present(&mut self, loc: Loc) -> io::Result<bool> {
+        let (z, c, t) = self.to_inner((loc.z, loc.c, loc.t));
+        self.reader.plane_present(Loc::new(z, c, t))
+    }
+
+    fn optimal_tile_size(&mut self) -> io::Result<(u64, u64)> {
+        self.reader.optimal_tile_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[test]
+    fn channels_presented_as_time() {
+        let f_name = "assets/example_valid.tiff";
+        let reader = TiffReader::new(f_name.into()).unwrap();
+        let mut swapped = DimensionSwapper::new(reader, Axis::Z, Axis::T, Axis::C).unwrap();
+        let mut direct = TiffReader::new(f_name.into()).unwrap();
+        let region = Region::new(5, 5, 2, 2);
+
+        assert_eq!(swapped.size_c().unwrap(), 1);
+        assert_eq!(swapped.size_t().unwrap(), 4);
+        assert_eq!(
+            swapped.open_bytes(Loc::new(0, 0, 3), region).unwrap(),
+            direct.open_bytes(Loc::new(0, 3, 0), region).unwrap()
+        );
+        assert!(DimensionSwapper::new(direct, Axis::Z, Axis::Z, Axis::T).is_err());
+    }
+}
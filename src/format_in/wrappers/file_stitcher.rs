@@ -0,0 +1,481 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Error},
+    path::{Path, PathBuf},
+};
+
+use crate::format_in::{
+    Axis, Dim, FormatReader, Loc, Metadata, ReaderOptions, Region, missing_plane,
+};
+
+// File names split into literal text and numeric blocks, e.g. "img_t03.tif"
+// is ["img_t", ".tif"] around [3]
+fn split_name(name: &str) -> (Vec<String>, Vec<u64>) {
+    let mut literals = vec![String::new()];
+    let mut numbers = Vec::new();
+    let mut digits = String::new();
+
+    for ch in name.chars() {
+        if ch.is_ascii_digit() {
+            digits.push(ch);
+            continue;
+        }
+        if !digits.is_empty() {
+            numbers.push(digits.parse().unwrap_or(0));
+            digits.clear();
+            literals.push(String::new());
+        }
+        literals.last_mut().unwrap().push(ch);
+    }
+    if !digits.is_empty() {
+        numbers.push(digits.parse().unwrap_or(0));
+        literals.push(String::new());
+    }
+
+    (literals, numbers)
+}
+
+// Sibling files differing from a given file only in their numbers
+#[derive(Debug, Clone)]
+pub struct FilePattern {
+    // Distinct, sorted values of each numeric block which varies
+    blocks: Vec<Vec<u64>>,
+    // Literal text preceding each varying block, used to guess its axis
+    prefixes: Vec<String>,
+    files: HashMap<Vec<u64>, PathBuf>,
+}
+
+impl FilePattern {
+    pub fn from_file(file: &Path) -> io::Result<FilePattern> {
+        let dir = file.parent().unwrap_or(Path::new("."));
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        let name = file
+            .file_name()
+            .ok_or(Error::other("Not a file"))?
+            .to_string_lossy()
+            .to_string();
+        let (literals, _) = split_name(&name);
+
+        let mut matches = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Some(other) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+                continue;
+            };
+
+            let (other_literals, numbers) = split_name(&other);
+            if other_literals == literals {
+                matches.push((numbers, dir.join(other)));
+            }
+        }
+
+        // Blocks with a single value (e.g. a date) don't index anything
+        let n_blocks = literals.len() - 1;
+        let varying = (0..n_blocks)
+            .filter(|b| {
+                let first = matches.first().map(|(n, _)| n[*b]);
+                matches.iter().any(|(n, _)| Some(n[*b]) != first)
+            })
+            .collect::<Vec<_>>();
+
+        let blocks = varying
+            .iter()
+            .map(|b| {
+                let mut values = matches.iter().map(|(n, _)| n[*b]).collect::<Vec<_>>();
+                values.sort();
+                values.dedup();
+                values
+            })
+            .collect();
+
+        let files = matches
+            .into_iter()
+            .map(|(n, path)| (varying.iter().map(|b| n[*b]).collect(), path))
+            .collect();
+
+        Ok(FilePattern {
+            blocks,
+            prefixes: varying.iter().map(|b| literals[*b].clone()).collect(),
+            files,
+        })
+    }
+
+    // A pattern of just the given file, with nothing varying
+    pub fn single(file: &Path) -> FilePattern {
+        FilePattern {
+            blocks: Vec::new(),
+            prefixes: Vec::new(),
+            files: HashMap::from([(Vec::new(), file.to_path_buf())]),
+        }
+    }
+
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn block_values(&self, block: usize) -> &[u64] {
+        &self.blocks[block]
+    }
+
+    // Every matching file, sorted
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files = self.files.values().cloned().collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    // File holding the given value of every varying block
+    pub fn file(&self, values: &[u64]) -> Option<&PathBuf> {
+        self.files.get(values)
+    }
+
+    // Guess each block's axis from the text before it ("z", "ch", "t", ...),
+    // assigning T to blocks with no recognisable label
+    pub fn guess_axes(&self) -> Vec<Axis> {
+        self.prefixes
+            .iter()
+            .map(|p| {
+                let p = p.to_lowercase();
+                let p = p.trim_end_matches(['_', '-', ' ', '.']);
+                if ["z", "focal", "plane", "slice"]
+                    .iter()
+                    .any(|s| p.ends_with(s))
+                {
+                    Axis::Z
+                } else if ["c", "ch", "channel", "w", "wave"]
+                    .iter()
+                    .any(|s| p.ends_with(s))
+                {
+                    Axis::C
+                } else {
+                    Axis::T
+                }
+            })
+            .collect()
+    }
+}
+
+// Presents a group of numbered files, e.g. one file per timepoint, as a
+// single dataset. Every file is assumed to have the dimensions of the one
+// given to set_id; each varying number in the file names becomes an extra
+// stretch of the Z, C or T axis.
+#[derive(Default)]
+pub struct FileStitcher<R: FormatReader + Default> {
+    reader: R,
+    axes: Option<Vec<Axis>>,
+    pattern: Option<FilePattern>,
+    // Sizes (z, c, t) of each series of a single file
+    file_sizes: Vec<(u64, u64, u64)>,
+    current: Option<PathBuf>,
+    series: u64,
+}
+
+impl<R: FormatReader + Default> FileStitcher<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Axis of each varying block of the file pattern, in the order the
+    // blocks appear in the file name. Guessed from the names when not set.
+    pub fn with_axes(mut self, axes: Vec<Axis>) -> Self {
+        self.axes = Some(axes);
+        self
+    }
+
+    pub fn pattern(&self) -> Option<&FilePattern> {
+        self.pattern.as_ref()
+    }
+
+    fn axes(&self) -> io::Result<Vec<Axis>> {
+        let pattern = self
+            .pattern
+            .as_ref()
+            .ok_or(Error::other("Reader not initialized, call set_id"))?;
+
+        // Axes given for a group don't apply when only one file is read
+        if pattern.block_count() == 0 {
+            return Ok(Vec::new());
+        }
+
+        let axes = self.axes.clone().unwrap_or_else(|| pattern.guess_axes());
+        if axes.len() != pattern.block_count() {
+            return Err(Error::other(format!(
+                "{} axes given for {} numbered blocks",
+                axes.len(),
+                pattern.block_count()
+            )));
+        }
+
+        Ok(axes)
+    }
+
+    // Number of files along each axis
+    fn file_counts(&self) -> io::Result<(u64, u64, u64)> {
+        let pattern = self
+            .pattern
+            .as_ref()
+            .ok_or(Error::other("Reader not initialized, call set_id"))?;
+
+        let mut counts = [1; 3];
+        for (b, axis) in self.axes()?.iter().enumerate() {
+            counts[axis.index()] *= pattern.block_values(b).len() as u64;
+        }
+
+        Ok((counts[0], counts[1], counts[2]))
+    }
+
+    // The file holding plane loc, None where that file doesn't exist, and
+    // the location of the plane within it
+    fn file_for(&self, loc: Loc) -> io::Result<(Option<PathBuf>, Loc)> {
+        let (sz, sc, st) = *self
+            .file_sizes
+            .get(self.series as usize)
+            .ok_or(Error::other(format!("Invalid series {}", self.series)))?;
+        let axes = self.axes()?;
+        let pattern = self.pattern.as_ref().unwrap();
+
+        // Split each coordinate into a file index and a position within
+        // the file; blocks sharing an axis vary last-block-fastest
+        let mut file_index = [loc.z / sz, loc.c / sc, loc.t / st];
+        let mut values = vec![0; axes.len()];
+        for (b, axis) in axes.iter().enumerate().rev() {
+            let block = pattern.block_values(b);
+            let i = &mut file_index[axis.index()];
+            values[b] = *block.get((*i % block.len() as u64) as usize).unwrap();
+            *i /= block.len() as u64;
+        }
+        if file_index.iter().any(|i| *i > 0) {
+            return Err(Error::other("Plane out of range"));
+        }
+
+        let inner = Loc::new(loc.z % sz, loc.c % sc, loc.t % st);
+        Ok((pattern.file(&values).cloned(), inner))
+    }
+
+    fn switch_to(&mut self, file: &Path) -> io::Result<()> {
+        if self.current.as_deref() != Some(file) {
+            let resolution = self.reader.resolution();
+            self.reader.set_id(file)?;
+            self.reader.set_series(self.series)?;
+            self.reader.set_resolution(resolution)?;
+            self.current = Some(file.to_path_buf());
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: FormatReader + Default> FormatReader for FileStitcher<R> {
+    fn set_id(&mut self, id: &Path) -> io::Result<()> {
+        self.close()?;
+
+        self.pattern = Some(match self.options().group_files() {
+            true => FilePattern::from_file(id)?,
+            false => FilePattern::single(id),
+        });
+        self.axes()?;
+
+        self.reader.set_id(id)?;
+        self.current = Some(id.to_path_buf());
+        self.file_sizes = self
+            .reader
+            .metadata()?
+            .series
+            .iter()
+            .map(|s| s.dimensions.zct())
+            .collect();
+
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.pattern = None;
+        self.file_sizes.clear();
+        self.current = None;
+        self.series = 0;
+        self.reader.close()
+    }
+
+    fn used_files(&self) -> io::Result<Vec<PathBuf>> {
+        Ok(self.pattern.as_ref().map(|p| p.files()).unwrap_or_default())
+    }
+
+    fn options(&self) -> ReaderOptions {
+        self.reader.options()
+    }
+
+    fn set_options(&mut self, options: ReaderOptions) {
+        self.reader.set_options(options)
+    }
+
+    fn resolution(&self) -> u64 {
+        self.reader.resolution()
+    }
+
+    fn set_resolution(&mut self, resolution: u64) -> io::Result<()> {
+        self.reader.set_resolution(resolution)
+    }
+
+    fn resolution_size(&mut self) -> io::Result<(u64, u64)> {
+        self.reader.resolution_size()
+    }
+
+    fn series(&self) -> u64 {
+        self.series
+    }
+
+    fn set_series(&mut self, series: u64) -> io::Result<()> {
+        self.reader.set_series(series)?;
+        self.series = series;
+        Ok(())
+    }
+
+    fn is_deferred(&self) -> bool {
+        self.reader.is_deferred()
+    }
+
+    fn metadata(&mut self) -> io::Result<Metadata> {
+        let mut md = self.reader.metadata()?;
+        let (fz, fc, ft) = self.file_counts()?;
+
+        for series in md.series.iter_mut() {
+            let dim = &series.dimensions;
+            let (z, c, t) = dim.zct();
+            series.dimensions = Dim::new(dim.size_x(), dim.size_y(), z * fz, c * fc, t * ft);
+
+            // Channels repeat for every file along C, plane metadata only
+            // describes the file it came from
+            series.pixel_types = series.pixel_types.repeat(fc as usize);
+            series.channels = (0..fc)
+                .flat_map(|_| series.channels.iter().cloned())
+                .collect();
+            series.planes.clear();
+            series.tiff_data.clear();
+        }
+
+        Ok(md)
+    }
+
+    // Combinations of numbers without a file in the pattern are missing
+    fn plane_present(&mut self, loc: Loc) -> io::Result<bool> {
+        Ok(self.file_for(loc)?.0.is_some())
+    }
+
+    fn open_bytes(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u8>> {
+        let (Some(file), inner) = self.file_for(loc)? else {
+            return missing_plane(self, loc, region);
+        };
+
+        self.switch_to(&file)?;
+        self.reader.open_bytes(inner, region)
+    }
+
+    fn optimal_tile_size(&mut self) -> io::Result<(u64, u64)> {
+        self.reader.optimal_tile_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OmeError;
+    use crate::format_in::test_support::TempDir;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    fn group(files: &[&str]) -> TempDir {
+        let dir = TempDir::new();
+
+        for f in files {
+            let path = dir.join(f);
+            fs::hard_link("assets/example_valid.tiff", &path)
+                .or_else(|_| fs::copy("assets/example_valid.tiff", &path).map(|_| ()))
+                .unwrap();
+        }
+
+        dir
+    }
+
+    #[test]
+    fn pattern_guesses_axes() {
+        let dir = group(&[
+            "s2024_z0_ch1.tif",
+            "s2024_z1_ch1.tif",
+            "s2024_z0_ch2.tif",
+            "other.tif",
+        ]);
+
+        let pattern = FilePattern::from_file(&dir.join("s2024_z1_ch1.tif")).unwrap();
+
+        assert_eq!(pattern.block_count(), 2);
+        assert_eq!(pattern.block_values(1), &[1, 2]);
+        assert_eq!(pattern.guess_axes(), vec![Axis::Z, Axis::C]);
+        assert_eq!(pattern.files().len(), 3);
+        assert_eq!(pattern.file(&[0, 2]), Some(&dir.join("s2024_z0_ch2.tif")));
+    }
+
+    #[test]
+    fn missing_files_are_reported() {
+        let dir = group(&["s_z0_ch1.tif", "s_z1_ch1.tif", "s_z0_ch2.tif"]);
+
+        let mut stitcher = FileStitcher::<TiffReader>::new();
+        stitcher.set_id(&dir.join("s_z0_ch1.tif")).unwrap();
+        let (missing, region) = (Loc::new(1, 5, 0), Region::new(0, 0, 2, 2));
+
+        assert_eq!(
+            stitcher.missing_planes().unwrap(),
+            (4..8).map(|c| Loc::new(1, c, 0)).collect::<Vec<_>>()
+        );
+        let err = stitcher.open_bytes(missing, region).unwrap_err();
+        assert!(matches!(
+            OmeError::from_io(&err),
+            Some(OmeError::PlaneMissing { z: 1, c: 5, .. })
+        ));
+
+        stitcher.set_options(ReaderOptions::new().with_fill_missing_planes(true));
+        assert_eq!(stitcher.open_bytes(missing, region).unwrap(), vec![0; 8]);
+        assert!(stitcher.open_bytes(Loc::new(1, 1, 0), region).is_ok());
+    }
+
+    #[test]
+    fn ungrouped_reads_one_file() {
+        let dir = group(&["img_t0.tif", "img_t1.tif"]);
+
+        let mut stitcher = FileStitcher::<TiffReader>::new().with_axes(vec![Axis::T]);
+        stitcher.set_options(ReaderOptions::new().with_group_files(false));
+        stitcher.set_id(&dir.join("img_t1.tif")).unwrap();
+
+        assert_eq!(stitcher.size_t().unwrap(), 1);
+        assert_eq!(stitcher.used_files().unwrap(), vec![dir.join("img_t1.tif")]);
+        assert!(stitcher.missing_planes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn stitch_timepoints() {
+        let dir = group(&["img_t0.tif", "img_t1.tif"]);
+
+        let mut stitcher = FileStitcher::<TiffReader>::new();
+        stitcher.set_id(&dir.join("img_t0.tif")).unwrap();
+        let mut direct = TiffReader::new("assets/example_valid.tiff".into()).unwrap();
+
+        assert_eq!(stitcher.size_t().unwrap(), 2);
+        assert_eq!(stitcher.used_files().unwrap().len(), 2);
+        assert_eq!(
+            stitcher
+                .open_bytes(Loc::new(0, 1, 1), Region::new(3, 3, 2, 2))
+                .unwrap(),
+            direct
+                .open_bytes(Loc::new(0, 1, 0), Region::new(3, 3, 2, 2))
+                .unwrap()
+        );
+        assert!(
+            stitcher
+                .open_bytes(Loc::new(0, 0, 2), Region::new(0, 0, 1, 1))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::UNIX_EPOCH,
+};
+
+use crate::format_in::{FormatReader, Loc, Lut, Metadata, MetadataLevel, ReaderOptions, Region};
+
+// Bumped whenever the metadata model changes shape, invalidating old memos
+const MEMO_VERSION: u32 = 4;
+
+// Size and modification time of a file, used to detect stale memos
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Stamp {
+    file: PathBuf,
+    len: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+}
+
+impl Stamp {
+    fn new(file: &Path) -> io::Result<Self> {
+        let md = fs::metadata(file)?;
+        let modified = md
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_err(io::Error::other)?;
+
+        Ok(Stamp {
+            file: file.to_path_buf(),
+            len: md.len(),
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+        })
+    }
+}
+
+// The reader options the metadata depends on. A memo written under other
+// options, e.g. Minimum level or flattened pyramids, is stale
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct MemoOptions {
+    metadata_level: MetadataLevel,
+    flatten_resolutions: bool,
+    group_files: bool,
+}
+
+impl MemoOptions {
+    fn new(options: &ReaderOptions) -> Self {
+        MemoOptions {
+            metadata_level: options.metadata_level(),
+            flatten_resolutions: options.flatten_resolutions(),
+            group_files: options.group_files(),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Memo {
+    version: u32,
+    stamps: Vec<Stamp>,
+    options: MemoOptions,
+    metadata: Metadata,
+}
+
+// The memo of a dataset is a hidden sibling, e.g. "dir/.image.tiff.bfmemo"
+pub fn memo_path(id: &Path) -> PathBuf {
+    let name = id
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    id.with_file_name(format!(".{name}.bfmemo"))
+}
+
+// Caches the fully parsed metadata of another reader on disk, so reopening
+// a large (multi-file) dataset skips translating it. Only the metadata is
+// memoized: the wrapped reader still walks its own index (e.g. the IFD
+// chain and strip tables of a TIFF) when pixels are first read. The memo
+// is discarded when any of the reader's used files has changed since it
+// was written.
+pub struct Memoizer<R: FormatReader> {
+    reader: R,
+    memo: Option<PathBuf>,
+    metadata: Option<Arc<Metadata>>,
+    from_memo: bool,
+}
+
+impl<R: FormatReader> Memoizer<R> {
+    pub fn new(reader: R) -> Self {
+        Memoizer {
+            reader,
+            memo: None,
+            metadata: None,
+            from_memo: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // Whether the current metadata was rehydrated from a memo
+    pub fn is_from_memo(&self) -> bool {
+        self.from_memo
+    }
+
+    fn load(&self, memo: &Path) -> io::Result<Metadata> {
+        let memo: Memo =
+            serde_json::from_str(&fs::read_to_string(memo)?).map_err(io::Error::other)?;
+
+        let stamps = self
+            .reader
+            .used_files()?
+            .iter()
+            .map(|f| Stamp::new(f))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        if memo.version != MEMO_VERSION
+            || memo.stamps != stamps
+            || memo.options != MemoOptions::new(&self.reader.options())
+        {
+            return Err(io::Error::other("Stale memo"));
+        }
+
+        Ok(memo.metadata)
+    }
+
+    fn save(&self, memo: &Path, metadata: &Metadata) -> io::Result<()> {
+        let stamps = self
+            .reader
+            .used_files()?
+            .iter()
+            .map(|f| Stamp::new(f))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let memo_data = Memo {
+            version: MEMO_VERSION,
+            stamps,
+            options: MemoOptions::new(&self.reader.options()),
+            metadata: metadata.clone(),
+        };
+        let json = serde_json::to_string(&memo_data).map_err(io::Error::other)?;
+
+        fs::write(memo, json)
+    }
+}
+
+impl<R: FormatReader> FormatReader for Memoizer<R> {
+    fn set_id(&mut self, id: &Path) -> io::Result<()> {
+        self.close()?;
+        self.reader.set_id(id)?;
+
+        let memo = memo_path(id);
+        // A missing, stale or unreadable memo just means parsing as usual
+        self.metadata = self.load(&memo).ok().map(Arc::new);
+        self.from_memo = self.metadata.is_some();
+        self.memo = Some(memo);
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.memo = None;
+        self.metadata = None;
+        self.from_memo = false;
+        self.reader.close()
+    }
+
+    fn used_files(&self) -> io::Result<Vec<PathBuf>> {
+        self.reader.used_files()
+    }
+
+    fn options(&self) -> ReaderOptions {
+        self.reader.options()
+    }
+
+    fn set_options(&mut self, options: ReaderOptions) {
+        self.reader.set_options(options)
+    }
+
+    fn resolution(&self) -> u64 {
+        self.reader.resolution()
+    }
+
+    fn set_resolution(&mut self, resolution: u64) -> io::Result<()> {
+        self.reader.set_resolution(resolution)
+    }
+
+    fn resolution_size(&mut self) -> io::Result<(u64, u64)> {
+        self.reader.resolution_size()
+    }
+
+    fn series(&self) -> u64 {
+        self.reader.series()
+    }
+
+    fn set_series(&mut self, series: u64) -> io::Result<()> {
+        self.reader.set_series(series)
+    }
+
+    fn metadata(&mut self) -> io::Result<Metadata> {
+        Ok(Metadata::clone(&*self.metadata_arc()?))
+    }
+
+    fn metadata_arc(&mut self) -> io::Result<Arc<Metadata>> {
+        if let Some(md) = &self.metadata {
+            return Ok(md.clone());
+        }
+
+        let md = self.reader.metadata_arc()?;
+        // Partial metadata, e.g. under fast init, is neither kept nor
+        // memoized, so a later full open still parses the file
+        if self.reader.is_deferred() {
+            return Ok(md);
+        }
+        if let Some(memo) = &self.memo {
+            // Failing to write the memo (e.g. a read-only directory) only
+            // costs the next open its speed up
+            let _ = self.save(memo, &md);
+        }
+        self.metadata = Some(md.clone());
+
+        Ok(md)
+    }
+
+    fn is_deferred(&self) -> bool {
+        self.metadata.is_none() && self.reader.is_deferred()
+    }
+
+    fn open_bytes(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u8>> {
+        self.reader.open_bytes(loc, region)
+    }
+
+    fn open_bytes_into(&mut self, loc: Loc, region: Region, buf: &mut [u8]) -> io::Result<()> {
+        self.reader.open_bytes_into(loc, region, buf)
+    }
+
+    fn open_u16(&mut self, loc: Loc, region: Region) -> io::Result<Vec<u16>> {
+        self.reader.open_u16(loc, region)
+    }
+
+    fn open_u16_into(&mut self, loc: Loc, region: Region, buf: &mut [u16]) -> io::Result<()> {
+        self.reader.open_u16_into(loc, region, buf)
+    }
+
+    fn get_lut(&mut self, c: u64) -> io::Result<Option<Lut>> {
+        self.reader.get_lut(c)
+    }
+
+    fn plane_present(&mut self, loc: Loc) -> io::Result<bool> {
+        self.reader.plane_present(loc)
+    }
+
+    fn optimal_tile_size(&mut self) -> io::Result<(u64, u64)> {
+        self.reader.optimal_tile_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::test_support::TempDir;
+    use crate::format_in::tiff_reader::TiffReader;
+
+    #[test]
+    fn metadata_rehydrated_from_memo() {
+        let dir = TempDir::new();
+        let tiff = dir.join("image.tiff");
+        fs::copy("assets/example_valid.tiff", &tiff).unwrap();
+
+        let mut first = Memoizer::new(TiffReader::default());
+        first.set_id(&tiff).unwrap();
+        let parsed = first.metadata().unwrap();
+        assert!(!first.is_from_memo());
+
+        let mut second = Memoizer::new(TiffReader::default());
+        second.set_id(&tiff).unwrap();
+        assert!(second.is_from_memo());
+        assert_eq!(
+            second.metadata().unwrap().to_json().unwrap(),
+            parsed.to_json().unwrap()
+        );
+    }
+
+    #[test]
+    fn fast_init_metadata_is_not_memoized() {
+        let dir = TempDir::new();
+        let tiff = dir.join("image.tiff");
+        fs::copy("assets/example_valid.tiff", &tiff).unwrap();
+
+        let fast = ReaderOptions::new().with_fast_init(true);
+        let mut first = Memoizer::new(TiffReader::with_options(fast));
+        first.set_id(&tiff).unwrap();
+        assert!(
+            first
+                .metadata()
+                .unwrap()
+                .global()
+                .original_metadata()
+                .is_empty()
+        );
+        assert!(!memo_path(&tiff).exists());
+
+        // Reading pixels completes the metadata the wrapper then returns
+        first
+            .open_bytes(Loc::new(0, 0, 0), Region::new(0, 0, 1, 1))
+            .unwrap();
+        assert!(
+            !first
+                .metadata()
+                .unwrap()
+                .global()
+                .original_metadata()
+                .is_empty()
+        );
+
+        let mut second = Memoizer::new(TiffReader::default());
+        second.set_id(&tiff).unwrap();
+        assert!(
+            !second
+                .metadata()
+                .unwrap()
+                .global()
+                .original_metadata()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn memo_of_other_options_is_stale() {
+        let dir = TempDir::new();
+        let tiff = dir.join("image.tiff");
+        fs::copy("assets/example_valid.tiff", &tiff).unwrap();
+
+        let minimum = ReaderOptions::new().with_metadata_level(MetadataLevel::Minimum);
+        let mut first = Memoizer::new(TiffReader::with_options(minimum));
+        first.set_id(&tiff).unwrap();
+        first.metadata().unwrap();
+
+        let mut second = Memoizer::new(TiffReader::default());
+        second.set_id(&tiff).unwrap();
+        assert!(!second.is_from_memo());
+        assert!(
+            !second
+                .metadata()
+                .unwrap()
+                .global()
+                .original_metadata()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,13 @@
+// Readers which wrap another reader to change how its data is presented
+
+pub mod channel_separator;
+pub mod dimension_swapper;
+pub mod file_stitcher;
+#[cfg(feature = "serde")]
+pub mod memoizer;
+
+pub use channel_separator::ChannelSeparator;
+pub use dimension_swapper::DimensionSwapper;
+pub use file_stitcher::{FilePattern, FileStitcher};
+#[cfg(feature = "serde")]
+pub use memoizer::Memoizer;
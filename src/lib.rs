@@ -1,4 +1,9 @@
+pub mod error;
 pub mod format_in;
+pub mod ome;
+pub mod units;
+
+pub use error::OmeError;
 
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
@@ -0,0 +1,200 @@
+use crate::ome::xml::Element;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Annotation {
+    Map {
+        id: String,
+        namespace: Option<String>,
+        pairs: Vec<(String, String)>,
+    },
+    Xml {
+        id: String,
+        namespace: Option<String>,
+        value: Element,
+    },
+    // Any other annotation type, kept verbatim so it survives a round trip
+    Other(Element),
+}
+
+impl Annotation {
+    pub fn from_xml(elem: &Element) -> Self {
+        let id = elem.attr("ID").unwrap_or_default().to_string();
+        let namespace = elem.attr("Namespace").map(|a| a.to_string());
+
+        match elem.local_name() {
+            "MapAnnotation" => Annotation::Map {
+                id,
+                namespace,
+                pairs: elem
+                    .child("Value")
+                    .map(|v| {
+                        v.children_named("M")
+                            .map(|m| (m.attr("K").unwrap_or_default().into(), m.text()))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            },
+            "XMLAnnotation" => Annotation::Xml {
+                id,
+                namespace,
+                value: elem
+                    .child("Value")
+                    .cloned()
+                    .unwrap_or(Element::new("Value")),
+            },
+            _ => Annotation::Other(elem.clone()),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        match self {
+            Annotation::Map {
+                id,
+                namespace,
+                pairs,
+            } => {
+                let mut elem = Element::new("MapAnnotation").with_attr("ID", id);
+                if let Some(ns) = namespace {
+                    elem.set_attr("Namespace", ns);
+                }
+
+                let mut value = Element::new("Value");
+                for (k, v) in pairs {
+                    let mut m = Element::new("M").with_attr("K", k);
+                    m.push_text(v);
+                    value.push(m);
+                }
+                elem.push(value);
+                elem
+            }
+            Annotation::Xml {
+                id,
+                namespace,
+                value,
+            } => {
+                let mut elem = Element::new("XMLAnnotation").with_attr("ID", id);
+                if let Some(ns) = namespace {
+                    elem.set_attr("Namespace", ns);
+                }
+                elem.push(value.clone());
+                elem
+            }
+            Annotation::Other(elem) => elem.clone(),
+        }
+    }
+
+    pub fn namespace(&self) -> Option<&str> {
+        match self {
+            Annotation::Map { namespace, .. } | Annotation::Xml { namespace, .. } => {
+                namespace.as_deref()
+            }
+            Annotation::Other(elem) => elem.attr("Namespace"),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        match self {
+            Annotation::Map { id, .. } | Annotation::Xml { id, .. } => id,
+            Annotation::Other(elem) => elem.attr("ID").unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StructuredAnnotations {
+    pub annotations: Vec<Annotation>,
+}
+
+impl StructuredAnnotations {
+    pub fn from_xml(elem: &Element) -> Self {
+        StructuredAnnotations {
+            annotations: elem.elements().map(Annotation::from_xml).collect(),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("StructuredAnnotations");
+        self.annotations.iter().for_each(|a| elem.push(a.to_xml()));
+        elem
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Annotation> {
+        self.annotations.iter().find(|a| a.id() == id)
+    }
+
+    // Attach key/value metadata, returning the ID of the new annotation
+    pub fn add_map_annotation(
+        &mut self,
+        namespace: Option<&str>,
+        pairs: Vec<(String, String)>,
+    ) -> String {
+        let id = self.next_id();
+        self.annotations.push(Annotation::Map {
+            id: id.clone(),
+            namespace: namespace.map(|a| a.into()),
+            pairs,
+        });
+
+        id
+    }
+
+    // Attach an arbitrary XML value, returning the ID of the new annotation
+    pub fn add_xml_annotation(&mut self, namespace: Option<&str>, value: Element) -> String {
+        let id = self.next_id();
+        self.annotations.push(Annotation::Xml {
+            id: id.clone(),
+            namespace: namespace.map(|a| a.into()),
+            value,
+        });
+
+        id
+    }
+
+    fn next_id(&self) -> String {
+        let mut n = self.annotations.len();
+        while self.get(&format!("Annotation:{n}")).is_some() {
+            n += 1;
+        }
+
+        format!("Annotation:{n}")
+    }
+
+    pub fn map_annotations(&self) -> impl Iterator<Item = &Vec<(String, String)>> {
+        self.annotations.iter().filter_map(|a| match a {
+            Annotation::Map { pairs, .. } => Some(pairs),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annotations_round_trip() {
+        let src = r#"<StructuredAnnotations>
+                <MapAnnotation ID="Annotation:0" Namespace="lab.example/acq">
+                    <Value><M K="operator">ada</M><M K="temp">37</M></Value>
+                </MapAnnotation>
+                <XMLAnnotation ID="Annotation:1"><Value><Custom A="1"/></Value></XMLAnnotation>
+                <CommentAnnotation ID="Annotation:2"><Value>note</Value></CommentAnnotation>
+            </StructuredAnnotations>"#;
+
+        let mut sa = StructuredAnnotations::from_xml(&Element::parse(src).unwrap());
+        let id = sa.add_map_annotation(None, vec![("k".into(), "v".into())]);
+
+        assert_eq!(id, "Annotation:3");
+        assert!(matches!(sa.get("Annotation:2"), Some(Annotation::Other(_))));
+        assert_eq!(
+            sa.map_annotations().next().unwrap()[0],
+            ("operator".into(), "ada".into())
+        );
+
+        let reparsed =
+            StructuredAnnotations::from_xml(&Element::parse(&sa.to_xml().to_string()).unwrap());
+        assert_eq!(reparsed, sa);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::ome::{LightPath, xml::Element};
+use crate::units::{Length, LengthUnit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const RED: Color = Color::rgb(255, 0, 0);
+    pub const GREEN: Color = Color::rgb(0, 255, 0);
+    pub const BLUE: Color = Color::rgb(0, 0, 255);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b, a: 255 }
+    }
+
+    // OME-XML stores colors as a signed 32 bit RGBA integer
+    pub fn from_ome(val: i32) -> Self {
+        let [r, g, b, a] = val.to_be_bytes();
+        Color { r, g, b, a }
+    }
+
+    pub fn to_ome(&self) -> i32 {
+        i32::from_be_bytes([self.r, self.g, self.b, self.a])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Channel {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub samples_per_pixel: Option<u64>,
+    pub color: Option<Color>,
+    // Display range (min, max) in sample values, as in OMERO rendering settings
+    pub window: Option<(f64, f64)>,
+    pub excitation_wavelength: Option<Length>,
+    pub emission_wavelength: Option<Length>,
+    pub filter_set: Option<String>,
+    pub light_path: Option<LightPath>,
+}
+
+impl Channel {
+    pub fn from_xml(elem: &Element) -> Self {
+        Channel {
+            id: elem.attr("ID").map(|a| a.into()),
+            name: elem.attr("Name").map(|a| a.into()),
+            samples_per_pixel: elem.attr_as("SamplesPerPixel"),
+            color: elem.attr_as("Color").map(Color::from_ome),
+            window: None,
+            excitation_wavelength: elem.attr_quantity(
+                "ExcitationWavelength",
+                LengthUnit::Nanometer,
+                Length::new,
+            ),
+            emission_wavelength: elem.attr_quantity(
+                "EmissionWavelength",
+                LengthUnit::Nanometer,
+                Length::new,
+            ),
+            filter_set: elem
+                .child("FilterSetRef")
+                .and_then(|f| f.attr("ID"))
+                .map(|a| a.into()),
+            light_path: elem.child("LightPath").map(LightPath::from_xml),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ome_color_round_trip() {
+        // Opaque red as written by Bio-Formats
+        let red = Color::from_ome(-16776961);
+
+        assert_eq!(red, Color::RED);
+        assert_eq!(red.to_ome(), -16776961);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::ome::xml::Element;
+
+fn opt(elem: &Element, key: &str) -> Option<String> {
+    elem.attr(key).map(|a| a.into())
+}
+
+fn ref_id(elem: &Element, name: &str) -> Option<String> {
+    elem.child(name).and_then(|r| opt(r, "ID"))
+}
+
+fn set_opt(elem: &mut Element, key: &str, value: &Option<String>) {
+    if let Some(v) = value {
+        elem.set_attr(key, v);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Experimenter {
+    pub id: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub email: Option<String>,
+    pub institution: Option<String>,
+    pub user_name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Project {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub experimenter: Option<String>,
+    pub datasets: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dataset {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub experimenter: Option<String>,
+    pub images: Vec<String>,
+}
+
+impl Experimenter {
+    pub fn from_xml(elem: &Element) -> Self {
+        Experimenter {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            first_name: opt(elem, "FirstName"),
+            last_name: opt(elem, "LastName"),
+            email: opt(elem, "Email"),
+            institution: opt(elem, "Institution"),
+            user_name: opt(elem, "UserName"),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("Experimenter").with_attr("ID", &self.id);
+        set_opt(&mut elem, "FirstName", &self.first_name);
+        set_opt(&mut elem, "LastName", &self.last_name);
+        set_opt(&mut elem, "Email", &self.email);
+        set_opt(&mut elem, "Institution", &self.institution);
+        set_opt(&mut elem, "UserName", &self.user_name);
+        elem
+    }
+}
+
+impl Project {
+    pub fn from_xml(elem: &Element) -> Self {
+        Project {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            name: opt(elem, "Name"),
+            description: elem.child("Description").map(|d| d.text()),
+            experimenter: ref_id(elem, "ExperimenterRef"),
+            datasets: elem
+                .children_named("DatasetRef")
+                .filter_map(|r| opt(r, "ID"))
+                .collect(),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("Project").with_attr("ID", &self.id);
+        set_opt(&mut elem, "Name", &self.name);
+        push_common(&mut elem, &self.description, &self.experimenter);
+        self.datasets
+            .iter()
+            .for_each(|d| elem.push(Element::new("DatasetRef").with_attr("ID", d)));
+        elem
+    }
+}
+
+impl Dataset {
+    pub fn from_xml(elem: &Element) -> Self {
+        Dataset {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            name: opt(elem, "Name"),
+            description: elem.child("Description").map(|d| d.text()),
+            experimenter: ref_id(elem, "ExperimenterRef"),
+            images: elem
+                .children_named("ImageRef")
+                .filter_map(|r| opt(r, "ID"))
+                .collect(),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("Dataset").with_attr("ID", &self.id);
+        set_opt(&mut elem, "Name", &self.name);
+        push_common(&mut elem, &self.description, &self.experimenter);
+        self.images
+            .iter()
+            .for_each(|i| elem.push(Element::new("ImageRef").with_attr("ID", i)));
+        elem
+    }
+}
+
+fn push_common(elem: &mut Element, description: &Option<String>, experimenter: &Option<String>) {
+    if let Some(text) = description {
+        let mut desc = Element::new("Description");
+        desc.push_text(text);
+        elem.push(desc);
+    }
+    if let Some(id) = experimenter {
+        elem.push(Element::new("ExperimenterRef").with_attr("ID", id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn containers_round_trip() {
+        let src = r#"<Dataset ID="Dataset:0" Name="day 1">
+                <Description>first run</Description>
+                <ExperimenterRef ID="Experimenter:0"/>
+                <ImageRef ID="Image:0"/><ImageRef ID="Image:1"/>
+            </Dataset>"#;
+
+        let dataset = Dataset::from_xml(&Element::parse(src).unwrap());
+
+        assert_eq!(dataset.images, vec!["Image:0", "Image:1"]);
+        assert_eq!(dataset.description.as_deref(), Some("first run"));
+        assert_eq!(Dataset::from_xml(&dataset.to_xml()), dataset);
+
+        let experimenter = Experimenter {
+            id: "Experimenter:0".into(),
+            institution: Some("Institute of Imaging".into()),
+            ..Default::default()
+        };
+        assert_eq!(Experimenter::from_xml(&experimenter.to_xml()), experimenter);
+    }
+}
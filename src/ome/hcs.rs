@@ -0,0 +1,236 @@
+use crate::ome::xml::Element;
+use crate::units::{Length, LengthUnit};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WellSample {
+    pub id: String,
+    pub index: u64,
+    pub position_x: Option<Length>,
+    pub position_y: Option<Length>,
+    pub image_ref: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Well {
+    pub id: String,
+    pub row: u64,
+    pub column: u64,
+    pub samples: Vec<WellSample>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlateAcquisition {
+    pub id: String,
+    pub name: Option<String>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub well_sample_refs: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plate {
+    pub id: String,
+    pub name: Option<String>,
+    pub rows: Option<u64>,
+    pub columns: Option<u64>,
+    pub row_naming_convention: Option<String>,
+    pub column_naming_convention: Option<String>,
+    pub wells: Vec<Well>,
+    pub acquisitions: Vec<PlateAcquisition>,
+}
+
+impl WellSample {
+    pub fn from_xml(elem: &Element) -> Self {
+        WellSample {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            index: elem.attr_as("Index").unwrap_or(0),
+            position_x: elem.attr_quantity("PositionX", LengthUnit::ReferenceFrame, Length::new),
+            position_y: elem.attr_quantity("PositionY", LengthUnit::ReferenceFrame, Length::new),
+            image_ref: elem
+                .child("ImageRef")
+                .and_then(|r| r.attr("ID"))
+                .map(|a| a.into()),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("WellSample")
+            .with_attr("ID", &self.id)
+            .with_attr("Index", self.index);
+        if let Some(x) = self.position_x {
+            elem.set_attr("PositionX", x.value());
+            elem.set_attr("PositionXUnit", x.unit().symbol());
+        }
+        if let Some(y) = self.position_y {
+            elem.set_attr("PositionY", y.value());
+            elem.set_attr("PositionYUnit", y.unit().symbol());
+        }
+        if let Some(image) = &self.image_ref {
+            elem.push(Element::new("ImageRef").with_attr("ID", image));
+        }
+        elem
+    }
+}
+
+impl Well {
+    pub fn from_xml(elem: &Element) -> Self {
+        Well {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            row: elem.attr_as("Row").unwrap_or(0),
+            column: elem.attr_as("Column").unwrap_or(0),
+            samples: elem
+                .children_named("WellSample")
+                .map(WellSample::from_xml)
+                .collect(),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("Well")
+            .with_attr("ID", &self.id)
+            .with_attr("Row", self.row)
+            .with_attr("Column", self.column);
+        self.samples.iter().for_each(|s| elem.push(s.to_xml()));
+        elem
+    }
+}
+
+impl PlateAcquisition {
+    pub fn from_xml(elem: &Element) -> Self {
+        PlateAcquisition {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            name: elem.attr("Name").map(|a| a.into()),
+            start_time: elem.attr("StartTime").map(|a| a.into()),
+            end_time: elem.attr("EndTime").map(|a| a.into()),
+            well_sample_refs: elem
+                .children_named("WellSampleRef")
+                .filter_map(|r| r.attr("ID"))
+                .map(|a| a.into())
+                .collect(),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("PlateAcquisition").with_attr("ID", &self.id);
+        if let Some(name) = &self.name {
+            elem.set_attr("Name", name);
+        }
+        if let Some(start) = &self.start_time {
+            elem.set_attr("StartTime", start);
+        }
+        if let Some(end) = &self.end_time {
+            elem.set_attr("EndTime", end);
+        }
+        self.well_sample_refs
+            .iter()
+            .for_each(|r| elem.push(Element::new("WellSampleRef").with_attr("ID", r)));
+        elem
+    }
+}
+
+impl Plate {
+    pub fn from_xml(elem: &Element) -> Self {
+        Plate {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            name: elem.attr("Name").map(|a| a.into()),
+            rows: elem.attr_as("Rows"),
+            columns: elem.attr_as("Columns"),
+            row_naming_convention: elem.attr("RowNamingConvention").map(|a| a.into()),
+            column_naming_convention: elem.attr("ColumnNamingConvention").map(|a| a.into()),
+            wells: elem.children_named("Well").map(Well::from_xml).collect(),
+            acquisitions: elem
+                .children_named("PlateAcquisition")
+                .map(PlateAcquisition::from_xml)
+                .collect(),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("Plate").with_attr("ID", &self.id);
+        if let Some(name) = &self.name {
+            elem.set_attr("Name", name);
+        }
+        if let Some(rows) = self.rows {
+            elem.set_attr("Rows", rows);
+        }
+        if let Some(columns) = self.columns {
+            elem.set_attr("Columns", columns);
+        }
+        if let Some(conv) = &self.row_naming_convention {
+            elem.set_attr("RowNamingConvention", conv);
+        }
+        if let Some(conv) = &self.column_naming_convention {
+            elem.set_attr("ColumnNamingConvention", conv);
+        }
+        self.wells.iter().for_each(|w| elem.push(w.to_xml()));
+        self.acquisitions.iter().for_each(|a| elem.push(a.to_xml()));
+        elem
+    }
+
+    pub fn well(&self, row: u64, column: u64) -> Option<&Well> {
+        self.wells
+            .iter()
+            .find(|w| w.row == row && w.column == column)
+    }
+
+    // ID of the Image acquired at a given field of a well
+    pub fn image_ref(&self, row: u64, column: u64, field: u64) -> Option<&str> {
+        self.well(row, column)?
+            .samples
+            .iter()
+            .find(|s| s.index == field)?
+            .image_ref
+            .as_deref()
+    }
+
+    // Human readable well label, e.g. "B3", honouring the naming conventions
+    pub fn well_label(&self, row: u64, column: u64) -> String {
+        let label = |conv: &Option<String>, i: u64| match conv.as_deref() {
+            Some("letter") => char::from_u32('A' as u32 + i as u32)
+                .map(|c| c.to_string())
+                .unwrap_or_default(),
+            _ => (i + 1).to_string(),
+        };
+
+        let row_conv = self.row_naming_convention.clone().or(Some("letter".into()));
+
+        format!(
+            "{}{}",
+            label(&row_conv, row),
+            label(&self.column_naming_convention, column)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plate_lookup() {
+        let src = r#"<Plate ID="Plate:0" Rows="8" Columns="12" RowNamingConvention="letter">
+                <Well ID="Well:0" Row="1" Column="2">
+                    <WellSample ID="WellSample:0" Index="0"><ImageRef ID="Image:0"/></WellSample>
+                    <WellSample ID="WellSample:1" Index="1"><ImageRef ID="Image:1"/></WellSample>
+                </Well>
+                <PlateAcquisition ID="PlateAcquisition:0">
+                    <WellSampleRef ID="WellSample:0"/>
+                </PlateAcquisition>
+            </Plate>"#;
+
+        let plate = Plate::from_xml(&Element::parse(src).unwrap());
+
+        assert_eq!(plate.image_ref(1, 2, 1), Some("Image:1"));
+        assert_eq!(plate.image_ref(0, 0, 0), None);
+        assert_eq!(plate.well_label(1, 2), "B3");
+        assert_eq!(plate.acquisitions[0].well_sample_refs, vec!["WellSample:0"]);
+        assert_eq!(
+            Plate::from_xml(&Element::parse(&plate.to_xml().to_string()).unwrap()),
+            plate
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use crate::ome::xml::Element;
+use crate::units::{Length, LengthUnit};
+
+fn refs(elem: &Element, name: &str) -> Vec<String> {
+    elem.children_named(name)
+        .filter_map(|r| r.attr("ID"))
+        .map(|a| a.into())
+        .collect()
+}
+
+fn opt(elem: &Element, key: &str) -> Option<String> {
+    elem.attr(key).map(|a| a.into())
+}
+
+fn push_refs(elem: &mut Element, name: &str, ids: &[String]) {
+    ids.iter()
+        .for_each(|id| elem.push(Element::new(name).with_attr("ID", id)));
+}
+
+fn set_opt(elem: &mut Element, key: &str, value: &Option<String>) {
+    if let Some(v) = value {
+        elem.set_attr(key, v);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransmittanceRange {
+    pub cut_in: Option<Length>,
+    pub cut_out: Option<Length>,
+    pub transmittance: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Filter {
+    pub id: String,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub kind: Option<String>,
+    pub filter_wheel: Option<String>,
+    pub transmittance_range: Option<TransmittanceRange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dichroic {
+    pub id: String,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilterSet {
+    pub id: String,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub excitation_filters: Vec<String>,
+    pub dichroic: Option<String>,
+    pub emission_filters: Vec<String>,
+}
+
+// The optical components light passes through for one channel, as IDs
+// into the Instrument
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LightPath {
+    pub excitation_filters: Vec<String>,
+    pub dichroic: Option<String>,
+    pub emission_filters: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Instrument {
+    pub id: String,
+    pub filters: Vec<Filter>,
+    pub dichroics: Vec<Dichroic>,
+    pub filter_sets: Vec<FilterSet>,
+}
+
+impl TransmittanceRange {
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("TransmittanceRange");
+        for (key, value) in [("CutIn", self.cut_in), ("CutOut", self.cut_out)] {
+            if let Some(v) = value {
+                elem.set_attr(key, v.value());
+                elem.set_attr(&format!("{key}Unit"), v.unit().symbol());
+            }
+        }
+        if let Some(t) = self.transmittance {
+            elem.set_attr("Transmittance", t);
+        }
+        elem
+    }
+}
+
+impl Filter {
+    pub fn from_xml(elem: &Element) -> Self {
+        Filter {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            manufacturer: opt(elem, "Manufacturer"),
+            model: opt(elem, "Model"),
+            kind: opt(elem, "Type"),
+            filter_wheel: opt(elem, "FilterWheel"),
+            transmittance_range: elem
+                .child("TransmittanceRange")
+                .map(|t| TransmittanceRange {
+                    cut_in: t.attr_quantity("CutIn", LengthUnit::Nanometer, Length::new),
+                    cut_out: t.attr_quantity("CutOut", LengthUnit::Nanometer, Length::new),
+                    transmittance: t.attr_as("Transmittance"),
+                }),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("Filter").with_attr("ID", &self.id);
+        set_opt(&mut elem, "Manufacturer", &self.manufacturer);
+        set_opt(&mut elem, "Model", &self.model);
+        set_opt(&mut elem, "Type", &self.kind);
+        set_opt(&mut elem, "FilterWheel", &self.filter_wheel);
+        if let Some(range) = &self.transmittance_range {
+            elem.push(range.to_xml());
+        }
+        elem
+    }
+}
+
+impl Dichroic {
+    pub fn from_xml(elem: &Element) -> Self {
+        Dichroic {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            manufacturer: opt(elem, "Manufacturer"),
+            model: opt(elem, "Model"),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("Dichroic").with_attr("ID", &self.id);
+        set_opt(&mut elem, "Manufacturer", &self.manufacturer);
+        set_opt(&mut elem, "Model", &self.model);
+        elem
+    }
+}
+
+impl FilterSet {
+    pub fn from_xml(elem: &Element) -> Self {
+        FilterSet {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            manufacturer: opt(elem, "Manufacturer"),
+            model: opt(elem, "Model"),
+            excitation_filters: refs(elem, "ExcitationFilterRef"),
+            dichroic: elem.child("DichroicRef").and_then(|d| opt(d, "ID")),
+            emission_filters: refs(elem, "EmissionFilterRef"),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("FilterSet").with_attr("ID", &self.id);
+        set_opt(&mut elem, "Manufacturer", &self.manufacturer);
+        set_opt(&mut elem, "Model", &self.model);
+        push_refs(&mut elem, "ExcitationFilterRef", &self.excitation_filters);
+        if let Some(dichroic) = &self.dichroic {
+            elem.push(Element::new("DichroicRef").with_attr("ID", dichroic));
+        }
+        push_refs(&mut elem, "EmissionFilterRef", &self.emission_filters);
+        elem
+    }
+}
+
+impl LightPath {
+    pub fn from_xml(elem: &Element) -> Self {
+        LightPath {
+            excitation_filters: refs(elem, "ExcitationFilterRef"),
+            dichroic: elem.child("DichroicRef").and_then(|d| opt(d, "ID")),
+            emission_filters: refs(elem, "EmissionFilterRef"),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("LightPath");
+        push_refs(&mut elem, "ExcitationFilterRef", &self.excitation_filters);
+        if let Some(dichroic) = &self.dichroic {
+            elem.push(Element::new("DichroicRef").with_attr("ID", dichroic));
+        }
+        push_refs(&mut elem, "EmissionFilterRef", &self.emission_filters);
+        elem
+    }
+}
+
+impl Instrument {
+    pub fn from_xml(elem: &Element) -> Self {
+        Instrument {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            filters: elem
+                .children_named("Filter")
+                .map(Filter::from_xml)
+                .collect(),
+            dichroics: elem
+                .children_named("Dichroic")
+                .map(Dichroic::from_xml)
+                .collect(),
+            filter_sets: elem
+                .children_named("FilterSet")
+                .map(FilterSet::from_xml)
+                .collect(),
+        }
+    }
+
+    // Children in the order of the OME schema
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("Instrument").with_attr("ID", &self.id);
+        self.filter_sets.iter().for_each(|f| elem.push(f.to_xml()));
+        self.filters.iter().for_each(|f| elem.push(f.to_xml()));
+        self.dichroics.iter().for_each(|d| elem.push(d.to_xml()));
+        elem
+    }
+
+    pub fn filter(&self, id: &str) -> Option<&Filter> {
+        self.filters.iter().find(|f| f.id == id)
+    }
+
+    pub fn dichroic(&self, id: &str) -> Option<&Dichroic> {
+        self.dichroics.iter().find(|d| d.id == id)
+    }
+
+    pub fn filter_set(&self, id: &str) -> Option<&FilterSet> {
+        self.filter_sets.iter().find(|f| f.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instrument_from_xml() {
+        let src = r#"<Instrument ID="Instrument:0">
+                <Dichroic ID="Dichroic:0" Model="T495lpxr"/>
+                <Filter ID="Filter:0" Type="BandPass">
+                    <TransmittanceRange CutIn="500" CutOut="0.55" CutOutUnit="µm"/>
+                </Filter>
+                <FilterSet ID="FilterSet:0">
+                    <ExcitationFilterRef ID="Filter:1"/>
+                    <DichroicRef ID="Dichroic:0"/>
+                    <EmissionFilterRef ID="Filter:0"/>
+                </FilterSet>
+            </Instrument>"#;
+
+        let instrument = Instrument::from_xml(&Element::parse(src).unwrap());
+        let set = instrument.filter_set("FilterSet:0").unwrap();
+        let range = instrument
+            .filter(&set.emission_filters[0])
+            .and_then(|f| f.transmittance_range.clone())
+            .unwrap();
+
+        assert_eq!(set.dichroic.as_deref(), Some("Dichroic:0"));
+        assert_eq!(
+            range.cut_in,
+            Some(Length::new(500.0, LengthUnit::Nanometer))
+        );
+        assert_eq!(
+            range
+                .cut_out
+                .unwrap()
+                .value_in(LengthUnit::Nanometer)
+                .map(f64::round),
+            Some(550.0)
+        );
+
+        let written = Element::parse(&instrument.to_xml().to_string()).unwrap();
+        assert_eq!(Instrument::from_xml(&written), instrument);
+        let names = written
+            .elements()
+            .map(|e| e.local_name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["FilterSet", "Filter", "Dichroic"]);
+    }
+
+    #[test]
+    fn light_path_round_trip() {
+        let path = LightPath {
+            excitation_filters: vec!["Filter:1".into()],
+            dichroic: Some("Dichroic:0".into()),
+            emission_filters: vec!["Filter:0".into(), "Filter:2".into()],
+        };
+
+        let written = Element::parse(&path.to_xml().to_string()).unwrap();
+        assert_eq!(LightPath::from_xml(&written), path);
+    }
+}
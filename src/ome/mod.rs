@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use crate::format_in::DimensionOrder;
+
+pub mod annotation;
+pub mod channel;
+pub mod container;
+pub mod hcs;
+pub mod instrument;
+pub mod modulo;
+pub mod namespace;
+pub mod plane;
+pub mod roi;
+pub mod stage_label;
+pub mod tiff_data;
+pub mod timestamp;
+pub mod tools;
+pub mod xml;
+
+pub use annotation::{Annotation, StructuredAnnotations};
+pub use channel::{Channel, Color};
+pub use container::{Dataset, Experimenter, Project};
+pub use hcs::{Plate, PlateAcquisition, Well, WellSample};
+pub use instrument::{Dichroic, Filter, FilterSet, Instrument, LightPath};
+pub use modulo::{Modulo, ModuloAxis};
+pub use namespace::{CustomAnnotation, NamespaceHandler, NamespaceRegistry};
+pub use plane::Plane;
+pub use roi::{Roi, Shape, ShapeKind};
+pub use stage_label::StageLabel;
+pub use tiff_data::{PlaneLocation, PlaneMap, TiffData};
+pub use timestamp::{Timestamp, parse_timestamp};
+pub use xml::{Document, Element};
+
+// Collect the Plane elements of every Image in an OME-XML document, keyed
+// by the index of the Image (i.e. the series)
+pub fn planes_from_xml(root: &Element) -> HashMap<u64, Vec<Plane>> {
+    root.children_named("Image")
+        .enumerate()
+        .map(|(i, image)| {
+            let planes = image
+                .children_named("Pixels")
+                .flat_map(|px| px.children_named("Plane"))
+                .map(Plane::from_xml)
+                .collect();
+
+            (i as u64, planes)
+        })
+        .collect()
+}
+
+// DimensionOrder attribute of each Image's Pixels, keyed by series
+pub fn dimension_orders_from_xml(root: &Element) -> HashMap<u64, DimensionOrder> {
+    root.children_named("Image")
+        .enumerate()
+        .filter_map(|(i, image)| {
+            let order = image.child("Pixels")?.attr("DimensionOrder")?;
+            Some((i as u64, order.parse().ok()?))
+        })
+        .collect()
+}
+
+// Modulo annotations referenced by each Image, keyed by series
+pub fn modulos_from_xml(root: &Element) -> HashMap<u64, Vec<Modulo>> {
+    let annotations = root
+        .children_named("StructuredAnnotations")
+        .flat_map(|sa| sa.children_named("XMLAnnotation"))
+        .filter(|a| {
+            a.attr("Namespace")
+                .is_some_and(|ns| ns.ends_with(modulo::MODULO_NS))
+        })
+        .collect::<Vec<_>>();
+
+    root.children_named("Image")
+        .enumerate()
+        .map(|(i, image)| {
+            let modulos = image
+                .children_named("AnnotationRef")
+                .filter_map(|r| r.attr("ID"))
+                .filter_map(|id| annotations.iter().find(|a| a.attr("ID") == Some(id)))
+                .filter_map(|a| a.child("Value"))
+                .flat_map(modulo::modulos_from_annotation)
+                .collect();
+
+            (i as u64, modulos)
+        })
+        .collect()
+}
+
+pub fn annotations_from_xml(root: &Element) -> StructuredAnnotations {
+    root.child("StructuredAnnotations")
+        .map(StructuredAnnotations::from_xml)
+        .unwrap_or_default()
+}
+
+// AcquisitionDate of each Image, keyed by series
+pub fn acquisition_dates_from_xml(root: &Element) -> HashMap<u64, Timestamp> {
+    root.children_named("Image")
+        .enumerate()
+        .filter_map(|(i, image)| {
+            let date = image.child("AcquisitionDate")?.text();
+            Some((i as u64, parse_timestamp(&date)?))
+        })
+        .collect()
+}
+
+// Channel elements of each Image's Pixels, keyed by series
+pub fn channels_from_xml(root: &Element) -> HashMap<u64, Vec<Channel>> {
+    root.children_named("Image")
+        .enumerate()
+        .map(|(i, image)| {
+            let channels = image
+                .children_named("Pixels")
+                .flat_map(|px| px.children_named("Channel"))
+                .map(Channel::from_xml)
+                .collect();
+
+            (i as u64, channels)
+        })
+        .collect()
+}
+
+// TiffData elements of each Image's Pixels, keyed by series
+pub fn tiff_data_from_xml(root: &Element) -> HashMap<u64, Vec<TiffData>> {
+    root.children_named("Image")
+        .enumerate()
+        .map(|(i, image)| {
+            let tiff_data = image
+                .children_named("Pixels")
+                .flat_map(|px| px.children_named("TiffData"))
+                .map(TiffData::from_xml)
+                .collect();
+
+            (i as u64, tiff_data)
+        })
+        .collect()
+}
+
+pub fn instruments_from_xml(root: &Element) -> Vec<Instrument> {
+    root.children_named("Instrument")
+        .map(Instrument::from_xml)
+        .collect()
+}
+
+pub fn experimenters_from_xml(root: &Element) -> Vec<Experimenter> {
+    root.children_named("Experimenter")
+        .map(Experimenter::from_xml)
+        .collect()
+}
+
+pub fn projects_from_xml(root: &Element) -> Vec<Project> {
+    root.children_named("Project")
+        .map(Project::from_xml)
+        .collect()
+}
+
+pub fn datasets_from_xml(root: &Element) -> Vec<Dataset> {
+    root.children_named("Dataset")
+        .map(Dataset::from_xml)
+        .collect()
+}
+
+pub fn rois_from_xml(root: &Element) -> Vec<Roi> {
+    root.children_named("ROI").map(Roi::from_xml).collect()
+}
+
+pub fn plates_from_xml(root: &Element) -> Vec<Plate> {
+    root.children_named("Plate").map(Plate::from_xml).collect()
+}
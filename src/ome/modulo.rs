@@ -0,0 +1,111 @@
+use crate::ome::xml::Element;
+
+pub const MODULO_NS: &str = "openmicroscopy.org/omero/dimension/modulo";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ModuloAxis {
+    Z,
+    C,
+    T,
+}
+
+// A sub-dimension interleaved along Z, C or T, e.g. FLIM lifetime bins
+// along T or SPIM view angles along Z
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Modulo {
+    pub axis: ModuloAxis,
+    pub kind: String,
+    pub type_description: Option<String>,
+    pub start: f64,
+    pub step: f64,
+    pub end: f64,
+    pub unit: Option<String>,
+    pub labels: Vec<String>,
+}
+
+impl Modulo {
+    pub fn from_xml(elem: &Element) -> Option<Self> {
+        let axis = match elem.local_name() {
+            "ModuloAlongZ" => ModuloAxis::Z,
+            "ModuloAlongC" => ModuloAxis::C,
+            "ModuloAlongT" => ModuloAxis::T,
+            _ => return None,
+        };
+
+        Some(Modulo {
+            axis,
+            kind: elem.attr("Type").unwrap_or("other").into(),
+            type_description: elem.attr("TypeDescription").map(|a| a.into()),
+            start: elem.attr_as("Start").unwrap_or(0.0),
+            step: elem.attr_as("Step").unwrap_or(1.0),
+            end: elem.attr_as("End").unwrap_or(0.0),
+            unit: elem.attr("Unit").map(|a| a.into()),
+            labels: elem
+                .children_named("Label")
+                .map(|l| l.text().trim().to_string())
+                .collect(),
+        })
+    }
+
+    // Number of steps in the sub-dimension
+    pub fn length(&self) -> u64 {
+        if !self.labels.is_empty() {
+            self.labels.len() as u64
+        } else if self.step == 0.0 {
+            1
+        } else {
+            // Tolerate float error, e.g. 0.3 / 0.1 = 2.9999999999999996
+            ((self.end - self.start) / self.step + 1e-9).floor() as u64 + 1
+        }
+    }
+
+    // Value (or label) of each step
+    pub fn values(&self) -> Vec<String> {
+        if !self.labels.is_empty() {
+            return self.labels.clone();
+        }
+
+        (0..self.length())
+            .map(|i| (self.start + i as f64 * self.step).to_string())
+            .collect()
+    }
+}
+
+// Read every ModuloAlong* element from the contents of a Modulo XMLAnnotation
+pub fn modulos_from_annotation(value: &Element) -> Vec<Modulo> {
+    value
+        .children_named("Modulo")
+        .flat_map(|m| m.elements())
+        .filter_map(Modulo::from_xml)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modulo_lengths() {
+        let src = r#"<Value><Modulo namespace="http://www.openmicroscopy.org/Schemas/Additions/2011-09">
+                <ModuloAlongT Type="lifetime" Start="0" Step="0.5" End="3.5" Unit="ns"/>
+                <ModuloAlongZ Type="angle"><Label>0</Label><Label>90</Label></ModuloAlongZ>
+            </Modulo></Value>"#;
+
+        let modulos = modulos_from_annotation(&Element::parse(src).unwrap());
+
+        assert_eq!(modulos.len(), 2);
+        assert_eq!(modulos[0].axis, ModuloAxis::T);
+        assert_eq!(modulos[0].length(), 8);
+        assert_eq!(modulos[1].values(), vec!["0", "90"]);
+
+        let tenths = Modulo {
+            start: 0.0,
+            step: 0.1,
+            end: 0.3,
+            ..modulos[0].clone()
+        };
+        assert_eq!(tenths.length(), 4);
+    }
+}
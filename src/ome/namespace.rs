@@ -0,0 +1,162 @@
+use std::{any::Any, collections::HashMap, io};
+
+use crate::ome::annotation::{Annotation, StructuredAnnotations};
+use crate::ome::xml::Element;
+
+// Parses and serializes the Value of XML annotations in one namespace, so
+// that lab-specific metadata can be handled as typed data. Annotations in
+// unregistered namespaces are still carried through verbatim.
+pub trait NamespaceHandler: Send + Sync {
+    fn namespace(&self) -> &str;
+
+    fn parse(&self, value: &Element) -> io::Result<Box<dyn Any + Send + Sync>>;
+
+    fn serialize(&self, data: &dyn Any) -> io::Result<Element>;
+}
+
+// A decoded annotation; `data` is whatever the handler's parse returned
+#[derive(Debug)]
+pub struct CustomAnnotation {
+    pub id: String,
+    pub namespace: String,
+    pub data: Box<dyn Any + Send + Sync>,
+}
+
+impl CustomAnnotation {
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.data.downcast_ref()
+    }
+}
+
+#[derive(Default)]
+pub struct NamespaceRegistry {
+    handlers: HashMap<String, Box<dyn NamespaceHandler>>,
+}
+
+impl NamespaceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Replaces any handler previously registered for the same namespace
+    pub fn register(&mut self, handler: Box<dyn NamespaceHandler>) {
+        self.handlers.insert(handler.namespace().into(), handler);
+    }
+
+    pub fn handler(&self, namespace: &str) -> Option<&dyn NamespaceHandler> {
+        self.handlers.get(namespace).map(|h| h.as_ref())
+    }
+
+    pub fn namespaces(&self) -> impl Iterator<Item = &str> {
+        self.handlers.keys().map(|k| k.as_str())
+    }
+
+    // Decode every XML annotation whose namespace has a registered handler
+    pub fn decode(&self, annotations: &StructuredAnnotations) -> io::Result<Vec<CustomAnnotation>> {
+        let mut decoded = Vec::new();
+
+        for annotation in &annotations.annotations {
+            let Annotation::Xml {
+                id,
+                namespace: Some(ns),
+                value,
+            } = annotation
+            else {
+                continue;
+            };
+
+            if let Some(handler) = self.handler(ns) {
+                decoded.push(CustomAnnotation {
+                    id: id.clone(),
+                    namespace: ns.clone(),
+                    data: handler.parse(value)?,
+                });
+            }
+        }
+
+        Ok(decoded)
+    }
+
+    // Serialize `data` with the handler for `namespace` and attach it,
+    // returning the ID of the new annotation
+    pub fn encode(
+        &self,
+        annotations: &mut StructuredAnnotations,
+        namespace: &str,
+        data: &dyn Any,
+    ) -> io::Result<String> {
+        let handler = self.handler(namespace).ok_or(io::Error::other(format!(
+            "No handler registered for namespace {namespace}"
+        )))?;
+
+        let mut value = handler.serialize(data)?;
+        value.name = "Value".into();
+
+        Ok(annotations.add_xml_annotation(Some(namespace), value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Incubation {
+        temperature: f64,
+    }
+
+    struct IncubationHandler;
+
+    impl NamespaceHandler for IncubationHandler {
+        fn namespace(&self) -> &str {
+            "lab.example/incubation"
+        }
+
+        fn parse(&self, value: &Element) -> io::Result<Box<dyn Any + Send + Sync>> {
+            let temperature = value
+                .child("Incubation")
+                .and_then(|i| i.attr_as("Temperature"))
+                .ok_or(io::Error::other("Missing temperature"))?;
+
+            Ok(Box::new(Incubation { temperature }))
+        }
+
+        fn serialize(&self, data: &dyn Any) -> io::Result<Element> {
+            let data = data
+                .downcast_ref::<Incubation>()
+                .ok_or(io::Error::other("Not an Incubation"))?;
+
+            let mut value = Element::new("Value");
+            value.push(Element::new("Incubation").with_attr("Temperature", data.temperature));
+            Ok(value)
+        }
+    }
+
+    #[test]
+    fn registered_namespace_round_trip() {
+        let mut registry = NamespaceRegistry::new();
+        registry.register(Box::new(IncubationHandler));
+
+        let mut sa = StructuredAnnotations::default();
+        sa.add_map_annotation(None, vec![]);
+        let id = registry
+            .encode(
+                &mut sa,
+                "lab.example/incubation",
+                &Incubation { temperature: 37.0 },
+            )
+            .unwrap();
+
+        let reparsed =
+            StructuredAnnotations::from_xml(&Element::parse(&sa.to_xml().to_string()).unwrap());
+        let decoded = registry.decode(&reparsed).unwrap();
+
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].id, id);
+        assert_eq!(
+            decoded[0].downcast_ref::<Incubation>(),
+            Some(&Incubation { temperature: 37.0 })
+        );
+        assert!(registry.encode(&mut sa, "unknown", &()).is_err());
+    }
+}
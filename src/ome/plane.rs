@@ -0,0 +1,63 @@
+use crate::ome::xml::Element;
+use crate::units::{Length, LengthUnit, Time, TimeUnit};
+
+// Acquisition data for a single (z, c, t) plane
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plane {
+    pub the_z: u64,
+    pub the_c: u64,
+    pub the_t: u64,
+    pub delta_t: Option<Time>,
+    pub exposure_time: Option<Time>,
+    pub position_x: Option<Length>,
+    pub position_y: Option<Length>,
+    pub position_z: Option<Length>,
+}
+
+impl Plane {
+    pub fn new(the_z: u64, the_c: u64, the_t: u64) -> Self {
+        Plane {
+            the_z,
+            the_c,
+            the_t,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_xml(elem: &Element) -> Self {
+        Plane {
+            the_z: elem.attr_as("TheZ").unwrap_or(0),
+            the_c: elem.attr_as("TheC").unwrap_or(0),
+            the_t: elem.attr_as("TheT").unwrap_or(0),
+            delta_t: elem.attr_quantity("DeltaT", TimeUnit::Second, Time::new),
+            exposure_time: elem.attr_quantity("ExposureTime", TimeUnit::Second, Time::new),
+            position_x: elem.attr_quantity("PositionX", LengthUnit::ReferenceFrame, Length::new),
+            position_y: elem.attr_quantity("PositionY", LengthUnit::ReferenceFrame, Length::new),
+            position_z: elem.attr_quantity("PositionZ", LengthUnit::ReferenceFrame, Length::new),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plane_from_xml() {
+        let elem = Element::parse(
+            r#"<Plane TheZ="2" TheC="1" TheT="3" DeltaT="500" DeltaTUnit="ms" PositionX="-12.25"/>"#,
+        )
+        .unwrap();
+
+        let plane = Plane::from_xml(&elem);
+
+        assert_eq!((plane.the_z, plane.the_c, plane.the_t), (2, 1, 3));
+        assert_eq!(plane.delta_t.unwrap().value_in(TimeUnit::Second), Some(0.5));
+        assert_eq!(
+            plane.position_x,
+            Some(Length::new(-12.25, LengthUnit::ReferenceFrame))
+        );
+        assert_eq!(plane.exposure_time, None);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::ome::xml::Element;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ShapeKind {
+    Rectangle {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    Ellipse {
+        x: f64,
+        y: f64,
+        radius_x: f64,
+        radius_y: f64,
+    },
+    Point {
+        x: f64,
+        y: f64,
+    },
+    Line {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    },
+    Polygon {
+        points: Vec<(f64, f64)>,
+    },
+    Polyline {
+        points: Vec<(f64, f64)>,
+    },
+    // BinData is kept as the base64 text found in the document
+    Mask {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        bin_data: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Shape {
+    pub id: String,
+    pub the_z: Option<u64>,
+    pub the_c: Option<u64>,
+    pub the_t: Option<u64>,
+    pub text: Option<String>,
+    pub kind: ShapeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Roi {
+    pub id: String,
+    pub name: Option<String>,
+    pub shapes: Vec<Shape>,
+}
+
+impl Shape {
+    pub fn new(id: &str, kind: ShapeKind) -> Self {
+        Shape {
+            id: id.into(),
+            the_z: None,
+            the_c: None,
+            the_t: None,
+            text: None,
+            kind,
+        }
+    }
+
+    pub fn from_xml(elem: &Element) -> Option<Self> {
+        let f = |key: &str| elem.attr_as::<f64>(key).unwrap_or(0.0);
+
+        let kind = match elem.local_name() {
+            "Rectangle" => ShapeKind::Rectangle {
+                x: f("X"),
+                y: f("Y"),
+                width: f("Width"),
+                height: f("Height"),
+            },
+            "Ellipse" => ShapeKind::Ellipse {
+                x: f("X"),
+                y: f("Y"),
+                radius_x: f("RadiusX"),
+                radius_y: f("RadiusY"),
+            },
+            "Point" => ShapeKind::Point {
+                x: f("X"),
+                y: f("Y"),
+            },
+            "Line" => ShapeKind::Line {
+                x1: f("X1"),
+                y1: f("Y1"),
+                x2: f("X2"),
+                y2: f("Y2"),
+            },
+            "Polygon" => ShapeKind::Polygon {
+                points: parse_points(elem.attr("Points").unwrap_or("")),
+            },
+            "Polyline" => ShapeKind::Polyline {
+                points: parse_points(elem.attr("Points").unwrap_or("")),
+            },
+            "Mask" => ShapeKind::Mask {
+                x: f("X"),
+                y: f("Y"),
+                width: f("Width"),
+                height: f("Height"),
+                bin_data: elem
+                    .child("BinData")
+                    .map(|b| b.text().trim().to_string())
+                    .unwrap_or_default(),
+            },
+            _ => return None,
+        };
+
+        Some(Shape {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            the_z: elem.attr_as("TheZ"),
+            the_c: elem.attr_as("TheC"),
+            the_t: elem.attr_as("TheT"),
+            text: elem.attr("Text").map(|a| a.into()),
+            kind,
+        })
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = match &self.kind {
+            ShapeKind::Rectangle {
+                x,
+                y,
+                width,
+                height,
+            } => Element::new("Rectangle")
+                .with_attr("X", x)
+                .with_attr("Y", y)
+                .with_attr("Width", width)
+                .with_attr("Height", height),
+            ShapeKind::Ellipse {
+                x,
+                y,
+                radius_x,
+                radius_y,
+            } => Element::new("Ellipse")
+                .with_attr("X", x)
+                .with_attr("Y", y)
+                .with_attr("RadiusX", radius_x)
+                .with_attr("RadiusY", radius_y),
+            ShapeKind::Point { x, y } => Element::new("Point").with_attr("X", x).with_attr("Y", y),
+            ShapeKind::Line { x1, y1, x2, y2 } => Element::new("Line")
+                .with_attr("X1", x1)
+                .with_attr("Y1", y1)
+                .with_attr("X2", x2)
+                .with_attr("Y2", y2),
+            ShapeKind::Polygon { points } => {
+                Element::new("Polygon").with_attr("Points", format_points(points))
+            }
+            ShapeKind::Polyline { points } => {
+                Element::new("Polyline").with_attr("Points", format_points(points))
+            }
+            ShapeKind::Mask {
+                x,
+                y,
+                width,
+                height,
+                bin_data,
+            } => {
+                let mut mask = Element::new("Mask")
+                    .with_attr("X", x)
+                    .with_attr("Y", y)
+                    .with_attr("Width", width)
+                    .with_attr("Height", height);
+                let mut bin = Element::new("BinData").with_attr("Length", decoded_len(bin_data));
+                bin.push_text(bin_data);
+                mask.push(bin);
+                mask
+            }
+        };
+
+        elem.set_attr("ID", &self.id);
+        [
+            ("TheZ", self.the_z),
+            ("TheC", self.the_c),
+            ("TheT", self.the_t),
+        ]
+        .into_iter()
+        .filter_map(|(k, v)| v.map(|v| (k, v)))
+        .for_each(|(k, v)| elem.set_attr(k, v));
+        if let Some(text) = &self.text {
+            elem.set_attr("Text", text);
+        }
+
+        elem
+    }
+}
+
+impl Roi {
+    pub fn from_xml(elem: &Element) -> Self {
+        Roi {
+            id: elem.attr("ID").unwrap_or_default().into(),
+            name: elem.attr("Name").map(|a| a.into()),
+            shapes: elem
+                .children_named("Union")
+                .flat_map(|u| u.elements())
+                .filter_map(Shape::from_xml)
+                .collect(),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("ROI").with_attr("ID", &self.id);
+        if let Some(name) = &self.name {
+            elem.set_attr("Name", name);
+        }
+
+        let mut union = Element::new("Union");
+        self.shapes.iter().for_each(|s| union.push(s.to_xml()));
+        elem.push(union);
+
+        elem
+    }
+}
+
+// OME-XML points are "x1,y1 x2,y2 ..."
+fn parse_points(s: &str) -> Vec<(f64, f64)> {
+    s.split_whitespace()
+        .filter_map(|p| {
+            let (x, y) = p.split_once(',')?;
+            Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+// Bytes base64 text decodes to, which BinData's Length counts
+fn decoded_len(base64: &str) -> usize {
+    let digits = base64.bytes().filter(|b| !b.is_ascii_whitespace());
+    let padding = base64
+        .trim_end()
+        .bytes()
+        .rev()
+        .take_while(|b| *b == b'=')
+        .count();
+
+    (digits.count() - padding) * 6 / 8
+}
+
+fn format_points(points: &[(f64, f64)]) -> String {
+    points
+        .iter()
+        .map(|(x, y)| format!("{x},{y}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roi_from_xml() {
+        let src = r#"<ROI ID="ROI:0" Name="cell">
+                <Union>
+                    <Rectangle ID="Shape:0" X="1" Y="2" Width="3" Height="4" TheZ="5"/>
+                    <Polygon ID="Shape:1" Points="0,0 10,0 10,10.5"/>
+                </Union>
+            </ROI>"#;
+
+        let roi = Roi::from_xml(&Element::parse(src).unwrap());
+
+        assert_eq!(roi.name.as_deref(), Some("cell"));
+        assert_eq!(roi.shapes.len(), 2);
+        assert_eq!(roi.shapes[0].the_z, Some(5));
+        assert_eq!(
+            roi.shapes[1].kind,
+            ShapeKind::Polygon {
+                points: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.5)]
+            }
+        );
+    }
+
+    #[test]
+    fn roi_round_trip() {
+        let mut shape = Shape::new(
+            "Shape:0",
+            ShapeKind::Mask {
+                x: 0.0,
+                y: 0.0,
+                width: 2.0,
+                height: 2.0,
+                bin_data: "8A==".into(),
+            },
+        );
+        shape.the_t = Some(3);
+
+        let roi = Roi {
+            id: "ROI:1".into(),
+            name: None,
+            shapes: vec![shape],
+        };
+
+        let parsed = Element::parse(&roi.to_xml().to_string()).unwrap();
+        let bin = parsed
+            .child("Union")
+            .and_then(|u| u.child("Mask"))
+            .and_then(|m| m.child("BinData"))
+            .unwrap();
+
+        assert_eq!(bin.attr_as::<usize>("Length"), Some(1));
+        assert_eq!(Roi::from_xml(&parsed), roi);
+    }
+}
@@ -0,0 +1,53 @@
+use crate::ome::xml::Element;
+use crate::units::{Length, LengthUnit};
+
+// Named stage position of an Image, e.g. one field of a multi-position
+// acquisition
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StageLabel {
+    pub name: String,
+    pub x: Option<Length>,
+    pub y: Option<Length>,
+    pub z: Option<Length>,
+}
+
+impl StageLabel {
+    pub fn from_xml(elem: &Element) -> Self {
+        StageLabel {
+            name: elem.attr("Name").unwrap_or_default().into(),
+            x: elem.attr_quantity("X", LengthUnit::ReferenceFrame, Length::new),
+            y: elem.attr_quantity("Y", LengthUnit::ReferenceFrame, Length::new),
+            z: elem.attr_quantity("Z", LengthUnit::ReferenceFrame, Length::new),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("StageLabel").with_attr("Name", &self.name);
+        for (key, value) in [("X", self.x), ("Y", self.y), ("Z", self.z)] {
+            if let Some(v) = value {
+                elem.set_attr(key, v.value());
+                elem.set_attr(&format!("{key}Unit"), v.unit().symbol());
+            }
+        }
+        elem
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stage_label_round_trip() {
+        let elem =
+            Element::parse(r#"<StageLabel Name="Pos 3" X="1500" XUnit="nm" Y="-2.5"/>"#).unwrap();
+
+        let label = StageLabel::from_xml(&elem);
+
+        assert_eq!(label.name, "Pos 3");
+        assert!((label.x.unwrap().value_in(LengthUnit::Micrometer).unwrap() - 1.5).abs() < 1e-9);
+        assert_eq!(label.z, None);
+        assert_eq!(StageLabel::from_xml(&label.to_xml()), label);
+    }
+}
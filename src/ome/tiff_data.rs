@@ -0,0 +1,198 @@
+use std::io;
+
+use crate::format_in::DimensionOrder;
+use crate::ome::xml::Element;
+
+// A TiffData element: `plane_count` consecutive planes, starting at
+// (first_z, first_c, first_t) in the Pixels dimension order, stored in
+// consecutive IFDs from `ifd` of `file_name` (or of the file holding the
+// OME-XML when absent)
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TiffData {
+    pub first_z: u64,
+    pub first_c: u64,
+    pub first_t: u64,
+    pub ifd: u64,
+    pub plane_count: Option<u64>,
+    pub file_name: Option<String>,
+    pub uuid: Option<String>,
+}
+
+impl TiffData {
+    pub fn from_xml(elem: &Element) -> Self {
+        let uuid = elem.child("UUID");
+
+        TiffData {
+            first_z: elem.attr_as("FirstZ").unwrap_or(0),
+            first_c: elem.attr_as("FirstC").unwrap_or(0),
+            first_t: elem.attr_as("FirstT").unwrap_or(0),
+            ifd: elem.attr_as("IFD").unwrap_or(0),
+            plane_count: elem.attr_as("PlaneCount"),
+            file_name: uuid.and_then(|u| u.attr("FileName")).map(|a| a.into()),
+            uuid: uuid
+                .map(|u| u.text().trim().to_string())
+                .filter(|u| !u.is_empty()),
+        }
+    }
+
+    pub fn to_xml(&self) -> Element {
+        let mut elem = Element::new("TiffData")
+            .with_attr("FirstZ", self.first_z)
+            .with_attr("FirstC", self.first_c)
+            .with_attr("FirstT", self.first_t)
+            .with_attr("IFD", self.ifd);
+        if let Some(count) = self.plane_count {
+            elem.set_attr("PlaneCount", count);
+        }
+
+        if self.file_name.is_some() || self.uuid.is_some() {
+            let mut uuid = Element::new("UUID");
+            if let Some(name) = &self.file_name {
+                uuid.set_attr("FileName", name);
+            }
+            if let Some(id) = &self.uuid {
+                uuid.push_text(id);
+            }
+            elem.push(uuid);
+        }
+
+        elem
+    }
+}
+
+// Where a single plane is stored
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaneLocation {
+    // None when the plane is in the file holding the OME-XML
+    pub file_name: Option<String>,
+    pub uuid: Option<String>,
+    pub ifd: u64,
+}
+
+// The TiffData of one series resolved to linear plane index ranges, answering
+// "which file and IFD holds plane (z, c, t)"
+#[derive(Debug, Clone)]
+pub struct PlaneMap {
+    order: DimensionOrder,
+    sizes: (u64, u64, u64),
+    ranges: Vec<(u64, u64, TiffData)>,
+}
+
+impl PlaneMap {
+    // `sizes` are (size_z, size_c, size_t) of the series
+    pub fn new(
+        tiff_data: &[TiffData],
+        order: DimensionOrder,
+        sizes: (u64, u64, u64),
+    ) -> io::Result<Self> {
+        let n_planes = sizes.0 * sizes.1 * sizes.2;
+        let mut ranges = Vec::with_capacity(tiff_data.len());
+
+        for td in tiff_data {
+            let start = order.zct_to_index(sizes, (td.first_z, td.first_c, td.first_t))?;
+            // A lone TiffData without a PlaneCount covers every remaining plane
+            let count = td.plane_count.unwrap_or(if tiff_data.len() == 1 {
+                n_planes - start
+            } else {
+                1
+            });
+
+            ranges.push((start, count, td.clone()));
+        }
+
+        Ok(PlaneMap {
+            order,
+            sizes,
+            ranges,
+        })
+    }
+
+    pub fn locate_index(&self, index: u64) -> Option<PlaneLocation> {
+        self.ranges
+            .iter()
+            .find(|(start, count, _)| (*start..start + count).contains(&index))
+            .map(|(start, _, td)| PlaneLocation {
+                file_name: td.file_name.clone(),
+                uuid: td.uuid.clone(),
+                ifd: td.ifd + index - start,
+            })
+    }
+
+    pub fn locate(&self, z: u64, c: u64, t: u64) -> Option<PlaneLocation> {
+        let index = self.order.zct_to_index(self.sizes, (z, c, t)).ok()?;
+        self.locate_index(index)
+    }
+
+    // Distinct files referenced, in order of first appearance
+    pub fn files(&self) -> Vec<&str> {
+        let mut files: Vec<&str> = Vec::new();
+        for name in self
+            .ranges
+            .iter()
+            .filter_map(|(_, _, td)| td.file_name.as_deref())
+        {
+            if !files.contains(&name) {
+                files.push(name);
+            }
+        }
+        files
+    }
+
+    // Planes with no TiffData describing where they are stored
+    pub fn missing_planes(&self) -> Vec<u64> {
+        let n_planes = self.sizes.0 * self.sizes.1 * self.sizes.2;
+        (0..n_planes)
+            .filter(|i| self.locate_index(*i).is_none())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plane_map_locates_planes() {
+        // Two channels in separate files, three timepoints each
+        let tiff_data = (0..2)
+            .map(|c| TiffData {
+                first_c: c,
+                plane_count: Some(1),
+                file_name: Some(format!("c{c}.tif")),
+                ..Default::default()
+            })
+            .chain((0..2).map(|c| TiffData {
+                first_c: c,
+                first_t: 1,
+                ifd: 1,
+                plane_count: Some(1),
+                file_name: Some(format!("c{c}.tif")),
+                ..Default::default()
+            }))
+            .collect::<Vec<_>>();
+
+        let map = PlaneMap::new(&tiff_data, DimensionOrder::XYCZT, (1, 2, 3)).unwrap();
+        let loc = map.locate(0, 1, 1).unwrap();
+
+        assert_eq!(loc.file_name.as_deref(), Some("c1.tif"));
+        assert_eq!(loc.ifd, 1);
+        assert_eq!(map.locate(0, 0, 2), None);
+        assert_eq!(map.files(), vec!["c0.tif", "c1.tif"]);
+        assert_eq!(map.missing_planes(), vec![4, 5]);
+    }
+
+    #[test]
+    fn tiff_data_round_trip() {
+        let src = r#"<TiffData FirstC="1" IFD="3" PlaneCount="2">
+                <UUID FileName="a.ome.tif">urn:uuid:1234</UUID>
+            </TiffData>"#;
+
+        let td = TiffData::from_xml(&Element::parse(src).unwrap());
+
+        assert_eq!((td.first_c, td.ifd, td.plane_count), (1, 3, Some(2)));
+        assert_eq!(td.file_name.as_deref(), Some("a.ome.tif"));
+        assert_eq!(td.uuid.as_deref(), Some("urn:uuid:1234"));
+        assert_eq!(TiffData::from_xml(&td.to_xml()), td);
+    }
+}
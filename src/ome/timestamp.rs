@@ -0,0 +1,47 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+pub type Timestamp = DateTime<Utc>;
+
+// Formats seen in the wild, tried in order. Timestamps without a zone are
+// assumed to be UTC.
+const NAIVE_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    // TIFF DateTime tag
+    "%Y:%m:%d %H:%M:%S",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y/%m/%d %H:%M:%S",
+];
+
+pub fn parse_timestamp(s: &str) -> Option<Timestamp> {
+    let s = s.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    NAIVE_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .map(|dt| dt.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_known_formats() {
+        let expected = "2024-03-01T12:30:05+00:00";
+
+        for s in [
+            "2024:03:01 12:30:05\0",
+            "2024-03-01T12:30:05",
+            "2024-03-01T13:30:05+01:00",
+            "2024-03-01T12:30:05Z",
+        ] {
+            assert_eq!(parse_timestamp(s).unwrap().to_rfc3339(), expected, "{s}");
+        }
+
+        assert!(parse_timestamp("yesterday").is_none());
+    }
+}
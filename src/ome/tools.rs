@@ -0,0 +1,167 @@
+use crate::format_in::{ByteOrder, Metadata, PixelType};
+use crate::ome::xml::Element;
+
+pub const OME_NS: &str = "http://www.openmicroscopy.org/Schemas/OME/2016-06";
+
+// Build the smallest valid OME document describing the core metadata of
+// every series (Image/Pixels/Channel with IDs, sizes and pixel type), plus
+// whatever containers, plates, instruments, ROIs and annotations the reader
+// found.
+// TiffData assumes the planes of each series are written to consecutive IFDs.
+pub fn minimal_ome(md: &Metadata) -> Element {
+    let mut root = Element::new("OME").with_attr("xmlns", OME_NS);
+    let big_endian = *md.byte_order() == ByteOrder::BE;
+    let mut ifd = 0;
+
+    // Top level elements must follow the order of the OME schema
+    let global = md.global();
+    global.projects().iter().for_each(|p| root.push(p.to_xml()));
+    global.datasets().iter().for_each(|d| root.push(d.to_xml()));
+    global.plates().iter().for_each(|p| root.push(p.to_xml()));
+    global
+        .experimenters()
+        .iter()
+        .for_each(|e| root.push(e.to_xml()));
+    global
+        .instruments()
+        .iter()
+        .for_each(|i| root.push(i.to_xml()));
+
+    for i in 0..md.series_count() {
+        let Ok(series) = md.series(i) else { continue };
+        let dim = series.dimensions();
+        let pixel_type = series.pixel_type(0).unwrap_or(PixelType::U8);
+        let significant_bits = series.significant_bits(0).unwrap_or(pixel_type.bits());
+        let plane_count = dim.size_z() * dim.size_t() * dim.size_c();
+
+        let mut pixels = Element::new("Pixels")
+            .with_attr("ID", format!("Pixels:{i}"))
+            .with_attr("DimensionOrder", format!("{:?}", series.dimension_order()))
+            .with_attr("Type", pixel_type.ome_name())
+            .with_attr("SignificantBits", significant_bits)
+            .with_attr("Interleaved", series.is_interleaved())
+            .with_attr("BigEndian", big_endian)
+            .with_attr("SizeX", dim.size_x())
+            .with_attr("SizeY", dim.size_y())
+            .with_attr("SizeZ", dim.size_z())
+            .with_attr("SizeC", dim.size_c())
+            .with_attr("SizeT", dim.size_t());
+
+        // Interleaved samples form a single multi-sample (e.g. RGB) channel
+        let (n_channels, samples) = if series.is_interleaved() && dim.size_c() > 1 {
+            (1, dim.size_c())
+        } else {
+            (dim.size_c(), 1)
+        };
+
+        for c in 0..n_channels {
+            let mut channel = Element::new("Channel")
+                .with_attr("ID", format!("Channel:{i}:{c}"))
+                .with_attr("SamplesPerPixel", samples);
+            if let Some(path) = series.channel(c).and_then(|ch| ch.light_path.as_ref()) {
+                channel.push(path.to_xml());
+            }
+            pixels.push(channel);
+        }
+
+        let ifd_planes = plane_count / samples;
+        pixels.push(
+            Element::new("TiffData")
+                .with_attr("IFD", ifd)
+                .with_attr("PlaneCount", ifd_planes),
+        );
+        ifd += ifd_planes;
+
+        let mut image = Element::new("Image")
+            .with_attr("ID", series.image_id().unwrap_or(&format!("Image:{i}")))
+            .with_attr("Name", series.name().unwrap_or(&format!("Series {i}")));
+        if let Some(experimenter) = series.experimenter() {
+            image.push(Element::new("ExperimenterRef").with_attr("ID", experimenter));
+        }
+        if let Some(label) = series.stage_label() {
+            image.push(label.to_xml());
+        }
+        image.push(pixels);
+        root.push(image);
+    }
+
+    if !global.annotations().annotations.is_empty() {
+        root.push(global.annotations().to_xml());
+    }
+    global.rois().iter().for_each(|r| root.push(r.to_xml()));
+
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_in::{Dim, GlobalMetadata, SeriesMetadata};
+    use crate::ome::{Channel, Experimenter, Instrument, LightPath};
+
+    #[test]
+    fn minimal_ome_for_rgb_series() {
+        let md = Metadata {
+            global: GlobalMetadata::new(ByteOrder::BE),
+            series: vec![SeriesMetadata::new(
+                Dim::from_whc(10, 20, 3),
+                vec![PixelType::U16; 3],
+                true,
+            )],
+        };
+
+        let root = minimal_ome(&md);
+        let pixels = root.child("Image").unwrap().child("Pixels").unwrap();
+
+        assert_eq!(pixels.attr("Type"), Some("uint16"));
+        assert_eq!(pixels.attr("SizeY"), Some("20"));
+        assert_eq!(pixels.attr("SizeC"), Some("3"));
+        assert_eq!(pixels.attr("BigEndian"), Some("true"));
+        assert_eq!(pixels.children_named("Channel").count(), 1);
+        assert_eq!(
+            pixels.child("Channel").unwrap().attr("SamplesPerPixel"),
+            Some("3")
+        );
+    }
+
+    #[test]
+    fn minimal_ome_keeps_instruments() {
+        let mut series = SeriesMetadata::new(Dim::from_whc(4, 4, 1), vec![PixelType::U8], false);
+        series.channels = vec![Channel {
+            light_path: Some(LightPath {
+                dichroic: Some("Dichroic:0".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }];
+        let mut global = GlobalMetadata::new(ByteOrder::LE);
+        global.instruments = vec![Instrument {
+            id: "Instrument:0".into(),
+            ..Default::default()
+        }];
+        global.experimenters = vec![Experimenter {
+            id: "Experimenter:0".into(),
+            ..Default::default()
+        }];
+
+        let root = minimal_ome(&Metadata {
+            global,
+            series: vec![series],
+        });
+        let names = root.elements().map(|e| e.local_name()).collect::<Vec<_>>();
+        let channel = root
+            .child("Image")
+            .and_then(|i| i.child("Pixels"))
+            .and_then(|p| p.child("Channel"))
+            .unwrap();
+
+        assert_eq!(names, ["Experimenter", "Instrument", "Image"]);
+        assert_eq!(
+            channel
+                .child("LightPath")
+                .and_then(|l| l.child("DichroicRef"))
+                .and_then(|d| d.attr("ID")),
+            Some("Dichroic:0")
+        );
+    }
+}
@@ -0,0 +1,526 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, Error},
+};
+
+// A minimal XML document model, sufficient for OME-XML as embedded in
+// TIFF ImageDescription tags and companion files. Parsed documents remember
+// how they were written, so serializing an unmodified (or partly modified)
+// document reproduces the untouched parts byte for byte.
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Node {
+    Element(Element),
+    Text(String),
+    Comment(String),
+    CData(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Node>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    source: Source,
+}
+
+// How an element was written in the source document. Raw forms are only
+// used while the parsed value is unchanged, so edits serialize normally.
+#[derive(Debug, Clone, Default)]
+struct Source {
+    // (key, value as parsed, attribute as written with leading whitespace)
+    attributes: Vec<(String, String, String)>,
+    // (child index, text as parsed, text as written)
+    texts: Vec<(usize, String, String)>,
+    // Whitespace before the '>' or '/>' of the start tag
+    start_tag_end: String,
+    // Whitespace before the '>' of the end tag, None when self-closing
+    end_tag: Option<String>,
+}
+
+// Formatting never affects equality
+impl PartialEq for Source {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+// A complete document: the root element plus the declaration, comments and
+// doctype around it, kept verbatim
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub prolog: String,
+    pub root: Element,
+    pub epilog: String,
+}
+
+impl Document {
+    pub fn new(root: Element) -> Self {
+        Document {
+            prolog: "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".into(),
+            root,
+            epilog: String::new(),
+        }
+    }
+
+    pub fn parse(src: &str) -> io::Result<Document> {
+        let mut p = Parser { src, pos: 0 };
+        p.skip_misc()?;
+        let prolog = src[..p.pos].to_string();
+
+        let root = p.element()?;
+        let root_end = p.pos;
+        p.skip_misc()?;
+
+        if p.pos < src.len() {
+            return Err(Error::other("Trailing content after root element"));
+        }
+
+        Ok(Document {
+            prolog,
+            root,
+            epilog: src[root_end..].to_string(),
+        })
+    }
+}
+
+impl Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.prolog, self.root, self.epilog)
+    }
+}
+
+impl Element {
+    pub fn new(name: &str) -> Self {
+        Element {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    // Element name with any namespace prefix removed
+    pub fn local_name(&self) -> &str {
+        local(&self.name)
+    }
+
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| local(k) == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn attr_as<T: std::str::FromStr>(&self, key: &str) -> Option<T> {
+        self.attr(key).and_then(|v| v.trim().parse().ok())
+    }
+
+    // A numeric attribute with its companion "<key>Unit" attribute, as used
+    // throughout OME-XML for physical quantities
+    pub fn attr_quantity<Q, U: std::str::FromStr>(
+        &self,
+        key: &str,
+        default_unit: U,
+        new: fn(f64, U) -> Q,
+    ) -> Option<Q> {
+        let value = self.attr_as::<f64>(key)?;
+        let unit = self
+            .attr(&format!("{key}Unit"))
+            .and_then(|u| u.parse().ok())
+            .unwrap_or(default_unit);
+
+        Some(new(value, unit))
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|n| match n {
+            Node::Element(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    pub fn elements_mut(&mut self) -> impl Iterator<Item = &mut Element> {
+        self.children.iter_mut().filter_map(|n| match n {
+            Node::Element(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.elements().filter(move |e| e.local_name() == name)
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|e| e.local_name() == name)
+    }
+
+    // Concatenated text content of direct children
+    pub fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|n| match n {
+                Node::Text(t) | Node::CData(t) => Some(t.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn with_attr(mut self, key: &str, value: impl ToString) -> Self {
+        self.set_attr(key, value);
+        self
+    }
+
+    pub fn set_attr(&mut self, key: &str, value: impl ToString) {
+        match self.attributes.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.attributes.push((key.into(), value.to_string())),
+        }
+    }
+
+    pub fn push(&mut self, child: Element) {
+        self.children.push(Node::Element(child));
+    }
+
+    pub fn push_text(&mut self, text: &str) {
+        self.children.push(Node::Text(text.into()));
+    }
+
+    // Parse the root element of a document, discarding the prolog
+    pub fn parse(src: &str) -> io::Result<Element> {
+        Document::parse(src).map(|d| d.root)
+    }
+}
+
+impl Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let src = &self.source;
+
+        write!(f, "<{}", self.name)?;
+        for (k, v) in &self.attributes {
+            match src.attributes.iter().find(|(sk, sv, _)| sk == k && sv == v) {
+                Some((_, _, raw)) => write!(f, "{raw}")?,
+                None => write!(f, " {k}=\"{}\"", escape(v))?,
+            }
+        }
+        write!(f, "{}", src.start_tag_end)?;
+
+        if self.children.is_empty() && src.end_tag.is_none() {
+            return write!(f, "/>");
+        }
+
+        write!(f, ">")?;
+        for (i, child) in self.children.iter().enumerate() {
+            match child {
+                Node::Element(e) => write!(f, "{e}")?,
+                Node::Text(t) => match src.texts.iter().find(|(si, st, _)| *si == i && st == t) {
+                    Some((_, _, raw)) => write!(f, "{raw}")?,
+                    None => write!(f, "{}", escape(t))?,
+                },
+                Node::Comment(c) => write!(f, "<!--{c}-->")?,
+                Node::CData(c) => write!(f, "<![CDATA[{c}]]>")?,
+            }
+        }
+        write!(
+            f,
+            "</{}{}>",
+            self.name,
+            src.end_tag.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+fn local(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_ws(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.src.len() - trimmed.len();
+    }
+
+    fn skip_past(&mut self, delim: &str) -> io::Result<()> {
+        let idx = self
+            .rest()
+            .find(delim)
+            .ok_or(Error::other(format!("Unterminated XML, expected {delim}")))?;
+        self.pos += idx + delim.len();
+        Ok(())
+    }
+
+    // Skip prolog, comments, doctype and whitespace outside the root
+    fn skip_misc(&mut self) -> io::Result<()> {
+        loop {
+            self.skip_ws();
+            if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<!") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> io::Result<String> {
+        let end = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '='))
+            .unwrap_or(self.rest().len());
+
+        if end == 0 {
+            return Err(Error::other(format!("Expected XML name at {}", self.pos)));
+        }
+
+        let name = self.rest()[..end].to_string();
+        self.pos += end;
+        Ok(name)
+    }
+
+    fn element(&mut self) -> io::Result<Element> {
+        if !self.rest().starts_with('<') {
+            return Err(Error::other(format!("Expected element at {}", self.pos)));
+        }
+        self.pos += 1;
+
+        let mut elem = Element::new(&self.name()?);
+
+        loop {
+            let start = self.pos;
+            self.skip_ws();
+            if self.rest().starts_with("/>") {
+                elem.source.start_tag_end = self.src[start..self.pos].to_string();
+                self.pos += 2;
+                return Ok(elem);
+            } else if self.rest().starts_with('>') {
+                elem.source.start_tag_end = self.src[start..self.pos].to_string();
+                self.pos += 1;
+                break;
+            }
+
+            let key = self.name()?;
+            self.skip_ws();
+            if !self.rest().starts_with('=') {
+                return Err(Error::other(format!("Expected '=' after {key}")));
+            }
+            self.pos += 1;
+            self.skip_ws();
+
+            let quote = self
+                .rest()
+                .chars()
+                .next()
+                .filter(|c| *c == '"' || *c == '\'')
+                .ok_or(Error::other(format!("Unquoted attribute {key}")))?;
+            self.pos += 1;
+
+            let end = self
+                .rest()
+                .find(quote)
+                .ok_or(Error::other(format!("Unterminated attribute {key}")))?;
+            let value = unescape(&self.rest()[..end]);
+            self.pos += end + 1;
+
+            let raw = self.src[start..self.pos].to_string();
+            elem.source
+                .attributes
+                .push((key.clone(), value.clone(), raw));
+            elem.attributes.push((key, value));
+        }
+
+        loop {
+            if self.rest().starts_with("</") {
+                self.pos += 2;
+                let close = self.name()?;
+                if close != elem.name {
+                    return Err(Error::other(format!(
+                        "Mismatched closing tag: {} / {close}",
+                        elem.name
+                    )));
+                }
+                let start = self.pos;
+                self.skip_past(">")?;
+                elem.source.end_tag = Some(self.src[start..self.pos - 1].to_string());
+                return Ok(elem);
+            } else if self.rest().starts_with("<!--") {
+                self.pos += 4;
+                let end = self
+                    .rest()
+                    .find("-->")
+                    .ok_or(Error::other("Unterminated comment"))?;
+                elem.children
+                    .push(Node::Comment(self.rest()[..end].to_string()));
+                self.pos += end + 3;
+            } else if self.rest().starts_with("<![CDATA[") {
+                self.pos += 9;
+                let end = self
+                    .rest()
+                    .find("]]>")
+                    .ok_or(Error::other("Unterminated CDATA"))?;
+                elem.children
+                    .push(Node::CData(self.rest()[..end].to_string()));
+                self.pos += end + 3;
+            } else if self.rest().starts_with('<') {
+                elem.children.push(Node::Element(self.element()?));
+            } else if self.rest().is_empty() {
+                return Err(Error::other(format!("Unclosed element {}", elem.name)));
+            } else {
+                let end = self.rest().find('<').unwrap_or(self.rest().len());
+                let raw = &self.rest()[..end];
+                let text = unescape(raw);
+
+                elem.source
+                    .texts
+                    .push((elem.children.len(), text.clone(), raw.to_string()));
+                elem.children.push(Node::Text(text));
+                self.pos += end;
+            }
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(idx) = rest.find('&') {
+        out.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+
+        let Some(end) = rest.find(';') else { break };
+        let entity = &rest[1..end];
+
+        let decoded = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16)
+                .ok()
+                .and_then(char::from_u32),
+            _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_nested_elements() {
+        let src = r#"<?xml version="1.0"?>
+            <!-- comment -->
+            <OME xmlns="http://www.openmicroscopy.org/Schemas/OME/2016-06">
+                <Image ID="Image:0" Name="a &amp; b">
+                    <Pixels SizeX='10'><Plane TheZ="1"/></Pixels>
+                    <Description>text</Description>
+                </Image>
+            </OME>"#;
+
+        let root = Element::parse(src).unwrap();
+        let image = root.child("Image").unwrap();
+
+        assert_eq!(root.local_name(), "OME");
+        assert_eq!(image.attr("Name"), Some("a & b"));
+        assert_eq!(
+            image.child("Pixels").unwrap().attr_as::<u64>("SizeX"),
+            Some(10)
+        );
+        assert_eq!(image.child("Description").unwrap().text(), "text");
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        let mut root = Element::new("ROI").with_attr("ID", "ROI:0");
+        root.push(Element::new("Label").with_attr("Text", "<\"a\" & b>"));
+
+        let text = root.to_string();
+
+        assert_eq!(Element::parse(&text).unwrap(), root);
+    }
+
+    #[test]
+    fn unmodified_document_is_byte_stable() {
+        let src = "<?xml version='1.0'?>\n<!-- made by hand -->\n\
+            <OME xmlns='http://www.openmicroscopy.org/Schemas/OME/2016-06'\n     \
+            xmlns:lab=\"lab.example\">\n  \
+            <Image  ID = 'Image:0' Name=\"a &#38; b\" >\n    \
+            <Description>x &gt; y<![CDATA[<raw>]]></Description>\n    \
+            <lab:Extension Note='it&apos;s'></lab:Extension>\n    \
+            <Pixels SizeX=\"10\" SizeY=\"20\" />\n  \
+            </Image >\n</OME>\n";
+
+        let mut doc = Document::parse(src).unwrap();
+
+        assert_eq!(doc.to_string(), src);
+        assert_eq!(
+            doc.root
+                .child("Image")
+                .unwrap()
+                .child("Description")
+                .unwrap()
+                .text(),
+            "x > y<raw>"
+        );
+
+        // Editing one attribute only changes that attribute
+        let image = doc.root.elements_mut().next().unwrap();
+        let pixels = image.elements_mut().find(|e| e.name == "Pixels").unwrap();
+        pixels.set_attr("SizeX", 11);
+
+        assert_eq!(doc.to_string(), src.replace("SizeX=\"10\"", "SizeX=\"11\""));
+    }
+
+    #[test]
+    fn parse_rejects_mismatched_tags() {
+        assert!(Element::parse("<a><b></a>").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use std::{fmt::Display, io, str::FromStr};
+
+// Each quantity is a value paired with a unit. Units carry their OME symbol
+// and a factor to the SI base unit; units without a physical scale (e.g.
+// pixels or a stage's reference frame) have no factor and can't be converted.
+macro_rules! quantity {
+    ($quantity:ident, $unit:ident, $default:ident, { $($variant:ident => ($symbol:expr, $factor:expr)),+ $(,)? }) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub enum $unit {
+            $($variant),+
+        }
+
+        impl $unit {
+            pub fn symbol(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $symbol),+
+                }
+            }
+
+            fn factor(&self) -> Option<f64> {
+                match self {
+                    $(Self::$variant => $factor),+
+                }
+            }
+        }
+
+        impl Default for $unit {
+            fn default() -> Self {
+                Self::$default
+            }
+        }
+
+        impl FromStr for $unit {
+            type Err = io::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                // Accept "u" as an ASCII stand-in for the micro sign
+                let s = s.trim().replace('u', "µ");
+                [$(Self::$variant),+]
+                    .into_iter()
+                    .find(|u| u.symbol().replace('u', "µ") == s)
+                    .ok_or(io::Error::other(format!("Unknown unit: {s}")))
+            }
+        }
+
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct $quantity {
+            value: f64,
+            unit: $unit,
+        }
+
+        impl $quantity {
+            pub fn new(value: f64, unit: $unit) -> Self {
+                Self { value, unit }
+            }
+
+            pub fn value(&self) -> f64 {
+                self.value
+            }
+
+            pub fn unit(&self) -> $unit {
+                self.unit
+            }
+
+            // Convert to another unit, None if either unit has no physical scale
+            pub fn to(&self, unit: $unit) -> Option<Self> {
+                if unit == self.unit {
+                    return Some(*self);
+                }
+
+                Some(Self::new(self.value * self.unit.factor()? / unit.factor()?, unit))
+            }
+
+            pub fn value_in(&self, unit: $unit) -> Option<f64> {
+                self.to(unit).map(|q| q.value)
+            }
+        }
+
+        impl Display for $quantity {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{} {}", self.value, self.unit.symbol())
+            }
+        }
+    };
+}
+
+quantity!(Length, LengthUnit, Micrometer, {
+    Meter => ("m", Some(1.0)),
+    Centimeter => ("cm", Some(1e-2)),
+    Millimeter => ("mm", Some(1e-3)),
+    Micrometer => ("µm", Some(1e-6)),
+    Nanometer => ("nm", Some(1e-9)),
+    Picometer => ("pm", Some(1e-12)),
+    Angstrom => ("Å", Some(1e-10)),
+    Inch => ("in", Some(0.0254)),
+    Pixel => ("pixel", None),
+    ReferenceFrame => ("reference frame", None),
+});
+
+quantity!(Time, TimeUnit, Second, {
+    Hour => ("h", Some(3600.0)),
+    Minute => ("min", Some(60.0)),
+    Second => ("s", Some(1.0)),
+    Millisecond => ("ms", Some(1e-3)),
+    Microsecond => ("µs", Some(1e-6)),
+    Nanosecond => ("ns", Some(1e-9)),
+    Picosecond => ("ps", Some(1e-12)),
+});
+
+quantity!(Frequency, FrequencyUnit, Hertz, {
+    Gigahertz => ("GHz", Some(1e9)),
+    Megahertz => ("MHz", Some(1e6)),
+    Kilohertz => ("kHz", Some(1e3)),
+    Hertz => ("Hz", Some(1.0)),
+    Millihertz => ("mHz", Some(1e-3)),
+});
+
+quantity!(ElectricPotential, ElectricPotentialUnit, Volt, {
+    Kilovolt => ("kV", Some(1e3)),
+    Volt => ("V", Some(1.0)),
+    Millivolt => ("mV", Some(1e-3)),
+    Microvolt => ("µV", Some(1e-6)),
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_units() {
+        let l = Length::new(1.5, LengthUnit::Millimeter);
+        let t = Time::new(250.0, TimeUnit::Millisecond);
+
+        assert!((l.value_in(LengthUnit::Micrometer).unwrap() - 1500.0).abs() < 1e-9);
+        assert!((t.value_in(TimeUnit::Second).unwrap() - 0.25).abs() < 1e-12);
+        assert_eq!(
+            Length::new(3.0, LengthUnit::Pixel).to(LengthUnit::Meter),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_symbols() {
+        assert_eq!("um".parse::<LengthUnit>().unwrap(), LengthUnit::Micrometer);
+        assert_eq!("µm".parse::<LengthUnit>().unwrap(), LengthUnit::Micrometer);
+        assert_eq!(
+            "kHz".parse::<FrequencyUnit>().unwrap(),
+            FrequencyUnit::Kilohertz
+        );
+        assert!("parsec".parse::<LengthUnit>().is_err());
+    }
+}